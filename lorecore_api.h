@@ -14,14 +14,51 @@ typedef struct CHistoryItem {
   uint32_t day;
   const char *content;
   const char *properties;
+  /**
+   * 0 for exact dates, 1 for circa, 2 for ranges, 3 for decades and 4 for centuries.
+   */
+  uint32_t precision;
+  /**
+   * The length of a range in years, ignored for other precisions.
+   */
+  uint32_t range_years;
+  int64_t sort_key;
 } CHistoryItem;
 
 typedef struct CEntityRelationship {
   const char *parent;
   const char *child;
   const char *role;
+  /**
+   * If false, the relationship has held since the beginning of time and `start_year` and
+   * `start_day` are ignored.
+   */
+  bool has_start;
+  int32_t start_year;
+  uint32_t start_day;
+  /**
+   * If false, the relationship still holds and `end_year` and `end_day` are ignored.
+   */
+  bool has_end;
+  int32_t end_year;
+  uint32_t end_day;
 } CEntityRelationship;
 
+typedef struct CLabelCount {
+  const char *label;
+  intptr_t count;
+} CLabelCount;
+
+typedef struct CYearCount {
+  int32_t year;
+  intptr_t count;
+} CYearCount;
+
+typedef struct CRoleCount {
+  const char *role;
+  intptr_t count;
+} CRoleCount;
+
 
 
 /**
@@ -36,20 +73,28 @@ const char *write_entity_columns(const char *db_path,
                                  intptr_t size);
 
 /**
+ * Only counts what is visible to `audience`, which is 0 for public, 1 for player-known,
+ * 2 for GM-only and 3 for author-only content.
+ *
  * # Safety
  *
  * `db_path` must be a valid C string.
  * `size` must be a valid pointer to allocated memory of `isize`.
  */
-const char *get_number_of_entity_columns(const char *db_path, intptr_t *size);
+const char *get_number_of_entity_columns(const char *db_path, uint32_t audience, intptr_t *size);
 
 /**
+ * Only reads what is visible to `audience`, which is 0 for public, 1 for player-known,
+ * 2 for GM-only and 3 for author-only content.
+ *
  * # Safety
  *
  * `db_path` must be a valid C string.
  * `columns` must be a valid pointer to an array of `CEntityColumn`s.
  */
-const char *read_entity_columns(const char *db_path, struct CEntityColumn *columns);
+const char *read_entity_columns(const char *db_path,
+                                uint32_t audience,
+                                struct CEntityColumn *columns);
 
 /**
  * # Safety
@@ -63,20 +108,43 @@ const char *write_history_items(const char *db_path,
                                 intptr_t size);
 
 /**
+ * Only counts what is visible to `audience`, which is 0 for public, 1 for player-known,
+ * 2 for GM-only and 3 for author-only content.
+ *
  * # Safety
  *
  * `db_path` must be a valid C string.
  * `size` must be a valid pointer to allocated memory of `isize`.
  */
-const char *get_number_of_history_items(const char *db_path, intptr_t *size);
+const char *get_number_of_history_items(const char *db_path, uint32_t audience, intptr_t *size);
 
 /**
+ * Only reads what is visible to `audience`, which is 0 for public, 1 for player-known,
+ * 2 for GM-only and 3 for author-only content.
+ *
  * # Safety
  *
  * `db_path` must be a valid C string.
  * `items` must be a valid pointer to an array of `CHistoryItem`s.
  */
-const char *read_history_items(const char *db_path, struct CHistoryItem *items);
+const char *read_history_items(const char *db_path, uint32_t audience, struct CHistoryItem *items);
+
+/**
+ * Moves the history item at `timestamp` directly before or, if `after` is set, directly after
+ * the one at `other`. Both have to be dated on the same day.
+ *
+ * # Safety
+ *
+ * `db_path` must be a valid C string.
+ */
+const char *move_history_item(const char *db_path, int64_t timestamp, int64_t other, bool after);
+
+/**
+ * # Safety
+ *
+ * `db_path` must be a valid C string.
+ */
+const char *change_history_item_sort_key(const char *db_path, int64_t timestamp, int64_t sort_key);
 
 /**
  * # Safety
@@ -90,19 +158,131 @@ const char *write_relationships(const char *db_path,
                                 intptr_t size);
 
 /**
+ * Only counts what is visible to `audience`, which is 0 for public, 1 for player-known,
+ * 2 for GM-only and 3 for author-only content.
+ *
  * # Safety
  *
  * `db_path` must be a valid C string.
  * `size` must be a valid pointer to allocated memory of `isize`.
  */
-const char *get_number_of_relationships(const char *db_path, intptr_t *size);
+const char *get_number_of_relationships(const char *db_path, uint32_t audience, intptr_t *size);
 
 /**
+ * Only reads what is visible to `audience`, which is 0 for public, 1 for player-known,
+ * 2 for GM-only and 3 for author-only content.
+ *
  * # Safety
  *
  * `db_path` must be a valid C string.
  * `relationships` must be a valid pointer to an array of `CEntityRelationship`s.
  */
-const char *read_relationships(const char *db_path, struct CEntityRelationship *relationships);
+const char *read_relationships(const char *db_path,
+                               uint32_t audience,
+                               struct CEntityRelationship *relationships);
+
+/**
+ * Only counts what is visible to `audience`, which is 0 for public, 1 for player-known,
+ * 2 for GM-only and 3 for author-only content.
+ *
+ * # Safety
+ *
+ * `db_path` must be a valid C string.
+ * `size` must be a valid pointer to allocated memory of `isize`.
+ */
+const char *get_number_of_labels(const char *db_path, uint32_t audience, intptr_t *size);
+
+/**
+ * Only reads what is visible to `audience`, which is 0 for public, 1 for player-known,
+ * 2 for GM-only and 3 for author-only content.
+ *
+ * # Safety
+ *
+ * `db_path` must be a valid C string.
+ * `counts` must be a valid pointer to an array of `CLabelCount`s.
+ */
+const char *read_descriptor_counts_per_label(const char *db_path,
+                                             uint32_t audience,
+                                             struct CLabelCount *counts);
+
+/**
+ * Only counts what is visible to `audience`, which is 0 for public, 1 for player-known,
+ * 2 for GM-only and 3 for author-only content.
+ *
+ * # Safety
+ *
+ * `db_path` must be a valid C string.
+ * `size` must be a valid pointer to allocated memory of `isize`.
+ */
+const char *get_number_of_years(const char *db_path, uint32_t audience, intptr_t *size);
+
+/**
+ * Only reads what is visible to `audience`, which is 0 for public, 1 for player-known,
+ * 2 for GM-only and 3 for author-only content.
+ *
+ * # Safety
+ *
+ * `db_path` must be a valid C string.
+ * `counts` must be a valid pointer to an array of `CYearCount`s.
+ */
+const char *read_history_item_counts_per_year(const char *db_path,
+                                              uint32_t audience,
+                                              struct CYearCount *counts);
+
+/**
+ * Only counts what is visible to `audience`, which is 0 for public, 1 for player-known,
+ * 2 for GM-only and 3 for author-only content.
+ *
+ * # Safety
+ *
+ * `db_path` must be a valid C string.
+ * `size` must be a valid pointer to allocated memory of `isize`.
+ */
+const char *get_number_of_roles(const char *db_path, uint32_t audience, intptr_t *size);
+
+/**
+ * Only reads what is visible to `audience`, which is 0 for public, 1 for player-known,
+ * 2 for GM-only and 3 for author-only content.
+ *
+ * # Safety
+ *
+ * `db_path` must be a valid C string.
+ * `counts` must be a valid pointer to an array of `CRoleCount`s.
+ */
+const char *read_relationship_counts_per_role(const char *db_path,
+                                              uint32_t audience,
+                                              struct CRoleCount *counts);
+
+/**
+ * Only counts what is visible to `audience`, which is 0 for public, 1 for player-known,
+ * 2 for GM-only and 3 for author-only content.
+ *
+ * # Safety
+ *
+ * `db_path` must be a valid C string.
+ * `label` must be a valid C string.
+ * `size` must be a valid pointer to allocated memory of `isize`.
+ */
+const char *get_number_of_label_suggestions(const char *db_path,
+                                            const char *label,
+                                            intptr_t max_suggestions,
+                                            uint32_t audience,
+                                            intptr_t *size);
+
+/**
+ * Only reads what is visible to `audience`, which is 0 for public, 1 for player-known,
+ * 2 for GM-only and 3 for author-only content.
+ *
+ * # Safety
+ *
+ * `db_path` must be a valid C string.
+ * `label` must be a valid C string.
+ * `suggestions` must be a valid pointer to an array of C strings.
+ */
+const char *read_label_suggestions(const char *db_path,
+                                   const char *label,
+                                   intptr_t max_suggestions,
+                                   uint32_t audience,
+                                   const char **suggestions);
 
 int64_t get_current_timestamp(void);
@@ -0,0 +1,11 @@
+CREATE TABLE roles (
+  role TEXT NOT NULL,
+  inverse TEXT,
+  PRIMARY KEY (role)
+);
+
+CREATE TABLE settings (
+  key TEXT NOT NULL,
+  value TEXT NOT NULL,
+  PRIMARY KEY (key)
+);
\ No newline at end of file
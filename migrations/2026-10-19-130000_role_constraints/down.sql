@@ -0,0 +1 @@
+DROP TABLE IF EXISTS role_constraints;
\ No newline at end of file
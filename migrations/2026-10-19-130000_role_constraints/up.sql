@@ -0,0 +1,8 @@
+CREATE TABLE role_constraints (
+  role TEXT NOT NULL,
+  rule TEXT NOT NULL,
+  max_count INTEGER NOT NULL DEFAULT 0,
+  descriptor TEXT NOT NULL DEFAULT '',
+  description TEXT NOT NULL DEFAULT '',
+  PRIMARY KEY (role, rule, descriptor, description)
+);
\ No newline at end of file
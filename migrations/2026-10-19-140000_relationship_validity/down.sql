@@ -0,0 +1,4 @@
+ALTER TABLE relationships DROP COLUMN end_day;
+ALTER TABLE relationships DROP COLUMN end_year;
+ALTER TABLE relationships DROP COLUMN start_day;
+ALTER TABLE relationships DROP COLUMN start_year;
\ No newline at end of file
@@ -0,0 +1,4 @@
+ALTER TABLE relationships ADD COLUMN start_year INTEGER;
+ALTER TABLE relationships ADD COLUMN start_day INTEGER;
+ALTER TABLE relationships ADD COLUMN end_year INTEGER;
+ALTER TABLE relationships ADD COLUMN end_day INTEGER;
\ No newline at end of file
@@ -0,0 +1,5 @@
+CREATE TABLE calendars (
+  name TEXT NOT NULL,
+  definition TEXT NOT NULL,
+  PRIMARY KEY (name)
+);
\ No newline at end of file
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS eras;
\ No newline at end of file
@@ -0,0 +1,10 @@
+CREATE TABLE eras (
+  name TEXT NOT NULL,
+  abbreviation TEXT NOT NULL,
+  epoch INTEGER NOT NULL,
+  counts_backwards INTEGER NOT NULL,
+  has_year_zero INTEGER NOT NULL,
+  start_year INTEGER,
+  end_year INTEGER,
+  PRIMARY KEY (name)
+);
\ No newline at end of file
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS history_anchors;
\ No newline at end of file
@@ -0,0 +1,7 @@
+CREATE TABLE history_anchors (
+  timestamp BIGINT NOT NULL,
+  anchor BIGINT NOT NULL,
+  year_offset INTEGER NOT NULL,
+  day_offset INTEGER NOT NULL,
+  PRIMARY KEY (timestamp)
+);
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE history_items DROP COLUMN range_years;
+ALTER TABLE history_items DROP COLUMN precision;
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE history_items ADD COLUMN precision TEXT NOT NULL DEFAULT 'exact';
+ALTER TABLE history_items ADD COLUMN range_years INTEGER NOT NULL DEFAULT 0;
\ No newline at end of file
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS timed_entity_columns;
\ No newline at end of file
@@ -0,0 +1,8 @@
+CREATE TABLE timed_entity_columns (
+  label TEXT NOT NULL,
+  descriptor TEXT NOT NULL,
+  description TEXT NOT NULL,
+  start_year INTEGER,
+  end_year INTEGER,
+  PRIMARY KEY (label, descriptor, start_year)
+);
\ No newline at end of file
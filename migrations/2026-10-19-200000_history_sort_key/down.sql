@@ -0,0 +1 @@
+ALTER TABLE history_items DROP COLUMN sort_key;
\ No newline at end of file
@@ -0,0 +1 @@
+ALTER TABLE history_items ADD COLUMN sort_key BIGINT NOT NULL DEFAULT 0;
\ No newline at end of file
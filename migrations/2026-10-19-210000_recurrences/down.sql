@@ -0,0 +1 @@
+DROP TABLE IF EXISTS recurrences;
\ No newline at end of file
@@ -0,0 +1,6 @@
+CREATE TABLE recurrences (
+  timestamp BIGINT NOT NULL,
+  every_years INTEGER NOT NULL,
+  until_year INTEGER,
+  PRIMARY KEY (timestamp)
+);
\ No newline at end of file
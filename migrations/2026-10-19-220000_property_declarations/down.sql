@@ -0,0 +1 @@
+DROP TABLE IF EXISTS property_declarations;
\ No newline at end of file
@@ -0,0 +1,5 @@
+CREATE TABLE property_declarations (
+  key TEXT NOT NULL,
+  value_type TEXT NOT NULL,
+  PRIMARY KEY (key)
+);
\ No newline at end of file
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS description_references;
+DROP TABLE IF EXISTS history_item_references;
\ No newline at end of file
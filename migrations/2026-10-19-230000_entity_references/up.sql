@@ -0,0 +1,13 @@
+CREATE TABLE description_references (
+  label TEXT NOT NULL,
+  descriptor TEXT NOT NULL,
+  target TEXT NOT NULL,
+  PRIMARY KEY (label, descriptor, target)
+);
+CREATE INDEX description_references_target ON description_references (target);
+CREATE TABLE history_item_references (
+  timestamp BIGINT NOT NULL,
+  target TEXT NOT NULL,
+  PRIMARY KEY (timestamp, target)
+);
+CREATE INDEX history_item_references_target ON history_item_references (target);
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE entities DROP COLUMN visibility;
+ALTER TABLE relationships DROP COLUMN visibility;
\ No newline at end of file
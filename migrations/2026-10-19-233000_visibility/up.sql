@@ -0,0 +1,2 @@
+ALTER TABLE entities ADD COLUMN visibility INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE relationships ADD COLUMN visibility INTEGER NOT NULL DEFAULT 0;
\ No newline at end of file
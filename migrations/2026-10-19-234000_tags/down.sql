@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS entity_tags;
+DROP TABLE IF EXISTS history_item_tags;
\ No newline at end of file
@@ -0,0 +1,12 @@
+CREATE TABLE entity_tags (
+  label TEXT NOT NULL,
+  tag TEXT NOT NULL,
+  PRIMARY KEY (label, tag)
+);
+CREATE INDEX entity_tags_tag ON entity_tags (tag);
+CREATE TABLE history_item_tags (
+  timestamp BIGINT NOT NULL,
+  tag TEXT NOT NULL,
+  PRIMARY KEY (timestamp, tag)
+);
+CREATE INDEX history_item_tags_tag ON history_item_tags (tag);
\ No newline at end of file
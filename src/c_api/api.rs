@@ -3,7 +3,10 @@ use crate::timestamp::current_timestamp;
 use super::{
     auxil::char_ptr,
     c_types::*,
-    write_database::{c_write_entity_column, c_write_history_item, c_write_relationship},
+    write_database::{
+        c_change_history_item_sort_key, c_move_history_item, c_write_entity_column,
+        c_write_history_item, c_write_relationship,
+    },
 };
 
 /// # Safety
@@ -25,6 +28,9 @@ pub unsafe extern "C" fn write_entity_columns(
     char_ptr("")
 }
 
+/// Only counts what is visible to `audience`, which is 0 for public, 1 for player-known,
+/// 2 for GM-only and 3 for author-only content.
+///
 /// # Safety
 ///
 /// `db_path` must be a valid C string.
@@ -32,17 +38,21 @@ pub unsafe extern "C" fn write_entity_columns(
 #[no_mangle]
 pub unsafe extern "C" fn get_number_of_entity_columns(
     db_path: *const libc::c_char,
+    audience: u32,
     size: *mut isize,
 ) -> *const libc::c_char {
-    match super::read_database::c_read_entity_columns(db_path) {
-        Ok(cols) => {
-            *size = cols.len() as isize;
+    match super::read_database::c_count_entity_columns(db_path, audience) {
+        Ok(count) => {
+            *size = count;
             char_ptr("")
         }
         Err(e) => char_ptr(&e.to_string()),
     }
 }
 
+/// Only reads what is visible to `audience`, which is 0 for public, 1 for player-known,
+/// 2 for GM-only and 3 for author-only content.
+///
 /// # Safety
 ///
 /// `db_path` must be a valid C string.
@@ -50,9 +60,10 @@ pub unsafe extern "C" fn get_number_of_entity_columns(
 #[no_mangle]
 pub unsafe extern "C" fn read_entity_columns(
     db_path: *const libc::c_char,
+    audience: u32,
     columns: *mut CEntityColumn,
 ) -> *const libc::c_char {
-    match super::read_database::c_read_entity_columns(db_path) {
+    match super::read_database::c_read_entity_columns(db_path, audience) {
         Ok(database_entries) => {
             for (i, _) in database_entries.iter().enumerate() {
                 *columns.add(i) = database_entries[i].clone();
@@ -82,6 +93,9 @@ pub unsafe extern "C" fn write_history_items(
     char_ptr("")
 }
 
+/// Only counts what is visible to `audience`, which is 0 for public, 1 for player-known,
+/// 2 for GM-only and 3 for author-only content.
+///
 /// # Safety
 ///
 /// `db_path` must be a valid C string.
@@ -89,17 +103,21 @@ pub unsafe extern "C" fn write_history_items(
 #[no_mangle]
 pub unsafe extern "C" fn get_number_of_history_items(
     db_path: *const libc::c_char,
+    audience: u32,
     size: *mut isize,
 ) -> *const libc::c_char {
-    match super::read_database::c_read_history_items(db_path) {
-        Ok(items) => {
-            *size = items.len() as isize;
+    match super::read_database::c_count_history_items(db_path, audience) {
+        Ok(count) => {
+            *size = count;
             char_ptr("")
         }
         Err(e) => char_ptr(&e.to_string()),
     }
 }
 
+/// Only reads what is visible to `audience`, which is 0 for public, 1 for player-known,
+/// 2 for GM-only and 3 for author-only content.
+///
 /// # Safety
 ///
 /// `db_path` must be a valid C string.
@@ -107,9 +125,10 @@ pub unsafe extern "C" fn get_number_of_history_items(
 #[no_mangle]
 pub unsafe extern "C" fn read_history_items(
     db_path: *const libc::c_char,
+    audience: u32,
     items: *mut CHistoryItem,
 ) -> *const libc::c_char {
-    match super::read_database::c_read_history_items(db_path) {
+    match super::read_database::c_read_history_items(db_path, audience) {
         Ok(database_entries) => {
             for (i, _) in database_entries.iter().enumerate() {
                 *items.add(i) = database_entries[i].clone();
@@ -120,6 +139,40 @@ pub unsafe extern "C" fn read_history_items(
     }
 }
 
+/// Moves the history item at `timestamp` directly before or, if `after` is set, directly after
+/// the one at `other`. Both have to be dated on the same day.
+///
+/// # Safety
+///
+/// `db_path` must be a valid C string.
+#[no_mangle]
+pub unsafe extern "C" fn move_history_item(
+    db_path: *const libc::c_char,
+    timestamp: i64,
+    other: i64,
+    after: bool,
+) -> *const libc::c_char {
+    match c_move_history_item(db_path, timestamp.into(), other.into(), after) {
+        Ok(()) => char_ptr(""),
+        Err(e) => char_ptr(&e.to_string()),
+    }
+}
+
+/// # Safety
+///
+/// `db_path` must be a valid C string.
+#[no_mangle]
+pub unsafe extern "C" fn change_history_item_sort_key(
+    db_path: *const libc::c_char,
+    timestamp: i64,
+    sort_key: i64,
+) -> *const libc::c_char {
+    match c_change_history_item_sort_key(db_path, timestamp.into(), sort_key) {
+        Ok(()) => char_ptr(""),
+        Err(e) => char_ptr(&e.to_string()),
+    }
+}
+
 /// # Safety
 ///
 /// `db_path` must be a valid C string.
@@ -139,6 +192,9 @@ pub unsafe extern "C" fn write_relationships(
     char_ptr("")
 }
 
+/// Only counts what is visible to `audience`, which is 0 for public, 1 for player-known,
+/// 2 for GM-only and 3 for author-only content.
+///
 /// # Safety
 ///
 /// `db_path` must be a valid C string.
@@ -146,17 +202,21 @@ pub unsafe extern "C" fn write_relationships(
 #[no_mangle]
 pub unsafe extern "C" fn get_number_of_relationships(
     db_path: *const libc::c_char,
+    audience: u32,
     size: *mut isize,
 ) -> *const libc::c_char {
-    match super::read_database::c_read_relationships(db_path) {
-        Ok(relationships) => {
-            *size = relationships.len() as isize;
+    match super::read_database::c_count_relationships(db_path, audience) {
+        Ok(count) => {
+            *size = count;
             char_ptr("")
         }
         Err(e) => char_ptr(&e.to_string()),
     }
 }
 
+/// Only reads what is visible to `audience`, which is 0 for public, 1 for player-known,
+/// 2 for GM-only and 3 for author-only content.
+///
 /// # Safety
 ///
 /// `db_path` must be a valid C string.
@@ -164,9 +224,10 @@ pub unsafe extern "C" fn get_number_of_relationships(
 #[no_mangle]
 pub unsafe extern "C" fn read_relationships(
     db_path: *const libc::c_char,
+    audience: u32,
     relationships: *mut CEntityRelationship,
 ) -> *const libc::c_char {
-    match super::read_database::c_read_relationships(db_path) {
+    match super::read_database::c_read_relationships(db_path, audience) {
         Ok(database_entries) => {
             for (i, _) in database_entries.iter().enumerate() {
                 *relationships.add(i) = database_entries[i].clone();
@@ -177,6 +238,198 @@ pub unsafe extern "C" fn read_relationships(
     }
 }
 
+/// Only counts what is visible to `audience`, which is 0 for public, 1 for player-known,
+/// 2 for GM-only and 3 for author-only content.
+///
+/// # Safety
+///
+/// `db_path` must be a valid C string.
+/// `size` must be a valid pointer to allocated memory of `isize`.
+#[no_mangle]
+pub unsafe extern "C" fn get_number_of_labels(
+    db_path: *const libc::c_char,
+    audience: u32,
+    size: *mut isize,
+) -> *const libc::c_char {
+    match super::read_database::c_read_descriptor_counts_per_label(db_path, audience) {
+        Ok(counts) => {
+            *size = counts.len() as isize;
+            char_ptr("")
+        }
+        Err(e) => char_ptr(&e.to_string()),
+    }
+}
+
+/// Only reads what is visible to `audience`, which is 0 for public, 1 for player-known,
+/// 2 for GM-only and 3 for author-only content.
+///
+/// # Safety
+///
+/// `db_path` must be a valid C string.
+/// `counts` must be a valid pointer to an array of `CLabelCount`s.
+#[no_mangle]
+pub unsafe extern "C" fn read_descriptor_counts_per_label(
+    db_path: *const libc::c_char,
+    audience: u32,
+    counts: *mut CLabelCount,
+) -> *const libc::c_char {
+    match super::read_database::c_read_descriptor_counts_per_label(db_path, audience) {
+        Ok(database_entries) => {
+            for (i, _) in database_entries.iter().enumerate() {
+                *counts.add(i) = database_entries[i].clone();
+            }
+            char_ptr("")
+        }
+        Err(e) => char_ptr(&e.to_string()),
+    }
+}
+
+/// Only counts what is visible to `audience`, which is 0 for public, 1 for player-known,
+/// 2 for GM-only and 3 for author-only content.
+///
+/// # Safety
+///
+/// `db_path` must be a valid C string.
+/// `size` must be a valid pointer to allocated memory of `isize`.
+#[no_mangle]
+pub unsafe extern "C" fn get_number_of_years(
+    db_path: *const libc::c_char,
+    audience: u32,
+    size: *mut isize,
+) -> *const libc::c_char {
+    match super::read_database::c_read_history_item_counts_per_year(db_path, audience) {
+        Ok(counts) => {
+            *size = counts.len() as isize;
+            char_ptr("")
+        }
+        Err(e) => char_ptr(&e.to_string()),
+    }
+}
+
+/// Only reads what is visible to `audience`, which is 0 for public, 1 for player-known,
+/// 2 for GM-only and 3 for author-only content.
+///
+/// # Safety
+///
+/// `db_path` must be a valid C string.
+/// `counts` must be a valid pointer to an array of `CYearCount`s.
+#[no_mangle]
+pub unsafe extern "C" fn read_history_item_counts_per_year(
+    db_path: *const libc::c_char,
+    audience: u32,
+    counts: *mut CYearCount,
+) -> *const libc::c_char {
+    match super::read_database::c_read_history_item_counts_per_year(db_path, audience) {
+        Ok(database_entries) => {
+            for (i, _) in database_entries.iter().enumerate() {
+                *counts.add(i) = database_entries[i].clone();
+            }
+            char_ptr("")
+        }
+        Err(e) => char_ptr(&e.to_string()),
+    }
+}
+
+/// Only counts what is visible to `audience`, which is 0 for public, 1 for player-known,
+/// 2 for GM-only and 3 for author-only content.
+///
+/// # Safety
+///
+/// `db_path` must be a valid C string.
+/// `size` must be a valid pointer to allocated memory of `isize`.
+#[no_mangle]
+pub unsafe extern "C" fn get_number_of_roles(
+    db_path: *const libc::c_char,
+    audience: u32,
+    size: *mut isize,
+) -> *const libc::c_char {
+    match super::read_database::c_read_relationship_counts_per_role(db_path, audience) {
+        Ok(counts) => {
+            *size = counts.len() as isize;
+            char_ptr("")
+        }
+        Err(e) => char_ptr(&e.to_string()),
+    }
+}
+
+/// Only reads what is visible to `audience`, which is 0 for public, 1 for player-known,
+/// 2 for GM-only and 3 for author-only content.
+///
+/// # Safety
+///
+/// `db_path` must be a valid C string.
+/// `counts` must be a valid pointer to an array of `CRoleCount`s.
+#[no_mangle]
+pub unsafe extern "C" fn read_relationship_counts_per_role(
+    db_path: *const libc::c_char,
+    audience: u32,
+    counts: *mut CRoleCount,
+) -> *const libc::c_char {
+    match super::read_database::c_read_relationship_counts_per_role(db_path, audience) {
+        Ok(database_entries) => {
+            for (i, _) in database_entries.iter().enumerate() {
+                *counts.add(i) = database_entries[i].clone();
+            }
+            char_ptr("")
+        }
+        Err(e) => char_ptr(&e.to_string()),
+    }
+}
+
+/// Only counts what is visible to `audience`, which is 0 for public, 1 for player-known,
+/// 2 for GM-only and 3 for author-only content.
+///
+/// # Safety
+///
+/// `db_path` must be a valid C string.
+/// `label` must be a valid C string.
+/// `size` must be a valid pointer to allocated memory of `isize`.
+#[no_mangle]
+pub unsafe extern "C" fn get_number_of_label_suggestions(
+    db_path: *const libc::c_char,
+    label: *const libc::c_char,
+    max_suggestions: isize,
+    audience: u32,
+    size: *mut isize,
+) -> *const libc::c_char {
+    match super::read_database::c_read_label_suggestions(db_path, label, max_suggestions, audience)
+    {
+        Ok(suggestions) => {
+            *size = suggestions.len() as isize;
+            char_ptr("")
+        }
+        Err(e) => char_ptr(&e.to_string()),
+    }
+}
+
+/// Only reads what is visible to `audience`, which is 0 for public, 1 for player-known,
+/// 2 for GM-only and 3 for author-only content.
+///
+/// # Safety
+///
+/// `db_path` must be a valid C string.
+/// `label` must be a valid C string.
+/// `suggestions` must be a valid pointer to an array of C strings.
+#[no_mangle]
+pub unsafe extern "C" fn read_label_suggestions(
+    db_path: *const libc::c_char,
+    label: *const libc::c_char,
+    max_suggestions: isize,
+    audience: u32,
+    suggestions: *mut *const libc::c_char,
+) -> *const libc::c_char {
+    match super::read_database::c_read_label_suggestions(db_path, label, max_suggestions, audience)
+    {
+        Ok(database_entries) => {
+            for (i, _) in database_entries.iter().enumerate() {
+                *suggestions.add(i) = database_entries[i];
+            }
+            char_ptr("")
+        }
+        Err(e) => char_ptr(&e.to_string()),
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn get_current_timestamp() -> i64 {
     current_timestamp().to_int()
@@ -1,4 +1,4 @@
-use crate::errors::LoreCoreError;
+use crate::{errors::LoreCoreError, types::Visibility};
 use std::ffi::{CStr, CString};
 
 /// # Safety
@@ -28,6 +28,25 @@ pub(super) fn char_ptr(message: &str) -> *const libc::c_char {
     CString::new(message).unwrap_or_default().into_raw()
 }
 
+pub(super) fn to_c_count(count: usize) -> Result<isize, LoreCoreError> {
+    isize::try_from(count).map_err(|e| {
+        LoreCoreError::InputError("Count does not fit into isize: ".to_string() + &e.to_string())
+    })
+}
+
+pub(super) fn to_visibility(audience: u32) -> Result<Visibility, LoreCoreError> {
+    match audience {
+        0 => Ok(Visibility::Public),
+        1 => Ok(Visibility::PlayerKnown),
+        2 => Ok(Visibility::GmOnly),
+        3 => Ok(Visibility::AuthorOnly),
+        _ => Err(LoreCoreError::InputError(format!(
+            "Unknown audience {}.",
+            audience
+        ))),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,5 +1,5 @@
 use crate::{
-    c_api::auxil::{char_pointer_to_string, string_to_char_pointer},
+    c_api::auxil::{char_pointer_to_string, string_to_char_pointer, to_c_count},
     errors::LoreCoreError,
     types::*,
 };
@@ -44,6 +44,24 @@ impl TryFrom<&CEntityColumn> for EntityColumn {
     }
 }
 
+#[repr(C)]
+#[derive(Clone)]
+pub struct CLabelCount {
+    pub label: *const libc::c_char,
+    pub count: isize,
+}
+
+impl TryFrom<(Label, usize)> for CLabelCount {
+    type Error = LoreCoreError;
+
+    fn try_from((label, count): (Label, usize)) -> Result<Self, Self::Error> {
+        Ok(CLabelCount {
+            label: string_to_char_pointer(label.to_str()),
+            count: to_c_count(count)?,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
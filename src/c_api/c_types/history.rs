@@ -1,5 +1,5 @@
 use crate::{
-    c_api::auxil::{char_pointer_to_string, string_to_char_pointer},
+    c_api::auxil::{char_pointer_to_string, string_to_char_pointer, to_c_count},
     errors::LoreCoreError,
     types::*,
 };
@@ -12,6 +12,35 @@ pub struct CHistoryItem {
     pub day: u32,
     pub content: *const libc::c_char,
     pub properties: *const libc::c_char,
+    /// 0 for exact dates, 1 for circa, 2 for ranges, 3 for decades and 4 for centuries.
+    pub precision: u32,
+    /// The length of a range in years, ignored for other precisions.
+    pub range_years: u32,
+    pub sort_key: i64,
+}
+
+fn to_c_precision(precision: DatePrecision) -> u32 {
+    match precision {
+        DatePrecision::Exact => 0,
+        DatePrecision::Circa => 1,
+        DatePrecision::Range(_) => 2,
+        DatePrecision::Decade => 3,
+        DatePrecision::Century => 4,
+    }
+}
+
+fn to_precision(precision: u32, range_years: u32) -> Result<DatePrecision, LoreCoreError> {
+    match precision {
+        0 => Ok(DatePrecision::Exact),
+        1 => Ok(DatePrecision::Circa),
+        2 => Ok(DatePrecision::Range(range_years)),
+        3 => Ok(DatePrecision::Decade),
+        4 => Ok(DatePrecision::Century),
+        _ => Err(LoreCoreError::InputError(format!(
+            "Unknown date precision {}.",
+            precision
+        ))),
+    }
 }
 
 fn to_c_history_item(item: &HistoryItem) -> Result<CHistoryItem, LoreCoreError> {
@@ -21,6 +50,9 @@ fn to_c_history_item(item: &HistoryItem) -> Result<CHistoryItem, LoreCoreError>
         day: item.day.to_int(),
         content: string_to_char_pointer(item.content.to_str()),
         properties: string_to_char_pointer(&item.properties.to_string()),
+        precision: to_c_precision(item.precision),
+        range_years: item.precision.range_years(),
+        sort_key: item.sort_key,
     })
 }
 
@@ -38,7 +70,10 @@ unsafe fn to_history_item(item: &CHistoryItem) -> Result<HistoryItem, LoreCoreEr
         year: item.year.into(),
         day: item.day.into(),
         content: char_pointer_to_string(item.content)?.into(),
-        properties: (&char_pointer_to_string(item.properties)?).into(),
+        properties: HistoryItemProperties::try_from(&char_pointer_to_string(item.properties)?)?,
+        precision: to_precision(item.precision, item.range_years)?,
+        sort_key: item.sort_key,
+        is_occurrence: false,
     })
 }
 
@@ -50,6 +85,24 @@ impl TryFrom<&CHistoryItem> for HistoryItem {
     }
 }
 
+#[repr(C)]
+#[derive(Clone)]
+pub struct CYearCount {
+    pub year: i32,
+    pub count: isize,
+}
+
+impl TryFrom<(Year, usize)> for CYearCount {
+    type Error = LoreCoreError;
+
+    fn try_from((year, count): (Year, usize)) -> Result<Self, Self::Error> {
+        Ok(CYearCount {
+            year: year.to_int(),
+            count: to_c_count(count)?,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -61,25 +114,37 @@ mod tests {
         let years = vec![Year(-13), Year(2021)];
         let days = vec![Day(None), Day(Some(14))];
         let contents = vec!["", "Test content", "\\_\"'%$&!{[]}"];
+        let precisions = vec![
+            DatePrecision::Exact,
+            DatePrecision::Range(20),
+            DatePrecision::Century,
+        ];
         let properties = vec![
             HistoryItemProperties::none(),
-            "{\"is_secret\":true}".into(),
-            "{\"additional_concerns\":[\"\\entityref{some_label}\"]}".into(),
+            "{\"is_secret\":true}".try_into().unwrap(),
+            "{\"additional_concerns\":[\"\\\\entityref{some_label}\"]}"
+                .try_into()
+                .unwrap(),
         ];
         for year in years {
             for day in &days {
                 for content in &contents {
                     for property in &properties {
-                        let item_before = HistoryItem {
-                            timestamp: current_timestamp(),
-                            year,
-                            day: *day,
-                            content: (*content).into(),
-                            properties: property.clone(),
-                        };
-                        let c_item = to_c_history_item(&item_before).unwrap();
-                        let item_after = unsafe { to_history_item(&c_item).unwrap() };
-                        assert_eq!(item_before, item_after);
+                        for precision in &precisions {
+                            let item_before = HistoryItem {
+                                timestamp: current_timestamp(),
+                                year,
+                                day: *day,
+                                content: (*content).into(),
+                                properties: property.clone(),
+                                precision: *precision,
+                                sort_key: 3,
+                                is_occurrence: false,
+                            };
+                            let c_item = to_c_history_item(&item_before).unwrap();
+                            let item_after = unsafe { to_history_item(&c_item).unwrap() };
+                            assert_eq!(item_before, item_after);
+                        }
                     }
                 }
             }
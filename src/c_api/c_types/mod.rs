@@ -5,6 +5,6 @@ pub(crate) mod entity;
 pub(crate) mod history;
 pub(crate) mod relationship;
 
-pub use entity::CEntityColumn;
-pub use history::CHistoryItem;
-pub use relationship::CEntityRelationship;
+pub use entity::{CEntityColumn, CLabelCount};
+pub use history::{CHistoryItem, CYearCount};
+pub use relationship::{CEntityRelationship, CRoleCount};
@@ -1,5 +1,5 @@
 use crate::{
-    c_api::auxil::{char_pointer_to_string, string_to_char_pointer},
+    c_api::auxil::{char_pointer_to_string, string_to_char_pointer, to_c_count},
     errors::LoreCoreError,
     types::*,
 };
@@ -10,13 +10,41 @@ pub struct CEntityRelationship {
     pub parent: *const libc::c_char,
     pub child: *const libc::c_char,
     pub role: *const libc::c_char,
+    /// If false, the relationship has held since the beginning of time and `start_year` and
+    /// `start_day` are ignored.
+    pub has_start: bool,
+    pub start_year: i32,
+    pub start_day: u32,
+    /// If false, the relationship still holds and `end_year` and `end_day` are ignored.
+    pub has_end: bool,
+    pub end_year: i32,
+    pub end_day: u32,
+}
+
+fn to_c_date(date: &Option<(Year, Day)>) -> (bool, i32, u32) {
+    match date {
+        Some((year, day)) => (true, year.to_int(), day.to_int()),
+        None => (false, 0, 0),
+    }
+}
+
+fn to_date(has_date: bool, year: i32, day: u32) -> Option<(Year, Day)> {
+    has_date.then(|| (year.into(), day.into()))
 }
 
 fn to_c_relationship(rel: &EntityRelationship) -> Result<CEntityRelationship, LoreCoreError> {
+    let (has_start, start_year, start_day) = to_c_date(&rel.start);
+    let (has_end, end_year, end_day) = to_c_date(&rel.end);
     Ok(CEntityRelationship {
         parent: string_to_char_pointer(rel.parent.to_str()),
         child: string_to_char_pointer(rel.child.to_str()),
         role: string_to_char_pointer(rel.role.to_str()),
+        has_start,
+        start_year,
+        start_day,
+        has_end,
+        end_year,
+        end_day,
     })
 }
 
@@ -33,6 +61,8 @@ unsafe fn to_relationship(rel: &CEntityRelationship) -> Result<EntityRelationshi
         parent: char_pointer_to_string(rel.parent)?.into(),
         child: char_pointer_to_string(rel.child)?.into(),
         role: char_pointer_to_string(rel.role)?.into(),
+        start: to_date(rel.has_start, rel.start_year, rel.start_day),
+        end: to_date(rel.has_end, rel.end_year, rel.end_day),
     })
 }
 
@@ -44,6 +74,24 @@ impl TryFrom<&CEntityRelationship> for EntityRelationship {
     }
 }
 
+#[repr(C)]
+#[derive(Clone)]
+pub struct CRoleCount {
+    pub role: *const libc::c_char,
+    pub count: isize,
+}
+
+impl TryFrom<(Role, usize)> for CRoleCount {
+    type Error = LoreCoreError;
+
+    fn try_from((role, count): (Role, usize)) -> Result<Self, Self::Error> {
+        Ok(CRoleCount {
+            role: string_to_char_pointer(role.to_str()),
+            count: to_c_count(count)?,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -57,17 +105,26 @@ mod tests {
             Some("Test role".to_string()),
             Some("\\_\"'%$&!{[]}".to_string()),
         ];
+        let dates = vec![
+            None,
+            Some((Year::from(-12), Day::NONE)),
+            Some((Year::from(1032), Day::from(7))),
+        ];
         for parent in &parents {
             for child in &children {
                 for role in &roles {
-                    let rel_before = EntityRelationship {
-                        parent: (*parent).into(),
-                        child: (*child).into(),
-                        role: role.clone().into(),
-                    };
-                    let c_rel = to_c_relationship(&rel_before).unwrap();
-                    let rel_after = unsafe { to_relationship(&c_rel).unwrap() };
-                    assert_eq!(rel_before, rel_after);
+                    for date in &dates {
+                        let rel_before = EntityRelationship {
+                            parent: (*parent).into(),
+                            child: (*child).into(),
+                            role: role.clone().into(),
+                            start: *date,
+                            end: *date,
+                        };
+                        let c_rel = to_c_relationship(&rel_before).unwrap();
+                        let rel_after = unsafe { to_relationship(&c_rel).unwrap() };
+                        assert_eq!(rel_before, rel_after);
+                    }
                 }
             }
         }
@@ -1,4 +1,7 @@
-use super::{auxil::char_pointer_to_string, c_types::*};
+use super::{
+    auxil::{char_pointer_to_string, string_to_char_pointer, to_c_count, to_visibility},
+    c_types::*,
+};
 use crate::{
     errors::LoreCoreError,
     sql::{
@@ -11,11 +14,14 @@ use crate::{
 
 pub(super) unsafe fn c_read_entity_columns(
     db_path: *const libc::c_char,
+    audience: u32,
 ) -> Result<Vec<CEntityColumn>, LoreCoreError> {
     let db_path = char_pointer_to_string(db_path)?;
+    let audience = to_visibility(audience)?;
     let db = LoreDatabase::open(db_path.into())?;
     let mut columns = Vec::new();
-    let database_entity_columns = db.read_entity_columns(EntityColumnSearchParams::empty())?;
+    let database_entity_columns =
+        db.read_entity_columns(EntityColumnSearchParams::empty().for_audience(audience))?;
     for col in database_entity_columns {
         columns.push(col.try_into()?);
     }
@@ -24,11 +30,14 @@ pub(super) unsafe fn c_read_entity_columns(
 
 pub(super) unsafe fn c_read_history_items(
     db_path: *const libc::c_char,
+    audience: u32,
 ) -> Result<Vec<CHistoryItem>, LoreCoreError> {
     let db_path = char_pointer_to_string(db_path)?;
+    let audience = to_visibility(audience)?;
     let db = LoreDatabase::open(db_path.into())?;
     let mut items = Vec::new();
-    let history_columns = db.read_history_items(HistoryItemSearchParams::empty())?;
+    let history_columns =
+        db.read_history_items(HistoryItemSearchParams::empty().for_audience(audience))?;
     for col in history_columns {
         items.push(col.try_into()?);
     }
@@ -37,13 +46,117 @@ pub(super) unsafe fn c_read_history_items(
 
 pub(super) unsafe fn c_read_relationships(
     db_path: *const libc::c_char,
+    audience: u32,
 ) -> Result<Vec<CEntityRelationship>, LoreCoreError> {
     let db_path = char_pointer_to_string(db_path)?;
+    let audience = to_visibility(audience)?;
     let db = LoreDatabase::open(db_path.into())?;
     let mut relationships = Vec::new();
-    let relationship_columns = db.read_relationships(RelationshipSearchParams::empty())?;
+    let relationship_columns =
+        db.read_relationships(RelationshipSearchParams::empty().for_audience(audience))?;
     for col in relationship_columns {
         relationships.push(col.try_into()?);
     }
     Ok(relationships)
 }
+
+pub(super) unsafe fn c_count_entity_columns(
+    db_path: *const libc::c_char,
+    audience: u32,
+) -> Result<isize, LoreCoreError> {
+    let db_path = char_pointer_to_string(db_path)?;
+    let audience = to_visibility(audience)?;
+    let db = LoreDatabase::open(db_path.into())?;
+    to_c_count(db.count_entity_columns(EntityColumnSearchParams::empty().for_audience(audience))?)
+}
+
+pub(super) unsafe fn c_count_history_items(
+    db_path: *const libc::c_char,
+    audience: u32,
+) -> Result<isize, LoreCoreError> {
+    let db_path = char_pointer_to_string(db_path)?;
+    let audience = to_visibility(audience)?;
+    let db = LoreDatabase::open(db_path.into())?;
+    to_c_count(db.count_history_items(HistoryItemSearchParams::empty().for_audience(audience))?)
+}
+
+pub(super) unsafe fn c_count_relationships(
+    db_path: *const libc::c_char,
+    audience: u32,
+) -> Result<isize, LoreCoreError> {
+    let db_path = char_pointer_to_string(db_path)?;
+    let audience = to_visibility(audience)?;
+    let db = LoreDatabase::open(db_path.into())?;
+    to_c_count(db.count_relationships(RelationshipSearchParams::empty().for_audience(audience))?)
+}
+
+pub(super) unsafe fn c_read_descriptor_counts_per_label(
+    db_path: *const libc::c_char,
+    audience: u32,
+) -> Result<Vec<CLabelCount>, LoreCoreError> {
+    let db_path = char_pointer_to_string(db_path)?;
+    let audience = to_visibility(audience)?;
+    let db = LoreDatabase::open(db_path.into())?;
+    let mut counts = Vec::new();
+    for count in
+        db.count_descriptors_per_label(EntityColumnSearchParams::empty().for_audience(audience))?
+    {
+        counts.push(count.try_into()?);
+    }
+    Ok(counts)
+}
+
+pub(super) unsafe fn c_read_history_item_counts_per_year(
+    db_path: *const libc::c_char,
+    audience: u32,
+) -> Result<Vec<CYearCount>, LoreCoreError> {
+    let db_path = char_pointer_to_string(db_path)?;
+    let audience = to_visibility(audience)?;
+    let db = LoreDatabase::open(db_path.into())?;
+    let mut counts = Vec::new();
+    for count in
+        db.count_history_items_per_year(HistoryItemSearchParams::empty().for_audience(audience))?
+    {
+        counts.push(count.try_into()?);
+    }
+    Ok(counts)
+}
+
+pub(super) unsafe fn c_read_relationship_counts_per_role(
+    db_path: *const libc::c_char,
+    audience: u32,
+) -> Result<Vec<CRoleCount>, LoreCoreError> {
+    let db_path = char_pointer_to_string(db_path)?;
+    let audience = to_visibility(audience)?;
+    let db = LoreDatabase::open(db_path.into())?;
+    let mut counts = Vec::new();
+    for count in
+        db.count_relationships_per_role(RelationshipSearchParams::empty().for_audience(audience))?
+    {
+        counts.push(count.try_into()?);
+    }
+    Ok(counts)
+}
+
+pub(super) unsafe fn c_read_label_suggestions(
+    db_path: *const libc::c_char,
+    label: *const libc::c_char,
+    max_suggestions: isize,
+    audience: u32,
+) -> Result<Vec<*const libc::c_char>, LoreCoreError> {
+    let db_path = char_pointer_to_string(db_path)?;
+    let audience = to_visibility(audience)?;
+    let label = char_pointer_to_string(label)?;
+    let max_suggestions = usize::try_from(max_suggestions).map_err(|e| {
+        LoreCoreError::InputError(
+            "Maximum number of suggestions must not be negative: ".to_string() + &e.to_string(),
+        )
+    })?;
+    let db = LoreDatabase::open(db_path.into())?;
+    let suggestions = db
+        .suggest_labels(&label, max_suggestions, audience)?
+        .into_iter()
+        .map(|m| string_to_char_pointer(m.candidate.to_str()))
+        .collect();
+    Ok(suggestions)
+}
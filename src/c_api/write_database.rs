@@ -1,5 +1,5 @@
 use super::{auxil::char_pointer_to_string, c_types::*};
-use crate::{errors::LoreCoreError, sql::lore_database::LoreDatabase};
+use crate::{errors::LoreCoreError, sql::lore_database::LoreDatabase, types::Timestamp};
 use std::path::PathBuf;
 
 pub(super) unsafe fn c_write_entity_column(
@@ -37,3 +37,30 @@ pub(super) unsafe fn c_write_relationship(
     db.write_relationships(vec![relationship])?;
     Ok(())
 }
+
+pub(super) unsafe fn c_move_history_item(
+    db_path: *const libc::c_char,
+    timestamp: Timestamp,
+    other: Timestamp,
+    after: bool,
+) -> Result<(), LoreCoreError> {
+    let db_path = char_pointer_to_string(db_path)?;
+    let db_path = PathBuf::from(db_path);
+    let db = LoreDatabase::open(db_path)?;
+    if after {
+        db.move_history_item_after(timestamp, other)
+    } else {
+        db.move_history_item_before(timestamp, other)
+    }
+}
+
+pub(super) unsafe fn c_change_history_item_sort_key(
+    db_path: *const libc::c_char,
+    timestamp: Timestamp,
+    sort_key: i64,
+) -> Result<(), LoreCoreError> {
+    let db_path = char_pointer_to_string(db_path)?;
+    let db_path = PathBuf::from(db_path);
+    let db = LoreDatabase::open(db_path)?;
+    db.change_history_item_sort_key(timestamp, sort_key)
+}
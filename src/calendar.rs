@@ -0,0 +1,669 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    era::{parse_year, Era},
+    errors::LoreCoreError,
+    types::{Day, Year},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Month {
+    pub name: String,
+    pub days: u32,
+    /// Days added to the month in leap years.
+    #[serde(default)]
+    pub leap_days: u32,
+}
+
+/// A named day that belongs to no month, such as "Midyear's Day".
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntercalaryDay {
+    pub name: String,
+    /// The number of months preceding the day, so 0 places it before the first month.
+    pub after_month: usize,
+    #[serde(default)]
+    pub leap_only: bool,
+}
+
+/// Years divisible by `every` are leap years, except for those divisible by `except_every`,
+/// which are leap years again if they are divisible by `unless_every`.
+/// The Gregorian rule is `every: 4, except_every: Some(100), unless_every: Some(400)`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeapRule {
+    pub every: u32,
+    #[serde(default)]
+    pub except_every: Option<u32>,
+    #[serde(default)]
+    pub unless_every: Option<u32>,
+}
+
+impl LeapRule {
+    pub fn is_leap_year(&self, year: i32) -> bool {
+        let divisible = |n: Option<u32>| n.is_some_and(|n| year.rem_euclid(n as i32) == 0);
+        divisible(Some(self.every))
+            && (!divisible(self.except_every) || divisible(self.unless_every))
+    }
+
+    /// The number of leap years in `[0, year)` if `year` is positive, or minus the number of
+    /// leap years in `[year, 0)` otherwise.
+    ///
+    /// Counts by inclusion-exclusion over common multiples, so the divisors need not divide
+    /// each other.
+    fn leap_years_before(&self, year: i32) -> i64 {
+        let multiples = |n: u128| multiples_before(n.min(i64::MAX as u128) as i64, year as i64);
+        let every = self.every as u128;
+        let Some(except_every) = self.except_every else {
+            return multiples(every);
+        };
+        let except_every = lcm(every, except_every as u128);
+        let unless_every = match self.unless_every {
+            Some(unless_every) => multiples(lcm(except_every, unless_every as u128)),
+            None => 0,
+        };
+        multiples(every) - multiples(except_every) + unless_every
+    }
+}
+
+/// Multiples of `n` in `[0, end)`, counted negatively for negative `end`.
+fn multiples_before(n: i64, end: i64) -> i64 {
+    (end - 1).div_euclid(n) + 1
+}
+
+fn lcm(a: u128, b: u128) -> u128 {
+    let (mut x, mut y) = (a, b);
+    while y != 0 {
+        (x, y) = (y, x % y);
+    }
+    a / x * b
+}
+
+/// Where a day of the year lies in a calendar.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CalendarDay<'a> {
+    OfMonth { month: &'a Month, day: u32 },
+    Intercalary(&'a IntercalaryDay),
+}
+
+/// A calendar dividing a year into months and intercalary days. A `Day` is the day of the year,
+/// starting at 1.
+///
+/// Several calendars can describe the same world. Their `epoch`s place the first day of their
+/// year 0 on a shared count of days, which makes dates convertible between them.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Calendar {
+    pub name: String,
+    pub months: Vec<Month>,
+    #[serde(default)]
+    pub weekdays: Vec<String>,
+    #[serde(default)]
+    pub leap_rule: Option<LeapRule>,
+    #[serde(default)]
+    pub intercalary_days: Vec<IntercalaryDay>,
+    #[serde(default)]
+    pub epoch: i64,
+    /// The index into `weekdays` of the first day of year 0.
+    #[serde(default)]
+    pub first_weekday: usize,
+}
+
+enum Segment<'a> {
+    Month(&'a Month),
+    Intercalary(&'a IntercalaryDay),
+}
+
+impl Calendar {
+    /// Fails if the definition cannot describe a year.
+    pub fn check(&self) -> Result<(), LoreCoreError> {
+        let error = |message: String| {
+            Err(LoreCoreError::InputError(format!(
+                "Calendar \"{}\" {}",
+                self.name, message
+            )))
+        };
+        if self.common_year_length() == 0 {
+            return error("has no days in common years.".to_string());
+        }
+        if let Some(month) = self.months.iter().find(|month| month.days == 0) {
+            return error(format!("has an empty month \"{}\".", month.name));
+        }
+        if let Some(day) = self
+            .intercalary_days
+            .iter()
+            .find(|day| day.after_month > self.months.len())
+        {
+            return error(format!(
+                "places \"{}\" after month {} of {}.",
+                day.name,
+                day.after_month,
+                self.months.len()
+            ));
+        }
+        let mut names: Vec<_> = self
+            .months
+            .iter()
+            .map(|month| month.name.to_lowercase())
+            .chain(
+                self.intercalary_days
+                    .iter()
+                    .map(|day| day.name.to_lowercase()),
+            )
+            .collect();
+        names.sort();
+        if let Some(name) = names.windows(2).find(|pair| pair[0] == pair[1]) {
+            return error(format!("uses the name \"{}\" twice.", name[0]));
+        }
+        if let Some(rule) = &self.leap_rule {
+            if [Some(rule.every), rule.except_every, rule.unless_every].contains(&Some(0)) {
+                return error("has a leap rule dividing by 0.".to_string());
+            }
+        }
+        if !self.weekdays.is_empty() && self.first_weekday >= self.weekdays.len() {
+            return error(format!(
+                "starts on weekday {} of {}.",
+                self.first_weekday,
+                self.weekdays.len()
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn is_leap_year(&self, year: Year) -> bool {
+        self.leap_rule
+            .as_ref()
+            .is_some_and(|rule| rule.is_leap_year(year.to_int()))
+    }
+
+    fn common_year_length(&self) -> u32 {
+        let month_days: u32 = self.months.iter().map(|month| month.days).sum();
+        let intercalary_days = self.intercalary_days.iter().filter(|d| !d.leap_only);
+        month_days + intercalary_days.count() as u32
+    }
+
+    fn leap_day_count(&self) -> u32 {
+        let month_days: u32 = self.months.iter().map(|month| month.leap_days).sum();
+        let intercalary_days = self.intercalary_days.iter().filter(|d| d.leap_only);
+        month_days + intercalary_days.count() as u32
+    }
+
+    pub fn year_length(&self, year: Year) -> u32 {
+        if self.is_leap_year(year) {
+            self.common_year_length() + self.leap_day_count()
+        } else {
+            self.common_year_length()
+        }
+    }
+
+    fn segments(&self, year: Year) -> Vec<Segment<'_>> {
+        let is_leap_year = self.is_leap_year(year);
+        let mut segments = Vec::new();
+        for position in 0..=self.months.len() {
+            for day in self.intercalary_days.iter() {
+                if day.after_month == position && (is_leap_year || !day.leap_only) {
+                    segments.push(Segment::Intercalary(day));
+                }
+            }
+            if let Some(month) = self.months.get(position) {
+                segments.push(Segment::Month(month));
+            }
+        }
+        segments
+    }
+
+    fn month_length(&self, month: &Month, year: Year) -> u32 {
+        if self.is_leap_year(year) {
+            month.days + month.leap_days
+        } else {
+            month.days
+        }
+    }
+
+    /// Fails if `day` lies beyond the end of `year`. A missing day is always valid.
+    pub fn validate(&self, year: Year, day: Day) -> Result<(), LoreCoreError> {
+        match day.to_optional_int() {
+            Some(day) if day > self.year_length(year) => Err(LoreCoreError::InputError(format!(
+                "Day {} lies beyond the end of year {}, which has {} days in calendar \"{}\".",
+                day,
+                year,
+                self.year_length(year),
+                self.name
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the month and day of the month, or the intercalary day, that `day` falls on.
+    pub fn calendar_day(
+        &self,
+        year: Year,
+        day: Day,
+    ) -> Result<Option<CalendarDay<'_>>, LoreCoreError> {
+        self.validate(year, day)?;
+        let Some(mut remaining) = day.to_optional_int() else {
+            return Ok(None);
+        };
+        for segment in self.segments(year) {
+            match segment {
+                Segment::Intercalary(intercalary_day) => {
+                    if remaining == 1 {
+                        return Ok(Some(CalendarDay::Intercalary(intercalary_day)));
+                    }
+                    remaining -= 1;
+                }
+                Segment::Month(month) => {
+                    let length = self.month_length(month, year);
+                    if remaining <= length {
+                        return Ok(Some(CalendarDay::OfMonth {
+                            month,
+                            day: remaining,
+                        }));
+                    }
+                    remaining -= length;
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Formats dates like "3rd of Frostmoon, 1032", "Midyear's Day, 1032" or "1032".
+    pub fn format(&self, year: Year, day: Day) -> Result<String, LoreCoreError> {
+        self.format_with_year(year, day, year.to_string())
+    }
+
+    /// Formats dates like [`Calendar::format`], but numbers the year in `era`, as in
+    /// "3rd of Frostmoon, 412 BS".
+    pub fn format_in_era(&self, year: Year, day: Day, era: &Era) -> Result<String, LoreCoreError> {
+        self.format_with_year(year, day, era.format(year))
+    }
+
+    pub(crate) fn format_with_year(
+        &self,
+        year: Year,
+        day: Day,
+        year_text: String,
+    ) -> Result<String, LoreCoreError> {
+        let formatted = match self.calendar_day(year, day)? {
+            Some(CalendarDay::OfMonth { month, day }) => {
+                format!("{} of {}, {}", ordinal(day), month.name, year_text)
+            }
+            Some(CalendarDay::Intercalary(day)) => format!("{}, {}", day.name, year_text),
+            None => year_text,
+        };
+        Ok(formatted)
+    }
+
+    /// Parses dates in the formats produced by [`Calendar::format`].
+    /// Month and intercalary day names are case insensitive, and ordinal suffixes are optional.
+    pub fn parse(&self, text: &str) -> Result<(Year, Day), LoreCoreError> {
+        self.parse_in_eras(text, &[])
+    }
+
+    /// Parses dates like [`Calendar::parse`], but also accepts years numbered in any of `eras`.
+    pub fn parse_in_eras(&self, text: &str, eras: &[Era]) -> Result<(Year, Day), LoreCoreError> {
+        let error = || {
+            LoreCoreError::InputError(format!(
+                "Unable to parse \"{}\" as a date of calendar \"{}\".",
+                text, self.name
+            ))
+        };
+        let (date, year) = match text.rsplit_once(',') {
+            Some((date, year)) => (Some(date.trim()), year.trim()),
+            None => (None, text.trim()),
+        };
+        let year = parse_year(eras, year)?;
+        let Some(date) = date else {
+            return Ok((year, Day::NONE));
+        };
+
+        let mut day_of_year = 0;
+        for segment in self.segments(year) {
+            match segment {
+                Segment::Intercalary(intercalary_day) => {
+                    day_of_year += 1;
+                    if intercalary_day.name.eq_ignore_ascii_case(date) {
+                        return Ok((year, day_of_year.into()));
+                    }
+                }
+                Segment::Month(month) => {
+                    let length = self.month_length(month, year);
+                    if let Some((day, month_name)) = date.split_once(" of ") {
+                        if month.name.eq_ignore_ascii_case(month_name.trim()) {
+                            let day = parse_ordinal(day.trim()).ok_or_else(error)?;
+                            if day == 0 || day > length {
+                                return Err(LoreCoreError::InputError(format!(
+                                    "{} has only {} days in year {}.",
+                                    month.name, length, year
+                                )));
+                            }
+                            return Ok((year, (day_of_year + day).into()));
+                        }
+                    }
+                    day_of_year += length;
+                }
+            }
+        }
+        Err(error())
+    }
+
+    /// The number of days from the first day of year 0 to the first day of `year`,
+    /// negative for years before year 0.
+    fn days_before(&self, year: Year) -> i64 {
+        let leap_years = match &self.leap_rule {
+            Some(rule) => rule.leap_years_before(year.to_int()),
+            None => 0,
+        };
+        year.to_int() as i64 * self.common_year_length() as i64
+            + leap_years * self.leap_day_count() as i64
+    }
+
+    /// Places a date on the day count shared by all calendars.
+    pub fn to_absolute_day(&self, year: Year, day: Day) -> Result<i64, LoreCoreError> {
+        self.validate(year, day)?;
+        let Some(day) = day.to_optional_int() else {
+            return Err(LoreCoreError::InputError(format!(
+                "Year {} needs a day to be placed on the shared day count.",
+                year
+            )));
+        };
+        Ok(self.epoch + self.days_before(year) + day as i64 - 1)
+    }
+
+    pub fn from_absolute_day(&self, absolute_day: i64) -> (Year, Day) {
+        let days = absolute_day - self.epoch;
+        let average_length = self.common_year_length().max(1) as i64;
+        let mut year = days.div_euclid(average_length) as i32;
+        while self.days_before((year + 1).into()) <= days {
+            year += 1;
+        }
+        while self.days_before(year.into()) > days {
+            year -= 1;
+        }
+        let day = days - self.days_before(year.into()) + 1;
+        (year.into(), (day as u32).into())
+    }
+
+    /// Converts a date of this calendar into the same day of `other`.
+    pub fn convert(
+        &self,
+        year: Year,
+        day: Day,
+        other: &Calendar,
+    ) -> Result<(Year, Day), LoreCoreError> {
+        let absolute_day = self.to_absolute_day(year, day)?;
+        Ok(other.from_absolute_day(absolute_day))
+    }
+
+    /// Every day of the year counts towards the week, including intercalary days.
+    /// Returns `None` if the calendar has no weekdays or the day is unknown.
+    pub fn weekday(&self, year: Year, day: Day) -> Result<Option<&str>, LoreCoreError> {
+        if self.weekdays.is_empty() || !day.is_some() {
+            return Ok(None);
+        }
+        let days = self.to_absolute_day(year, day)? - self.epoch;
+        let index = (self.first_weekday as i64 + days).rem_euclid(self.weekdays.len() as i64);
+        Ok(Some(self.weekdays[index as usize].as_str()))
+    }
+}
+
+pub(crate) fn ordinal(n: u32) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
+
+fn parse_ordinal(text: &str) -> Option<u32> {
+    let digits = text.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn month(name: &str, days: u32) -> Month {
+        Month {
+            name: name.to_string(),
+            days,
+            leap_days: 0,
+        }
+    }
+
+    fn example_calendar() -> Calendar {
+        Calendar {
+            name: "Reckoning".to_string(),
+            months: vec![
+                Month {
+                    name: "Frostmoon".to_string(),
+                    days: 30,
+                    leap_days: 1,
+                },
+                month("Thawmoon", 30),
+                month("Harvestmoon", 30),
+            ],
+            weekdays: vec!["Sunday".to_string(), "Moonday".to_string()],
+            leap_rule: Some(LeapRule {
+                every: 4,
+                except_every: Some(100),
+                unless_every: Some(400),
+            }),
+            intercalary_days: vec![
+                IntercalaryDay {
+                    name: "Yule".to_string(),
+                    after_month: 0,
+                    leap_only: false,
+                },
+                IntercalaryDay {
+                    name: "Midyear's Day".to_string(),
+                    after_month: 2,
+                    leap_only: false,
+                },
+                IntercalaryDay {
+                    name: "Overlithe".to_string(),
+                    after_month: 2,
+                    leap_only: true,
+                },
+            ],
+            epoch: 0,
+            first_weekday: 0,
+        }
+    }
+
+    #[test]
+    fn test_leap_rule() {
+        let rule = example_calendar().leap_rule.unwrap();
+        assert!(rule.is_leap_year(4));
+        assert!(rule.is_leap_year(0));
+        assert!(rule.is_leap_year(-4));
+        assert!(!rule.is_leap_year(100));
+        assert!(rule.is_leap_year(400));
+        assert!(!rule.is_leap_year(1033));
+    }
+
+    #[test]
+    fn test_leap_years_before_matches_counting() {
+        let rule = |every, except_every, unless_every| LeapRule {
+            every,
+            except_every,
+            unless_every,
+        };
+        let rules = [
+            example_calendar().leap_rule.unwrap(),
+            rule(4, Some(6), None),
+            rule(4, Some(6), Some(9)),
+            rule(6, Some(4), Some(10)),
+            rule(3, None, Some(5)),
+        ];
+        for rule in rules {
+            for year in -900..900 {
+                let counted = if year >= 0 {
+                    (0..year).filter(|y| rule.is_leap_year(*y)).count() as i64
+                } else {
+                    -((year..0).filter(|y| rule.is_leap_year(*y)).count() as i64)
+                };
+                assert_eq!(
+                    rule.leap_years_before(year),
+                    counted,
+                    "{:?}, year {}",
+                    rule,
+                    year
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_absolute_days_with_non_nested_leap_rule() {
+        let mut calendar = example_calendar();
+        calendar.leap_rule = Some(LeapRule {
+            every: 4,
+            except_every: Some(6),
+            unless_every: None,
+        });
+        assert!(calendar.check().is_ok());
+        let last_of_6 = calendar
+            .to_absolute_day(6.into(), calendar.year_length(6.into()).into())
+            .unwrap();
+        let first_of_7 = calendar.to_absolute_day(7.into(), 1.into()).unwrap();
+        assert_eq!(first_of_7, last_of_6 + 1);
+        for year in [-13, 6, 7, 12, 13] {
+            assert_eq!(
+                calendar
+                    .from_absolute_day(calendar.to_absolute_day(year.into(), 1.into()).unwrap()),
+                (year.into(), 1.into())
+            );
+        }
+    }
+
+    #[test]
+    fn test_year_length() {
+        let calendar = example_calendar();
+        assert_eq!(calendar.year_length(1033.into()), 92);
+        assert_eq!(calendar.year_length(1032.into()), 94);
+        assert!(calendar.validate(1033.into(), 92.into()).is_ok());
+        assert!(calendar.validate(1033.into(), 93.into()).is_err());
+        assert!(calendar.validate(1032.into(), 93.into()).is_ok());
+    }
+
+    #[test]
+    fn test_format() {
+        let calendar = example_calendar();
+        let format = |year: i32, day: u32| calendar.format(year.into(), day.into()).unwrap();
+        assert_eq!(format(1033, 1), "Yule, 1033");
+        assert_eq!(format(1033, 4), "3rd of Frostmoon, 1033");
+        assert_eq!(format(1033, 31), "30th of Frostmoon, 1033");
+        assert_eq!(format(1032, 32), "31st of Frostmoon, 1032");
+        assert_eq!(format(1033, 62), "Midyear's Day, 1033");
+        assert_eq!(format(1032, 64), "Overlithe, 1032");
+        assert_eq!(format(1032, 65), "1st of Harvestmoon, 1032");
+        assert_eq!(format(1032, 0), "1032");
+    }
+
+    #[test]
+    fn test_parse_roundtrips() {
+        let calendar = example_calendar();
+        for year in [-4, 1032, 1033] {
+            for day in 0..=calendar.year_length(year.into()) {
+                let text = calendar.format(year.into(), day.into()).unwrap();
+                assert_eq!(
+                    calendar.parse(&text).unwrap(),
+                    (year.into(), day.into()),
+                    "{}",
+                    text
+                );
+            }
+        }
+        assert_eq!(
+            calendar.parse("3 of frostmoon, 1033").unwrap(),
+            (1033.into(), 4.into())
+        );
+        assert!(calendar.parse("31st of Frostmoon, 1033").is_err());
+        assert!(calendar.parse("Overlithe, 1033").is_err());
+        assert!(calendar.parse("3rd of Snowmoon, 1033").is_err());
+    }
+
+    #[test]
+    fn test_absolute_days_roundtrip() {
+        let calendar = example_calendar();
+        for year in [-401, -1, 0, 1, 1032] {
+            for day in 1..=calendar.year_length(year.into()) {
+                let absolute_day = calendar.to_absolute_day(year.into(), day.into()).unwrap();
+                assert_eq!(
+                    calendar.from_absolute_day(absolute_day),
+                    (year.into(), day.into())
+                );
+            }
+        }
+        assert_eq!(calendar.to_absolute_day(0.into(), 1.into()).unwrap(), 0);
+        assert_eq!(
+            calendar.to_absolute_day((-1).into(), 92.into()).unwrap(),
+            -1
+        );
+    }
+
+    #[test]
+    fn test_convert_between_calendars() {
+        let reckoning = example_calendar();
+        let decimal = Calendar {
+            name: "Decimal".to_string(),
+            months: (1..=10)
+                .map(|i| month(&format!("Month {}", i), 10))
+                .collect(),
+            weekdays: Vec::new(),
+            leap_rule: None,
+            intercalary_days: Vec::new(),
+            epoch: 94,
+            first_weekday: 0,
+        };
+        assert_eq!(
+            reckoning.convert(1.into(), 1.into(), &decimal).unwrap(),
+            (0.into(), 1.into())
+        );
+        let (year, day) = reckoning.convert(1032.into(), 17.into(), &decimal).unwrap();
+        assert_eq!(
+            decimal.convert(year, day, &reckoning).unwrap(),
+            (1032.into(), 17.into())
+        );
+    }
+
+    #[test]
+    fn test_weekday() {
+        let calendar = example_calendar();
+        assert_eq!(
+            calendar.weekday(0.into(), 1.into()).unwrap(),
+            Some("Sunday")
+        );
+        assert_eq!(
+            calendar.weekday(0.into(), 2.into()).unwrap(),
+            Some("Moonday")
+        );
+        assert_eq!(
+            calendar.weekday((-1).into(), 92.into()).unwrap(),
+            Some("Moonday")
+        );
+        assert_eq!(calendar.weekday(0.into(), Day::NONE).unwrap(), None);
+    }
+
+    #[test]
+    fn test_check() {
+        assert!(example_calendar().check().is_ok());
+        let mut calendar = example_calendar();
+        calendar.intercalary_days[0].name = "frostmoon".to_string();
+        assert!(calendar.check().is_err());
+        let mut calendar = example_calendar();
+        calendar.intercalary_days[0].after_month = 4;
+        assert!(calendar.check().is_err());
+        let mut calendar = example_calendar();
+        calendar.leap_rule.as_mut().unwrap().every = 0;
+        assert!(calendar.check().is_err());
+        let mut calendar = example_calendar();
+        calendar.months.clear();
+        calendar.intercalary_days.retain(|day| day.leap_only);
+        calendar.intercalary_days[0].after_month = 0;
+        calendar.leap_rule = None;
+        assert!(calendar.check().is_err());
+    }
+}
@@ -0,0 +1,182 @@
+use crate::{errors::LoreCoreError, types::Year};
+
+/// A named way of numbering years, such as "412 BS" for 412 years before the Sundering.
+///
+/// `epoch` is the year, in the numbering used by the database, that is year 1 of the era, or
+/// year 0 if the era has one. Eras counting backwards number the years before their epoch.
+/// `start` and `end` optionally bound the years for which the era is used by default.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Era {
+    pub name: String,
+    pub abbreviation: String,
+    pub epoch: Year,
+    pub counts_backwards: bool,
+    pub has_year_zero: bool,
+    pub start: Option<Year>,
+    pub end: Option<Year>,
+}
+
+impl Era {
+    pub fn contains(&self, year: Year) -> bool {
+        self.start.is_none_or(|start| start <= year) && self.end.is_none_or(|end| year <= end)
+    }
+
+    /// The number of `year` in this era. Years before year 1 of an era without year 0 are
+    /// numbered -1, -2 and so on.
+    pub fn era_year(&self, year: Year) -> i32 {
+        let offset = if self.counts_backwards {
+            self.epoch.to_int() - year.to_int()
+        } else {
+            year.to_int() - self.epoch.to_int()
+        };
+        if self.has_year_zero || offset < 0 {
+            offset
+        } else {
+            offset + 1
+        }
+    }
+
+    /// The year in the numbering of the database that has the number `era_year` in this era.
+    pub fn year(&self, era_year: i32) -> Result<Year, LoreCoreError> {
+        let offset = match era_year {
+            0 if !self.has_year_zero => {
+                return Err(LoreCoreError::InputError(format!(
+                    "The era \"{}\" has no year 0.",
+                    self.name
+                )))
+            }
+            n if n > 0 && !self.has_year_zero => n - 1,
+            n => n,
+        };
+        if self.counts_backwards {
+            Ok(Year::from(self.epoch.to_int() - offset))
+        } else {
+            Ok(Year::from(self.epoch.to_int() + offset))
+        }
+    }
+
+    /// Formats years like "412 BS".
+    pub fn format(&self, year: Year) -> String {
+        format!("{} {}", self.era_year(year), self.abbreviation)
+    }
+
+    /// Parses years formatted by [`Era::format`], also accepting the full name of the era.
+    /// Returns `None` if the text does not end with the abbreviation or name of this era.
+    pub fn parse(&self, text: &str) -> Option<Result<Year, LoreCoreError>> {
+        let text = text.trim();
+        let number = [&self.abbreviation, &self.name]
+            .iter()
+            .filter(|suffix| !suffix.is_empty())
+            .find_map(|suffix| strip_suffix_ignore_case(text, suffix))?;
+        let number = number.trim();
+        let result = match number.parse::<i32>() {
+            Ok(era_year) => self.year(era_year),
+            Err(_) => Err(LoreCoreError::InputError(format!(
+                "Unable to parse \"{}\" as a year of the era \"{}\".",
+                number, self.name
+            ))),
+        };
+        Some(result)
+    }
+}
+
+fn strip_suffix_ignore_case<'a>(text: &'a str, suffix: &str) -> Option<&'a str> {
+    let split = text.len().checked_sub(suffix.len())?;
+    if !text.is_char_boundary(split) || !text[split..].eq_ignore_ascii_case(suffix) {
+        return None;
+    }
+    let number = &text[..split];
+    number.ends_with(char::is_whitespace).then_some(number)
+}
+
+/// The first era in `eras` containing `year`.
+pub fn era_for(eras: &[Era], year: Year) -> Option<&Era> {
+    eras.iter().find(|era| era.contains(year))
+}
+
+/// Formats `year` in the era containing it, or as a plain number if no era does.
+pub fn format_year(eras: &[Era], year: Year) -> String {
+    match era_for(eras, year) {
+        Some(era) => era.format(year),
+        None => year.to_string(),
+    }
+}
+
+/// Parses a year formatted in any of `eras`, or a plain number.
+pub fn parse_year(eras: &[Era], text: &str) -> Result<Year, LoreCoreError> {
+    eras.iter()
+        .find_map(|era| era.parse(text))
+        .unwrap_or_else(|| Year::try_from(text.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn before_sundering() -> Era {
+        Era {
+            name: "Before the Sundering".to_string(),
+            abbreviation: "BS".to_string(),
+            epoch: (-1).into(),
+            counts_backwards: true,
+            has_year_zero: false,
+            start: None,
+            end: Some((-1).into()),
+        }
+    }
+
+    fn age_of_ash() -> Era {
+        Era {
+            name: "Age of Ash".to_string(),
+            abbreviation: "AA".to_string(),
+            epoch: 0.into(),
+            counts_backwards: false,
+            has_year_zero: true,
+            start: Some(0.into()),
+            end: None,
+        }
+    }
+
+    #[test]
+    fn test_era_years() {
+        assert_eq!(before_sundering().era_year((-1).into()), 1);
+        assert_eq!(before_sundering().era_year((-412).into()), 412);
+        assert_eq!(before_sundering().era_year(0.into()), -1);
+        assert_eq!(age_of_ash().era_year(0.into()), 0);
+        assert_eq!(age_of_ash().era_year((-3).into()), -3);
+
+        let mut no_zero = age_of_ash();
+        no_zero.has_year_zero = false;
+        assert_eq!(no_zero.era_year(0.into()), 1);
+        assert_eq!(no_zero.era_year((-1).into()), -1);
+    }
+
+    #[test]
+    fn test_years_roundtrip() {
+        for era in [before_sundering(), age_of_ash()] {
+            for year in -20..20 {
+                let era_year = era.era_year(year.into());
+                assert_eq!(era.year(era_year).unwrap(), year.into());
+            }
+        }
+        assert!(before_sundering().year(0).is_err());
+    }
+
+    #[test]
+    fn test_format_and_parse() {
+        let eras = vec![before_sundering(), age_of_ash()];
+        assert_eq!(format_year(&eras, (-412).into()), "412 BS");
+        assert_eq!(format_year(&eras, 1032.into()), "1032 AA");
+        assert_eq!(parse_year(&eras, "412 BS").unwrap(), (-412).into());
+        assert_eq!(parse_year(&eras, "412 bs").unwrap(), (-412).into());
+        assert_eq!(
+            parse_year(&eras, "412 Before the Sundering").unwrap(),
+            (-412).into()
+        );
+        assert_eq!(parse_year(&eras, "1032 AA").unwrap(), 1032.into());
+        assert_eq!(parse_year(&eras, "-7").unwrap(), (-7).into());
+        assert!(parse_year(&eras, "0 BS").is_err());
+        assert!(parse_year(&eras, "many BS").is_err());
+        assert!(parse_year(&eras, "412BS").is_err());
+    }
+}
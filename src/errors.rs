@@ -1,14 +1,23 @@
 use std::fmt::{Debug, Display};
 
+use crate::types::ConstraintViolation;
+
 #[derive(Debug, Clone)]
 pub enum LoreCoreError {
     FileError(String),
     InputError(String),
     SqlError(String),
+    ConstraintError(Vec<ConstraintViolation>),
 }
 impl Display for LoreCoreError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        match self {
+            LoreCoreError::ConstraintError(violations) => {
+                let messages: Vec<_> = violations.iter().map(|v| v.to_string()).collect();
+                write!(f, "Role constraints violated: {}", messages.join(" "))
+            }
+            _ => write!(f, "{:?}", self),
+        }
     }
 }
 
@@ -28,6 +28,49 @@ pub fn extract_days(items: &[HistoryItem]) -> Vec<Day> {
     days
 }
 
+const ENTITY_REFERENCE_START: &str = "\\entityref{";
+
+/// Returns the labels of all `\entityref{label}` occurrences in `text`, in order of appearance.
+pub fn extract_entity_references(text: &str) -> Vec<Label> {
+    let mut labels = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(ENTITY_REFERENCE_START) {
+        rest = &rest[start + ENTITY_REFERENCE_START.len()..];
+        match rest.find('}') {
+            Some(end) => {
+                labels.push(rest[..end].into());
+                rest = &rest[end + 1..];
+            }
+            None => break,
+        }
+    }
+    labels
+}
+
+/// Replaces a reference to a hidden entity.
+pub const REDACTED: &str = "[redacted]";
+
+/// Replaces every `\entityref{label}` in `text` whose label is hidden by [`REDACTED`].
+pub fn redact_entity_references(text: &str, is_hidden: impl Fn(&Label) -> bool) -> String {
+    let mut redacted = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(ENTITY_REFERENCE_START) {
+        let after_start = &rest[start + ENTITY_REFERENCE_START.len()..];
+        let Some(end) = after_start.find('}') else {
+            break;
+        };
+        redacted.push_str(&rest[..start]);
+        if is_hidden(&after_start[..end].into()) {
+            redacted.push_str(REDACTED);
+        } else {
+            redacted.push_str(&rest[start..start + ENTITY_REFERENCE_START.len() + end + 1]);
+        }
+        rest = &after_start[end + 1..];
+    }
+    redacted.push_str(rest);
+    redacted
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -82,27 +125,9 @@ mod tests {
     fn test_extract_years() {
         use super::*;
         let items = vec![
-            HistoryItem {
-                timestamp: 0.into(),
-                year: 2021.into(),
-                day: Day::NONE,
-                content: "".into(),
-                properties: HistoryItemProperties::none(),
-            },
-            HistoryItem {
-                timestamp: 0.into(),
-                year: 2020.into(),
-                day: Day::NONE,
-                content: "".into(),
-                properties: HistoryItemProperties::none(),
-            },
-            HistoryItem {
-                timestamp: 0.into(),
-                year: 2020.into(),
-                day: 4.into(),
-                content: "".into(),
-                properties: HistoryItemProperties::none(),
-            },
+            HistoryItem::new(0.into(), 2021.into(), Day::NONE, "".into()),
+            HistoryItem::new(0.into(), 2020.into(), Day::NONE, "".into()),
+            HistoryItem::new(0.into(), 2020.into(), 4.into(), "".into()),
         ];
         let years = extract_years(&items);
         assert!(years == vec![2020.into(), 2021.into()]);
@@ -112,36 +137,35 @@ mod tests {
     fn test_extract_days() {
         use super::*;
         let items = vec![
-            HistoryItem {
-                timestamp: 0.into(),
-                year: 2020.into(),
-                day: 2.into(),
-                content: "".into(),
-                properties: HistoryItemProperties::none(),
-            },
-            HistoryItem {
-                timestamp: 0.into(),
-                year: 2020.into(),
-                day: 1.into(),
-                content: "".into(),
-                properties: HistoryItemProperties::none(),
-            },
-            HistoryItem {
-                timestamp: 0.into(),
-                year: 2020.into(),
-                day: 1.into(),
-                content: "".into(),
-                properties: HistoryItemProperties::none(),
-            },
-            HistoryItem {
-                timestamp: 0.into(),
-                year: 2020.into(),
-                day: Day::NONE,
-                content: "".into(),
-                properties: HistoryItemProperties::none(),
-            },
+            HistoryItem::new(0.into(), 2020.into(), 2.into(), "".into()),
+            HistoryItem::new(0.into(), 2020.into(), 1.into(), "".into()),
+            HistoryItem::new(0.into(), 2020.into(), 1.into(), "".into()),
+            HistoryItem::new(0.into(), 2020.into(), Day::NONE, "".into()),
         ];
         let days = extract_days(&items);
         assert!(days == vec![Day::NONE, 1.into(), 2.into()]);
     }
+
+    #[test]
+    fn test_extract_entity_references() {
+        let text = "\\entityref{a} met \\entityref{b_c} and \\entityref{a}. \\entityref{unclosed";
+        let labels = extract_entity_references(text);
+        assert_eq!(labels, vec!["a".into(), "b_c".into(), "a".into()]);
+    }
+
+    #[test]
+    fn test_redact_entity_references() {
+        let text = "\\entityref{a} met \\entityref{b} and \\entityref{a}. \\entityref{b";
+        assert_eq!(
+            redact_entity_references(text, |label| label.to_str() == "b"),
+            "\\entityref{a} met [redacted] and \\entityref{a}. \\entityref{b"
+        );
+        assert_eq!(redact_entity_references(text, |_| false), text);
+    }
+
+    #[test]
+    fn test_extract_entity_references_without_references() {
+        assert!(extract_entity_references("").is_empty());
+        assert!(extract_entity_references("\\ref{a} and {b}").is_empty());
+    }
 }
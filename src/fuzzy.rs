@@ -0,0 +1,147 @@
+use std::{collections::BTreeSet, fmt::Display};
+
+/// Candidates scoring below this similarity are not suggested.
+pub(crate) const MIN_SIMILARITY: f64 = 0.5;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FuzzyMatch<T> {
+    pub candidate: T,
+    pub score: f64,
+}
+
+/// The number of single character insertions, deletions and substitutions needed to turn `a` into `b`.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut previous_row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.iter().enumerate() {
+        let mut current_row = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution_cost = if a_char == b_char { 0 } else { 1 };
+            let value = (previous_row[j] + substitution_cost)
+                .min(previous_row[j + 1] + 1)
+                .min(current_row[j] + 1);
+            current_row.push(value);
+        }
+        previous_row = current_row;
+    }
+    previous_row[b.len()]
+}
+
+/// Case insensitive edit distance, normalised to a value between 0 (nothing in common) and 1 (identical).
+pub fn edit_similarity(a: &str, b: &str) -> f64 {
+    let a = a.to_lowercase();
+    let b = b.to_lowercase();
+    let max_len = a.chars().count().max(b.chars().count());
+    if max_len == 0 {
+        return 1.0;
+    }
+    1.0 - edit_distance(&a, &b) as f64 / max_len as f64
+}
+
+fn tokens(text: &str) -> BTreeSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_lowercase())
+        .collect()
+}
+
+/// Jaccard similarity of the case insensitive words in `a` and `b`.
+/// Words are separated by any non-alphanumeric character, so `Gandalf_the_Grey` and `gandalf the grey` are identical.
+pub fn token_similarity(a: &str, b: &str) -> f64 {
+    let a = tokens(a);
+    let b = tokens(b);
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    let intersection = a.intersection(&b).count();
+    let union = a.union(&b).count();
+    intersection as f64 / union as f64
+}
+
+pub fn similarity(a: &str, b: &str) -> f64 {
+    edit_similarity(a, b).max(token_similarity(a, b))
+}
+
+/// Returns at most `max_matches` candidates that are similar to `query`, best match first.
+pub fn rank_matches<T>(query: &str, candidates: &[T], max_matches: usize) -> Vec<FuzzyMatch<T>>
+where
+    T: Clone + Display,
+{
+    let mut matches: Vec<_> = candidates
+        .iter()
+        .map(|candidate| FuzzyMatch {
+            candidate: candidate.clone(),
+            score: similarity(query, &candidate.to_string()),
+        })
+        .filter(|m| m.score >= MIN_SIMILARITY)
+        .collect();
+    matches.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.candidate.to_string().cmp(&b.candidate.to_string()))
+    });
+    matches.truncate(max_matches);
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("abc", ""), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("Gandalf", "Gandalf"), 0);
+        assert_eq!(edit_distance("Gandlaf", "Gandalf"), 2);
+        assert_eq!(edit_distance("Eyjafjallajökull", "Eyjafjallajokull"), 1);
+    }
+
+    #[test]
+    fn test_edit_similarity_ignores_case() {
+        assert_eq!(edit_similarity("GANDALF", "gandalf"), 1.0);
+        assert_eq!(edit_similarity("", ""), 1.0);
+        assert_eq!(edit_similarity("abcd", "wxyz"), 0.0);
+    }
+
+    #[test]
+    fn test_token_similarity() {
+        assert_eq!(
+            token_similarity("Gandalf_the_Grey", "gandalf the grey"),
+            1.0
+        );
+        assert_eq!(
+            token_similarity("the_grey_Gandalf", "Gandalf_the_Grey"),
+            1.0
+        );
+        assert_eq!(
+            token_similarity("Gandalf_the_Grey", "Gandalf_the_White"),
+            0.5
+        );
+        assert_eq!(token_similarity("Gandalf", "Saruman"), 0.0);
+    }
+
+    #[test]
+    fn test_rank_matches() {
+        let candidates = vec![
+            "Saruman".to_string(),
+            "Gandalf_the_White".to_string(),
+            "Gandalf_the_Grey".to_string(),
+            "Grey_Havens".to_string(),
+        ];
+        let matches = rank_matches("gandalf_the_gray", &candidates, 5);
+        let ranked: Vec<_> = matches.iter().map(|m| m.candidate.as_str()).collect();
+        assert_eq!(ranked, vec!["Gandalf_the_Grey", "Gandalf_the_White"]);
+    }
+
+    #[test]
+    fn test_rank_matches_truncates() {
+        let candidates = vec!["aab".to_string(), "aaa".to_string(), "aac".to_string()];
+        let matches = rank_matches("aaa", &candidates, 2);
+        let ranked: Vec<_> = matches.iter().map(|m| m.candidate.as_str()).collect();
+        assert_eq!(ranked, vec!["aaa", "aab"]);
+    }
+}
@@ -1,8 +1,11 @@
 #![warn(clippy::unwrap_used)]
 
 pub mod c_api;
+pub mod calendar;
+pub mod era;
 pub mod errors;
 pub mod extractions;
+pub mod fuzzy;
 pub mod sql;
 pub mod timestamp;
 pub mod types;
@@ -0,0 +1,283 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use crate::{
+    errors::LoreCoreError,
+    extractions::{extract_entity_references, extract_labels},
+    types::*,
+};
+
+use super::{
+    lore_database::LoreDatabase,
+    relationship::{extract_children, extract_parents},
+    search_params::{EntityColumnSearchParams, HistoryItemSearchParams, RelationshipSearchParams},
+};
+
+/// An entity ranked by how central it is in the relationship graph.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hub {
+    pub label: Label,
+    /// The number of relationships the entity is the parent or child of.
+    pub degree: usize,
+    /// The number of shortest paths between other entities that pass through this one,
+    /// with paths of equal length sharing their weight. Directions of relationships are ignored.
+    pub betweenness: f64,
+}
+
+/// An overview of the relationship graph, meant to point editors to neglected parts of the world.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GraphReport {
+    /// Groups of entities connected by relationships, regardless of their direction.
+    /// Largest groups come first, entities without relationships form groups of their own.
+    pub components: Vec<Vec<Label>>,
+    /// Entities that are neither parent nor child of any relationship.
+    pub isolated_entities: Vec<Label>,
+    /// Entities with relationships, sorted by descending degree and betweenness.
+    pub hubs: Vec<Hub>,
+    /// Entities with columns that are neither the child of any relationship nor referenced by
+    /// `\entityref` in the descriptions of other entities or in history items.
+    pub unreferenced_entities: Vec<Label>,
+}
+
+impl LoreDatabase {
+    /// Analyses the relationship graph as far as it is visible to `audience`, listing at most
+    /// `max_hubs` hubs.
+    pub fn read_graph_report(
+        &self,
+        max_hubs: usize,
+        audience: Visibility,
+    ) -> Result<GraphReport, LoreCoreError> {
+        let cols =
+            self.read_entity_columns(EntityColumnSearchParams::empty().for_audience(audience))?;
+        let items =
+            self.read_history_items(HistoryItemSearchParams::empty().for_audience(audience))?;
+        let rels =
+            self.read_relationships(RelationshipSearchParams::empty().for_audience(audience))?;
+
+        let labels = extract_labels(&cols);
+        let parents = extract_parents(&rels);
+        let children = extract_children(&rels);
+
+        let mut nodes: BTreeSet<Label> = labels.iter().cloned().collect();
+        nodes.extend(parents.iter().map(|parent| parent.to_str().into()));
+        nodes.extend(children.iter().map(|child| child.to_str().into()));
+        let nodes: Vec<_> = nodes.into_iter().collect();
+
+        let isolated_entities = nodes
+            .iter()
+            .filter(|label| {
+                !parents
+                    .iter()
+                    .any(|parent| parent.to_str() == label.to_str())
+                    && !children
+                        .iter()
+                        .any(|child| child.to_str() == label.to_str())
+            })
+            .cloned()
+            .collect();
+
+        let mut hubs = hubs(&nodes, &rels);
+        hubs.truncate(max_hubs);
+
+        let mut referenced: BTreeSet<Label> =
+            children.iter().map(|child| child.to_str().into()).collect();
+        for col in cols.iter() {
+            referenced.extend(
+                extract_entity_references(col.description.to_str())
+                    .into_iter()
+                    .filter(|label| label != &col.label),
+            );
+        }
+        for item in items.iter() {
+            referenced.extend(item.entity_references());
+        }
+        let unreferenced_entities = labels
+            .into_iter()
+            .filter(|label| !referenced.contains(label))
+            .collect();
+
+        Ok(GraphReport {
+            components: connected_components(&nodes, &rels),
+            isolated_entities,
+            hubs,
+            unreferenced_entities,
+        })
+    }
+}
+
+fn neighbours(nodes: &[Label], rels: &[EntityRelationship]) -> Vec<BTreeSet<usize>> {
+    let index: BTreeMap<&str, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, label)| (label.to_str(), i))
+        .collect();
+    let mut neighbours = vec![BTreeSet::new(); nodes.len()];
+    for rel in rels.iter() {
+        if let (Some(&parent), Some(&child)) = (
+            index.get(rel.parent.to_str()),
+            index.get(rel.child.to_str()),
+        ) {
+            if parent != child {
+                neighbours[parent].insert(child);
+                neighbours[child].insert(parent);
+            }
+        }
+    }
+    neighbours
+}
+
+/// Splits `nodes` into groups connected by `rels`, ignoring the direction of relationships.
+/// Each group is sorted, larger groups come first and groups of equal size are sorted by label.
+pub fn connected_components(nodes: &[Label], rels: &[EntityRelationship]) -> Vec<Vec<Label>> {
+    let neighbours = neighbours(nodes, rels);
+    let mut visited = vec![false; nodes.len()];
+    let mut components = Vec::new();
+    for start in 0..nodes.len() {
+        if visited[start] {
+            continue;
+        }
+        visited[start] = true;
+        let mut component = Vec::new();
+        let mut queue = VecDeque::from([start]);
+        while let Some(node) = queue.pop_front() {
+            component.push(nodes[node].clone());
+            for &next in neighbours[node].iter() {
+                if !visited[next] {
+                    visited[next] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+        component.sort();
+        components.push(component);
+    }
+    components.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    components
+}
+
+/// Ranks all nodes with at least one relationship by degree, then betweenness, then label.
+pub fn hubs(nodes: &[Label], rels: &[EntityRelationship]) -> Vec<Hub> {
+    let betweenness = betweenness(nodes, rels);
+    let mut hubs: Vec<_> = nodes
+        .iter()
+        .zip(betweenness)
+        .map(|(label, betweenness)| Hub {
+            label: label.clone(),
+            degree: rels
+                .iter()
+                .filter(|rel| {
+                    rel.parent.to_str() == label.to_str() || rel.child.to_str() == label.to_str()
+                })
+                .count(),
+            betweenness,
+        })
+        .filter(|hub| hub.degree > 0)
+        .collect();
+    hubs.sort_by(|a, b| {
+        b.degree
+            .cmp(&a.degree)
+            .then_with(|| b.betweenness.total_cmp(&a.betweenness))
+            .then_with(|| a.label.cmp(&b.label))
+    });
+    hubs
+}
+
+/// Brandes' algorithm on the undirected, unweighted graph spanned by `rels`.
+fn betweenness(nodes: &[Label], rels: &[EntityRelationship]) -> Vec<f64> {
+    let neighbours = neighbours(nodes, rels);
+    let mut centrality = vec![0.0; nodes.len()];
+    for source in 0..nodes.len() {
+        let mut stack = Vec::new();
+        let mut predecessors = vec![Vec::new(); nodes.len()];
+        let mut path_counts = vec![0.0; nodes.len()];
+        let mut distances: Vec<Option<usize>> = vec![None; nodes.len()];
+        path_counts[source] = 1.0;
+        distances[source] = Some(0);
+        let mut queue = VecDeque::from([source]);
+        while let Some(node) = queue.pop_front() {
+            stack.push(node);
+            let distance = distances[node].unwrap_or_default();
+            for &next in neighbours[node].iter() {
+                if distances[next].is_none() {
+                    distances[next] = Some(distance + 1);
+                    queue.push_back(next);
+                }
+                if distances[next] == Some(distance + 1) {
+                    path_counts[next] += path_counts[node];
+                    predecessors[next].push(node);
+                }
+            }
+        }
+        let mut dependencies = vec![0.0; nodes.len()];
+        while let Some(node) = stack.pop() {
+            for &predecessor in predecessors[node].iter() {
+                dependencies[predecessor] +=
+                    path_counts[predecessor] / path_counts[node] * (1.0 + dependencies[node]);
+            }
+            if node != source {
+                centrality[node] += dependencies[node];
+            }
+        }
+    }
+    // Every path was counted once from each of its ends.
+    centrality.into_iter().map(|c| c / 2.0).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rel(parent: &str, child: &str) -> EntityRelationship {
+        EntityRelationship::new(parent.into(), child.into(), Role::NONE)
+    }
+
+    fn labels(labels: &[&str]) -> Vec<Label> {
+        labels.iter().map(|label| (*label).into()).collect()
+    }
+
+    #[test]
+    fn test_connected_components() {
+        let nodes = labels(&["a", "b", "c", "d", "e", "f"]);
+        let rels = vec![rel("a", "b"), rel("c", "b"), rel("e", "d")];
+        assert_eq!(
+            connected_components(&nodes, &rels),
+            vec![
+                labels(&["a", "b", "c"]),
+                labels(&["d", "e"]),
+                labels(&["f"])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_betweenness_of_a_path() {
+        let nodes = labels(&["a", "b", "c", "d"]);
+        let rels = vec![rel("a", "b"), rel("b", "c"), rel("c", "d")];
+        assert_eq!(betweenness(&nodes, &rels), vec![0.0, 2.0, 2.0, 0.0]);
+    }
+
+    #[test]
+    fn test_betweenness_splits_equal_paths() {
+        let nodes = labels(&["a", "b", "c", "d"]);
+        let rels = vec![rel("a", "b"), rel("a", "c"), rel("b", "d"), rel("c", "d")];
+        assert_eq!(betweenness(&nodes, &rels), vec![0.5, 0.5, 0.5, 0.5]);
+    }
+
+    #[test]
+    fn test_hubs_are_ranked_by_degree_then_betweenness() {
+        let nodes = labels(&["a", "b", "c", "d", "e"]);
+        let rels = vec![rel("a", "b"), rel("b", "c"), rel("c", "d"), rel("a", "a")];
+        let ranked: Vec<_> = hubs(&nodes, &rels)
+            .into_iter()
+            .map(|hub| (hub.label.to_string(), hub.degree))
+            .collect();
+        assert_eq!(
+            ranked,
+            vec![
+                ("b".to_string(), 2),
+                ("c".to_string(), 2),
+                ("a".to_string(), 2),
+                ("d".to_string(), 1)
+            ]
+        );
+    }
+}
@@ -0,0 +1,293 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    fmt::Display,
+};
+
+use ::diesel::prelude::*;
+
+use crate::{
+    calendar::Calendar,
+    errors::{sql_loading_error, LoreCoreError},
+    types::*,
+};
+
+use super::{
+    history::set_history_item_dates, lore_database::LoreDatabase, schema::history_anchors,
+    search_params::HistoryItemSearchParams, sql_types::SqlHistoryAnchor,
+};
+
+/// An anchor whose dates cannot be derived.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AnchorProblem {
+    /// The history item at `timestamp` is anchored to a history item that does not exist.
+    DanglingAnchor {
+        timestamp: Timestamp,
+        anchor: Timestamp,
+    },
+    /// The history items are anchored to each other in a circle, starting with the smallest
+    /// timestamp. Each item is anchored to the next one, the last to the first.
+    AnchorCycle(Vec<Timestamp>),
+}
+
+impl Display for AnchorProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnchorProblem::DanglingAnchor { timestamp, anchor } => write!(
+                f,
+                "History item {} is anchored to the missing history item {}.",
+                timestamp, anchor
+            ),
+            AnchorProblem::AnchorCycle(timestamps) => {
+                let timestamps: Vec<_> = timestamps.iter().map(|t| t.to_string()).collect();
+                write!(
+                    f,
+                    "History items {} are anchored to each other in a cycle.",
+                    timestamps.join(", ")
+                )
+            }
+        }
+    }
+}
+
+impl LoreDatabase {
+    /// Dates the history item at `timestamp` relative to another one, replacing any previous
+    /// anchor. The date of the item and of all items anchored to it is recomputed immediately.
+    pub fn write_history_anchor(
+        &self,
+        timestamp: Timestamp,
+        anchor: HistoryAnchor,
+    ) -> Result<(), LoreCoreError> {
+        let dates = self.read_history_dates()?;
+        if !dates.contains_key(&timestamp) {
+            return Err(LoreCoreError::InputError(format!(
+                "History item {} does not exist.",
+                timestamp
+            )));
+        }
+        let Some(&(anchor_year, anchor_day)) = dates.get(&anchor.anchor) else {
+            return Err(LoreCoreError::InputError(format!(
+                "Cannot anchor history item {} to the missing history item {}.",
+                timestamp, anchor.anchor
+            )));
+        };
+        let mut anchors: BTreeMap<_, _> = self.read_history_anchors()?.into_iter().collect();
+        anchors.insert(timestamp, anchor);
+        if let Some(cycle) = find_cycle(&anchors, timestamp) {
+            return Err(LoreCoreError::InputError(
+                AnchorProblem::AnchorCycle(cycle).to_string(),
+            ));
+        }
+        let calendar = self.read_default_calendar()?;
+        let (year, day) = anchor.apply(anchor_year, anchor_day, calendar.as_ref())?;
+        let anchors: Vec<_> = anchors.into_iter().collect();
+        let new_dates = propagate_dates(&anchors, timestamp, year, day, calendar.as_ref())?;
+
+        let mut connection = self.db_connection()?;
+        connection
+            .transaction(|connection| {
+                diesel::replace_into(history_anchors::table)
+                    .values(&anchor.to_sql_history_anchor(timestamp))
+                    .execute(connection)?;
+                set_history_item_dates(connection, new_dates)
+            })
+            .map_err(|e: diesel::result::Error| {
+                LoreCoreError::SqlError(
+                    "Writing history anchor to database failed: ".to_string() + &e.to_string(),
+                )
+            })
+    }
+
+    /// Returns all anchors, sorted by the timestamp of the anchored history item.
+    pub fn read_history_anchors(&self) -> Result<Vec<(Timestamp, HistoryAnchor)>, LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        let anchors = history_anchors::table
+            .order(history_anchors::timestamp)
+            .load::<SqlHistoryAnchor>(&mut connection)
+            .map_err(|e| sql_loading_error("history anchors", vec![], e))?
+            .iter()
+            .map(|anchor| anchor.to_history_anchor())
+            .collect();
+        Ok(anchors)
+    }
+
+    pub fn read_history_anchor(
+        &self,
+        timestamp: Timestamp,
+    ) -> Result<Option<HistoryAnchor>, LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        let anchor = history_anchors::table
+            .filter(history_anchors::timestamp.eq(timestamp.to_int()))
+            .load::<SqlHistoryAnchor>(&mut connection)
+            .map_err(|e| sql_loading_error("history anchor", vec![("timestamp", &timestamp)], e))?
+            .pop()
+            .map(|anchor| anchor.to_history_anchor().1);
+        Ok(anchor)
+    }
+
+    /// Detaches the history item at `timestamp` from its anchor. It keeps its current date.
+    pub fn delete_history_anchor(&self, timestamp: Timestamp) -> Result<(), LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        diesel::delete(
+            history_anchors::table.filter(history_anchors::timestamp.eq(timestamp.to_int())),
+        )
+        .execute(&mut connection)
+        .map_err(|e| {
+            LoreCoreError::SqlError(
+                "Deleting history anchor from database failed: ".to_string() + &e.to_string(),
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Finds anchors to deleted history items and anchors forming cycles.
+    pub fn find_anchor_problems(&self) -> Result<Vec<AnchorProblem>, LoreCoreError> {
+        let anchors = self.read_history_anchors()?;
+        let timestamps: BTreeSet<_> = self.read_history_dates()?.into_keys().collect();
+        Ok(anchor_problems(&anchors, &timestamps))
+    }
+
+    /// The new dates of the history item at `timestamp` and of all items anchored to it,
+    /// directly or indirectly, if the item is moved to `year` and `day`.
+    pub(super) fn anchored_dates(
+        &self,
+        timestamp: Timestamp,
+        year: Year,
+        day: Day,
+    ) -> Result<Vec<(Timestamp, Year, Day)>, LoreCoreError> {
+        let anchors = self.read_history_anchors()?;
+        let calendar = self.read_default_calendar()?;
+        propagate_dates(&anchors, timestamp, year, day, calendar.as_ref())
+    }
+
+    fn read_history_dates(&self) -> Result<BTreeMap<Timestamp, (Year, Day)>, LoreCoreError> {
+        let dates = self
+            .read_history_items(HistoryItemSearchParams::empty())?
+            .into_iter()
+            .map(|item| (item.timestamp, (item.year, item.day)))
+            .collect();
+        Ok(dates)
+    }
+}
+
+/// Dates `root` at `year` and `day` and derives the dates of everything anchored to it,
+/// see [`HistoryAnchor::apply`]. Anchors forming a cycle with `root` are not followed back to it.
+pub fn propagate_dates(
+    anchors: &[(Timestamp, HistoryAnchor)],
+    root: Timestamp,
+    year: Year,
+    day: Day,
+    calendar: Option<&Calendar>,
+) -> Result<Vec<(Timestamp, Year, Day)>, LoreCoreError> {
+    let mut dependents: BTreeMap<Timestamp, Vec<(Timestamp, &HistoryAnchor)>> = BTreeMap::new();
+    for (timestamp, anchor) in anchors.iter() {
+        dependents
+            .entry(anchor.anchor)
+            .or_default()
+            .push((*timestamp, anchor));
+    }
+    let mut visited = BTreeSet::from([root]);
+    let mut dates = vec![(root, year, day)];
+    let mut queue = VecDeque::from([(root, year, day)]);
+    while let Some((timestamp, year, day)) = queue.pop_front() {
+        for &(dependent, anchor) in dependents.get(&timestamp).into_iter().flatten() {
+            if visited.insert(dependent) {
+                let (year, day) = anchor.apply(year, day, calendar)?;
+                dates.push((dependent, year, day));
+                queue.push_back((dependent, year, day));
+            }
+        }
+    }
+    Ok(dates)
+}
+
+/// Finds anchors to missing `timestamps` and all cycles among `anchors`.
+pub fn anchor_problems(
+    anchors: &[(Timestamp, HistoryAnchor)],
+    timestamps: &BTreeSet<Timestamp>,
+) -> Vec<AnchorProblem> {
+    let anchor_map: BTreeMap<_, _> = anchors.iter().cloned().collect();
+    let mut problems = Vec::new();
+    for (timestamp, anchor) in anchors.iter() {
+        if !timestamps.contains(&anchor.anchor) {
+            problems.push(AnchorProblem::DanglingAnchor {
+                timestamp: *timestamp,
+                anchor: anchor.anchor,
+            });
+        }
+        if let Some(cycle) = find_cycle(&anchor_map, *timestamp) {
+            if cycle[0] == *timestamp {
+                problems.push(AnchorProblem::AnchorCycle(cycle));
+            }
+        }
+    }
+    problems.sort();
+    problems
+}
+
+/// The cycle that `start` is part of, beginning with its smallest timestamp.
+fn find_cycle(
+    anchors: &BTreeMap<Timestamp, HistoryAnchor>,
+    start: Timestamp,
+) -> Option<Vec<Timestamp>> {
+    let mut chain = vec![start];
+    let mut current = start;
+    while let Some(anchor) = anchors.get(&current) {
+        current = anchor.anchor;
+        if current == start {
+            let smallest = (0..chain.len()).min_by_key(|&i| chain[i])?;
+            chain.rotate_left(smallest);
+            return Some(chain);
+        }
+        if chain.contains(&current) {
+            return None;
+        }
+        chain.push(current);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anchored(timestamp: i64, anchor: i64, days: i32) -> (Timestamp, HistoryAnchor) {
+        (timestamp.into(), HistoryAnchor::new(anchor.into(), 0, days))
+    }
+
+    #[test]
+    fn test_propagate_dates() {
+        let anchors = vec![anchored(2, 1, 3), anchored(3, 2, 1), anchored(4, 1, 10)];
+        let dates = propagate_dates(&anchors, 1.into(), 100.into(), 5.into(), None).unwrap();
+        assert_eq!(
+            dates,
+            vec![
+                (1.into(), 100.into(), 5.into()),
+                (2.into(), 100.into(), 8.into()),
+                (4.into(), 100.into(), 15.into()),
+                (3.into(), 100.into(), 9.into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_anchor_problems() {
+        let anchors = vec![
+            anchored(1, 3, 1),
+            anchored(2, 1, 1),
+            anchored(3, 2, 1),
+            anchored(4, 1, 1),
+            anchored(5, 9, 1),
+        ];
+        let timestamps = (1..=5).map(Timestamp::from).collect();
+        assert_eq!(
+            anchor_problems(&anchors, &timestamps),
+            vec![
+                AnchorProblem::DanglingAnchor {
+                    timestamp: 5.into(),
+                    anchor: 9.into()
+                },
+                AnchorProblem::AnchorCycle(vec![1.into(), 3.into(), 2.into()]),
+            ]
+        );
+    }
+}
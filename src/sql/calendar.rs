@@ -0,0 +1,118 @@
+use ::diesel::prelude::*;
+
+use crate::{
+    calendar::Calendar,
+    errors::{sql_loading_error, LoreCoreError},
+    types::*,
+};
+
+use super::{
+    lore_database::LoreDatabase, schema::calendars, search_params::HistoryItemSearchParams,
+    sql_types::SqlCalendar,
+};
+
+const DEFAULT_CALENDAR_KEY: &str = "default_calendar";
+
+impl LoreDatabase {
+    /// Stores a calendar under its name. Fails if the definition is inconsistent
+    /// or a calendar with the same name exists.
+    pub fn write_calendar(&self, calendar: Calendar) -> Result<(), LoreCoreError> {
+        calendar.check()?;
+        let calendar = calendar.to_sql_calendar()?;
+        let mut connection = self.db_connection()?;
+        diesel::insert_into(calendars::table)
+            .values(&calendar)
+            .execute(&mut connection)
+            .map_err(|e| {
+                LoreCoreError::SqlError(
+                    "Writing calendar to database failed: ".to_string() + &e.to_string(),
+                )
+            })?;
+        Ok(())
+    }
+
+    pub fn read_calendar(&self, name: &str) -> Result<Calendar, LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        let calendar = calendars::table
+            .filter(calendars::name.eq(name))
+            .load::<SqlCalendar>(&mut connection)
+            .map_err(|e| sql_loading_error("calendar", vec![("name", &name)], e))?
+            .pop();
+        match calendar {
+            Some(calendar) => calendar.to_calendar(),
+            None => Err(LoreCoreError::InputError(format!(
+                "Calendar \"{}\" does not exist.",
+                name
+            ))),
+        }
+    }
+
+    /// Returns all calendars, sorted by name.
+    pub fn read_calendars(&self) -> Result<Vec<Calendar>, LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        calendars::table
+            .order(calendars::name)
+            .load::<SqlCalendar>(&mut connection)
+            .map_err(|e| sql_loading_error("calendars", vec![], e))?
+            .iter()
+            .map(|calendar| calendar.to_calendar())
+            .collect()
+    }
+
+    pub fn delete_calendar(&self, name: &str) -> Result<(), LoreCoreError> {
+        if self.read_setting(DEFAULT_CALENDAR_KEY)?.as_deref() == Some(name) {
+            self.set_default_calendar(None)?;
+        }
+        let mut connection = self.db_connection()?;
+        diesel::delete(calendars::table.filter(calendars::name.eq(name)))
+            .execute(&mut connection)
+            .map_err(|e| {
+                LoreCoreError::SqlError(
+                    "Deleting calendar from database failed: ".to_string() + &e.to_string(),
+                )
+            })?;
+        Ok(())
+    }
+
+    /// Configures the calendar that dates are checked against, or none if `name` is `None`.
+    pub fn set_default_calendar(&self, name: Option<&str>) -> Result<(), LoreCoreError> {
+        if let Some(name) = name {
+            self.read_calendar(name)?;
+        }
+        self.write_setting(DEFAULT_CALENDAR_KEY, name.unwrap_or_default())
+    }
+
+    pub fn read_default_calendar(&self) -> Result<Option<Calendar>, LoreCoreError> {
+        match self.read_setting(DEFAULT_CALENDAR_KEY)? {
+            Some(name) if !name.is_empty() => Ok(Some(self.read_calendar(&name)?)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Converts a date between two stored calendars.
+    pub fn convert_date(
+        &self,
+        year: Year,
+        day: Day,
+        from_calendar: &str,
+        to_calendar: &str,
+    ) -> Result<(Year, Day), LoreCoreError> {
+        let from_calendar = self.read_calendar(from_calendar)?;
+        let to_calendar = self.read_calendar(to_calendar)?;
+        from_calendar.convert(year, day, &to_calendar)
+    }
+
+    /// Returns the history items whose day lies beyond the end of their year in the calendar.
+    pub fn find_history_items_with_invalid_days(
+        &self,
+        calendar: &str,
+    ) -> Result<Vec<HistoryItem>, LoreCoreError> {
+        let calendar = self.read_calendar(calendar)?;
+        let items = self
+            .read_history_items(HistoryItemSearchParams::empty())?
+            .into_iter()
+            .filter(|item| calendar.validate(item.year, item.day).is_err())
+            .collect();
+        Ok(items)
+    }
+}
@@ -0,0 +1,321 @@
+use std::{collections::HashMap, fmt::Display};
+
+use crate::{calendar::Calendar, errors::LoreCoreError, types::*};
+
+use super::{
+    lifespan::lifespans,
+    lore_database::LoreDatabase,
+    search_params::{
+        HistoryItemSearchParams, RelationshipSearchParams, RelationshipTraversalParams,
+    },
+};
+
+/// A contradiction between the dates of history items, lifespans and relationships.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TimelineIssue {
+    /// The history item references the entity before it was born or founded.
+    BeforeStart {
+        timestamp: Timestamp,
+        label: Label,
+        start: LifeEvent,
+    },
+    /// The history item references the entity after it died or was dissolved.
+    AfterEnd {
+        timestamp: Timestamp,
+        label: Label,
+        end: LifeEvent,
+    },
+    /// The entity ends before it starts.
+    EndBeforeStart {
+        label: Label,
+        start: LifeEvent,
+        end: LifeEvent,
+    },
+    /// The child of a followed relationship was born before its parent.
+    ChildBeforeParent {
+        parent: Label,
+        child: Label,
+        parent_start: LifeEvent,
+        child_start: LifeEvent,
+    },
+    /// The relationship starts or ends while the entity `label` does not exist, according to
+    /// the life event `event`.
+    RelationshipOutsideLifespan {
+        relationship: EntityRelationship,
+        label: Label,
+        event: LifeEvent,
+    },
+    /// The day of the history item lies beyond the end of its year in the default calendar.
+    DayBeyondYear {
+        timestamp: Timestamp,
+        year: Year,
+        day: u32,
+        year_length: u32,
+    },
+}
+
+impl TimelineIssue {
+    /// The timestamps of the history items involved in the issue.
+    pub fn timestamps(&self) -> Vec<Timestamp> {
+        match self {
+            TimelineIssue::BeforeStart {
+                timestamp, start, ..
+            } => vec![*timestamp, start.timestamp],
+            TimelineIssue::AfterEnd { timestamp, end, .. } => vec![*timestamp, end.timestamp],
+            TimelineIssue::EndBeforeStart { start, end, .. } => {
+                vec![start.timestamp, end.timestamp]
+            }
+            TimelineIssue::ChildBeforeParent {
+                parent_start,
+                child_start,
+                ..
+            } => vec![parent_start.timestamp, child_start.timestamp],
+            TimelineIssue::RelationshipOutsideLifespan { event, .. } => vec![event.timestamp],
+            TimelineIssue::DayBeyondYear { timestamp, .. } => vec![*timestamp],
+        }
+    }
+
+    /// The labels of the entities involved in the issue.
+    pub fn labels(&self) -> Vec<Label> {
+        match self {
+            TimelineIssue::BeforeStart { label, .. }
+            | TimelineIssue::AfterEnd { label, .. }
+            | TimelineIssue::EndBeforeStart { label, .. } => vec![label.clone()],
+            TimelineIssue::ChildBeforeParent { parent, child, .. } => {
+                vec![parent.clone(), child.clone()]
+            }
+            TimelineIssue::RelationshipOutsideLifespan { relationship, .. } => vec![
+                relationship.parent.to_str().into(),
+                relationship.child.to_str().into(),
+            ],
+            TimelineIssue::DayBeyondYear { .. } => Vec::new(),
+        }
+    }
+}
+
+impl Display for TimelineIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimelineIssue::BeforeStart {
+                timestamp,
+                label,
+                start,
+            } => write!(
+                f,
+                "History item {} mentions \"{}\" before its start in history item {}.",
+                timestamp, label, start.timestamp
+            ),
+            TimelineIssue::AfterEnd {
+                timestamp,
+                label,
+                end,
+            } => write!(
+                f,
+                "History item {} mentions \"{}\" after its end in history item {}.",
+                timestamp, label, end.timestamp
+            ),
+            TimelineIssue::EndBeforeStart { label, start, end } => write!(
+                f,
+                "\"{}\" ends in history item {} before it starts in history item {}.",
+                label, end.timestamp, start.timestamp
+            ),
+            TimelineIssue::ChildBeforeParent { parent, child, .. } => {
+                write!(f, "\"{}\" starts before its parent \"{}\".", child, parent)
+            }
+            TimelineIssue::RelationshipOutsideLifespan {
+                relationship,
+                label,
+                event,
+            } => write!(
+                f,
+                "The relationship \"{}\" -[{}]-> \"{}\" is dated while \"{}\" does not exist \
+                 according to history item {}.",
+                relationship.parent, relationship.role, relationship.child, label, event.timestamp
+            ),
+            TimelineIssue::DayBeyondYear {
+                timestamp,
+                year,
+                day,
+                year_length,
+            } => write!(
+                f,
+                "History item {} is dated on day {} of year {}, which has only {} days.",
+                timestamp, day, year, year_length
+            ),
+        }
+    }
+}
+
+impl LoreDatabase {
+    /// Cross-references history items, lifespans and relationships, see [`timeline_issues`].
+    /// Days are checked against the default calendar, if one is set. Only what is visible to
+    /// `audience` is checked, so that the issues do not reveal hidden entities.
+    pub fn check_timeline(
+        &self,
+        params: RelationshipTraversalParams,
+        audience: Visibility,
+    ) -> Result<Vec<TimelineIssue>, LoreCoreError> {
+        let items =
+            self.read_history_items(HistoryItemSearchParams::empty().for_audience(audience))?;
+        let rels =
+            self.read_relationships(RelationshipSearchParams::empty().for_audience(audience))?;
+        let calendar = self.read_default_calendar()?;
+        Ok(timeline_issues(&items, &rels, &params, calendar.as_ref()))
+    }
+}
+
+/// Finds all contradictions between the dates of `items` and `rels`. Parents have to start
+/// before their children for the relationships followed according to `params`.
+/// Uncertain dates are only flagged if all of their possible years are contradictory.
+pub fn timeline_issues(
+    items: &[HistoryItem],
+    rels: &[EntityRelationship],
+    params: &RelationshipTraversalParams,
+    calendar: Option<&Calendar>,
+) -> Vec<TimelineIssue> {
+    let lifespans = lifespans(items);
+    let items_by_timestamp: HashMap<Timestamp, &HistoryItem> =
+        items.iter().map(|item| (item.timestamp, item)).collect();
+    let earliest = |event: &LifeEvent| match items_by_timestamp.get(&event.timestamp) {
+        Some(item) => earliest_date(item),
+        None => (event.year, event.day),
+    };
+    let latest = |event: &LifeEvent| match items_by_timestamp.get(&event.timestamp) {
+        Some(item) => latest_date(item),
+        None => (event.year, event.day),
+    };
+    let mut issues = Vec::new();
+
+    for (label, lifespan) in lifespans.iter() {
+        if let (Some(start), Some(end)) = (lifespan.start, lifespan.end) {
+            if precedes(latest(&end), earliest(&start)) {
+                issues.push(TimelineIssue::EndBeforeStart {
+                    label: label.clone(),
+                    start,
+                    end,
+                });
+            }
+        }
+    }
+
+    for item in items.iter() {
+        for label in item.entity_references() {
+            let Some(lifespan) = lifespans.get(&label) else {
+                continue;
+            };
+            if let Some(start) = lifespan.start {
+                if start.timestamp != item.timestamp
+                    && precedes(latest_date(item), earliest(&start))
+                {
+                    issues.push(TimelineIssue::BeforeStart {
+                        timestamp: item.timestamp,
+                        label: label.clone(),
+                        start,
+                    });
+                }
+            }
+            if let Some(end) = lifespan.end {
+                if end.timestamp != item.timestamp && precedes(latest(&end), earliest_date(item)) {
+                    issues.push(TimelineIssue::AfterEnd {
+                        timestamp: item.timestamp,
+                        label,
+                        end,
+                    });
+                }
+            }
+        }
+        if let (Some(calendar), Some(day)) = (calendar, item.day.to_optional_int()) {
+            if calendar.validate(item.year, item.day).is_err() {
+                issues.push(TimelineIssue::DayBeyondYear {
+                    timestamp: item.timestamp,
+                    year: item.year,
+                    day,
+                    year_length: calendar.year_length(item.year),
+                });
+            }
+        }
+    }
+
+    for rel in rels.iter() {
+        let parent = lifespans.get(&Label::from(rel.parent.to_str()));
+        let child = lifespans.get(&Label::from(rel.child.to_str()));
+        if params.follows(rel) {
+            let parent_start = parent.and_then(|lifespan| lifespan.start);
+            let child_start = child.and_then(|lifespan| lifespan.start);
+            if let (Some(parent_start), Some(child_start)) = (parent_start, child_start) {
+                if precedes(latest(&child_start), earliest(&parent_start)) {
+                    issues.push(TimelineIssue::ChildBeforeParent {
+                        parent: rel.parent.to_str().into(),
+                        child: rel.child.to_str().into(),
+                        parent_start,
+                        child_start,
+                    });
+                }
+            }
+        }
+        for (label, lifespan) in [(rel.parent.to_str(), parent), (rel.child.to_str(), child)] {
+            let Some(lifespan) = lifespan else {
+                continue;
+            };
+            let dates = [rel.start, rel.end].into_iter().flatten();
+            let start = lifespan.start.filter(|start| {
+                dates
+                    .clone()
+                    .any(|rel_date| precedes(rel_date, earliest(start)))
+            });
+            let end = lifespan.end.filter(|end| {
+                dates
+                    .clone()
+                    .any(|rel_date| precedes(latest(end), rel_date))
+            });
+            if let Some(event) = start.or(end) {
+                issues.push(TimelineIssue::RelationshipOutsideLifespan {
+                    relationship: rel.clone(),
+                    label: label.into(),
+                    event,
+                });
+            }
+        }
+    }
+
+    issues.sort();
+    issues
+}
+
+/// The earliest date the item may have happened on. Days of uncertain dates are ignored.
+fn earliest_date(item: &HistoryItem) -> (Year, Day) {
+    (item.earliest_year(), certain_day(item))
+}
+
+/// The latest date the item may have happened on. Days of uncertain dates are ignored.
+fn latest_date(item: &HistoryItem) -> (Year, Day) {
+    (item.latest_year(), certain_day(item))
+}
+
+fn certain_day(item: &HistoryItem) -> Day {
+    match item.precision {
+        DatePrecision::Exact => item.day,
+        _ => Day::NONE,
+    }
+}
+
+/// Whether the first date is certainly earlier than the second one.
+/// Days are only compared if both are known.
+fn precedes((year, day): (Year, Day), (other_year, other_day): (Year, Day)) -> bool {
+    year < other_year
+        || (year == other_year && day.is_some() && other_day.is_some() && day < other_day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_precedes() {
+        assert!(precedes((1.into(), Day::NONE), (2.into(), Day::NONE)));
+        assert!(precedes((1.into(), 5.into()), (1.into(), 6.into())));
+        assert!(!precedes((1.into(), 5.into()), (1.into(), Day::NONE)));
+        assert!(!precedes((1.into(), 5.into()), (1.into(), 5.into())));
+        assert!(!precedes((2.into(), 1.into()), (1.into(), 6.into())));
+    }
+}
@@ -1,29 +1,51 @@
 use ::diesel::prelude::*;
-use diesel::RunQueryDsl;
+use diesel::{dsl::count_star, sql_types::Bool, sqlite::Sqlite, RunQueryDsl};
 
 use crate::{
     errors::{sql_loading_error, LoreCoreError},
-    sql::schema::entities,
+    extractions::{extract_descriptors, extract_labels},
+    fuzzy::{rank_matches, FuzzyMatch},
+    sql::schema::{
+        entities, entity_tags, history_item_references, history_items, relationships,
+        timed_entity_columns,
+    },
     types::*,
 };
 
-use super::{lore_database::LoreDatabase, search_params::EntityColumnSearchParams, sql_types::*};
+use super::{
+    lore_database::LoreDatabase,
+    reference::{
+        index_description_references, move_description_references, unindex_description_references,
+    },
+    search_params::EntityColumnSearchParams,
+    sql_types::*,
+    tag::nested_tag_range,
+    visibility::{concerns_hidden_entity, redact_entity_column, redact_history_item},
+};
 
 impl LoreDatabase {
     pub fn write_entity_columns(&self, cols: Vec<EntityColumn>) -> Result<(), LoreCoreError> {
         let mut connection = self.db_connection()?;
-        for col in cols.into_iter() {
-            let col = col.to_sql_entity_column();
-            diesel::insert_into(entities::table)
-                .values(&col)
-                .execute(&mut connection)
-                .map_err(|e| {
-                    LoreCoreError::SqlError(
-                        "Writing column to database failed: ".to_string() + &e.to_string(),
-                    )
-                })?;
-        }
-        Ok(())
+        connection
+            .transaction(|connection| {
+                for col in cols.into_iter() {
+                    diesel::insert_into(entities::table)
+                        .values(&col.to_sql_entity_column())
+                        .execute(connection)?;
+                    index_description_references(
+                        connection,
+                        &col.label,
+                        &col.descriptor,
+                        &col.description,
+                    )?;
+                }
+                Ok(())
+            })
+            .map_err(|e: diesel::result::Error| {
+                LoreCoreError::SqlError(
+                    "Writing column to database failed: ".to_string() + &e.to_string(),
+                )
+            })
     }
 
     pub fn relabel_entity(
@@ -32,27 +54,51 @@ impl LoreDatabase {
         new_label: &Label,
     ) -> Result<(), LoreCoreError> {
         let mut connection = self.db_connection()?;
-        diesel::update(entities::table.filter(entities::label.eq(old_label.to_str())))
-            .set(entities::label.eq(new_label.to_str()))
-            .execute(&mut connection)
-            .map_err(|e| {
+        connection
+            .transaction(|connection| {
+                diesel::update(entities::table.filter(entities::label.eq(old_label.to_str())))
+                    .set(entities::label.eq(new_label.to_str()))
+                    .execute(connection)?;
+                diesel::update(
+                    timed_entity_columns::table
+                        .filter(timed_entity_columns::label.eq(old_label.to_str())),
+                )
+                .set(timed_entity_columns::label.eq(new_label.to_str()))
+                .execute(connection)?;
+                diesel::update(
+                    entity_tags::table.filter(entity_tags::label.eq(old_label.to_str())),
+                )
+                .set(entity_tags::label.eq(new_label.to_str()))
+                .execute(connection)?;
+                move_description_references(connection, (old_label, None), (new_label, None))
+            })
+            .map_err(|e: diesel::result::Error| {
                 LoreCoreError::SqlError(
                     "Relabeling entity in database failed: ".to_string() + &e.to_string(),
                 )
-            })?;
-        Ok(())
+            })
     }
 
     pub fn delete_entity(&self, label: Label) -> Result<(), LoreCoreError> {
         let mut connection = self.db_connection()?;
-        diesel::delete(entities::table.filter(entities::label.eq(label.to_str())))
-            .execute(&mut connection)
-            .map_err(|e| {
+        connection
+            .transaction(|connection| {
+                diesel::delete(entities::table.filter(entities::label.eq(label.to_str())))
+                    .execute(connection)?;
+                diesel::delete(
+                    timed_entity_columns::table
+                        .filter(timed_entity_columns::label.eq(label.to_str())),
+                )
+                .execute(connection)?;
+                diesel::delete(entity_tags::table.filter(entity_tags::label.eq(label.to_str())))
+                    .execute(connection)?;
+                unindex_description_references(connection, &label, None)
+            })
+            .map_err(|e: diesel::result::Error| {
                 LoreCoreError::SqlError(
                     "Deleting entity from database failed: ".to_string() + &e.to_string(),
                 )
-            })?;
-        Ok(())
+            })
     }
 
     pub fn change_entity_descriptor(
@@ -61,19 +107,33 @@ impl LoreDatabase {
         new_descriptor: &Descriptor,
     ) -> Result<(), LoreCoreError> {
         let mut connection = self.db_connection()?;
-        diesel::update(
-            entities::table
-                .filter(entities::label.eq(label.to_str()))
-                .filter(entities::descriptor.eq(old_descriptor.to_str())),
-        )
-        .set(entities::descriptor.eq(new_descriptor.to_str()))
-        .execute(&mut connection)
-        .map_err(|e| {
-            LoreCoreError::SqlError(
-                "Changing entity descriptor in database failed: ".to_string() + &e.to_string(),
-            )
-        })?;
-        Ok(())
+        connection
+            .transaction(|connection| {
+                diesel::update(
+                    entities::table
+                        .filter(entities::label.eq(label.to_str()))
+                        .filter(entities::descriptor.eq(old_descriptor.to_str())),
+                )
+                .set(entities::descriptor.eq(new_descriptor.to_str()))
+                .execute(connection)?;
+                diesel::update(
+                    timed_entity_columns::table
+                        .filter(timed_entity_columns::label.eq(label.to_str()))
+                        .filter(timed_entity_columns::descriptor.eq(old_descriptor.to_str())),
+                )
+                .set(timed_entity_columns::descriptor.eq(new_descriptor.to_str()))
+                .execute(connection)?;
+                move_description_references(
+                    connection,
+                    (label, Some(&old_descriptor)),
+                    (label, Some(new_descriptor)),
+                )
+            })
+            .map_err(|e: diesel::result::Error| {
+                LoreCoreError::SqlError(
+                    "Changing entity descriptor in database failed: ".to_string() + &e.to_string(),
+                )
+            })
     }
 
     pub fn delete_entity_column(
@@ -81,18 +141,27 @@ impl LoreDatabase {
         (label, descriptor): (Label, Descriptor),
     ) -> Result<(), LoreCoreError> {
         let mut connection = self.db_connection()?;
-        diesel::delete(
-            entities::table
-                .filter(entities::label.eq(label.to_str()))
-                .filter(entities::descriptor.eq(descriptor.to_str())),
-        )
-        .execute(&mut connection)
-        .map_err(|e| {
-            LoreCoreError::SqlError(
-                "Deleting entity column from database failed: ".to_string() + &e.to_string(),
-            )
-        })?;
-        Ok(())
+        connection
+            .transaction(|connection| {
+                diesel::delete(
+                    entities::table
+                        .filter(entities::label.eq(label.to_str()))
+                        .filter(entities::descriptor.eq(descriptor.to_str())),
+                )
+                .execute(connection)?;
+                diesel::delete(
+                    timed_entity_columns::table
+                        .filter(timed_entity_columns::label.eq(label.to_str()))
+                        .filter(timed_entity_columns::descriptor.eq(descriptor.to_str())),
+                )
+                .execute(connection)?;
+                unindex_description_references(connection, &label, Some(&descriptor))
+            })
+            .map_err(|e: diesel::result::Error| {
+                LoreCoreError::SqlError(
+                    "Deleting entity column from database failed: ".to_string() + &e.to_string(),
+                )
+            })
     }
 
     pub fn change_entity_description(
@@ -101,19 +170,25 @@ impl LoreDatabase {
         new_description: &Description,
     ) -> Result<(), LoreCoreError> {
         let mut connection = self.db_connection()?;
-        diesel::update(
-            entities::table
-                .filter(entities::label.eq(label.to_str()))
-                .filter(entities::descriptor.eq(descriptor.to_str())),
-        )
-        .set(entities::description.eq(new_description.to_str()))
-        .execute(&mut connection)
-        .map_err(|e| {
-            LoreCoreError::SqlError(
-                "Changing entity description in database failed: ".to_string() + &e.to_string(),
-            )
-        })?;
-        Ok(())
+        connection
+            .transaction(|connection| {
+                let changed = diesel::update(
+                    entities::table
+                        .filter(entities::label.eq(label.to_str()))
+                        .filter(entities::descriptor.eq(descriptor.to_str())),
+                )
+                .set(entities::description.eq(new_description.to_str()))
+                .execute(connection)?;
+                if changed > 0 {
+                    index_description_references(connection, label, descriptor, new_description)?;
+                }
+                Ok(())
+            })
+            .map_err(|e: diesel::result::Error| {
+                LoreCoreError::SqlError(
+                    "Changing entity description in database failed: ".to_string() + &e.to_string(),
+                )
+            })
     }
 
     pub fn read_entity_columns(
@@ -121,36 +196,220 @@ impl LoreDatabase {
         search_params: EntityColumnSearchParams,
     ) -> Result<Vec<EntityColumn>, LoreCoreError> {
         let mut connection = self.db_connection()?;
-        let mut query = entities::table.into_boxed();
-        let label = search_params.label;
-        if label.is_some() {
-            if label.is_exact {
-                query = query.filter(entities::label.eq(label.exact_text()));
-            } else {
-                query = query.filter(entities::label.like(label.search_pattern()));
-            }
-        }
-        let descriptor = search_params.descriptor;
-        if descriptor.is_some() {
-            if descriptor.is_exact {
-                query = query.filter(entities::descriptor.eq(descriptor.exact_text()));
-            } else {
-                query = query.filter(entities::descriptor.like(descriptor.search_pattern()));
-            }
-        }
-        let mut cols: Vec<_> = query
+        let mut cols: Vec<_> = entities::table
+            .filter(entity_column_filter(&search_params))
             .load::<SqlEntityColumn>(&mut connection)
-            .map_err(|e| {
-                sql_loading_error(
-                    "entities",
-                    vec![("label", &label), ("descriptor", &descriptor)],
-                    e,
-                )
-            })?
+            .map_err(|e| entity_loading_error(&search_params, e))?
             .into_iter()
             .map(|c| c.to_entity_column())
             .collect();
+        if let Some(hidden) = self.read_redactions(search_params.audience)? {
+            cols = cols
+                .into_iter()
+                .map(|col| redact_entity_column(col, &hidden))
+                .collect();
+        }
         cols.sort();
         Ok(cols)
     }
+
+    pub fn count_entity_columns(
+        &self,
+        search_params: EntityColumnSearchParams,
+    ) -> Result<usize, LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        let count = entities::table
+            .filter(entity_column_filter(&search_params))
+            .count()
+            .get_result::<i64>(&mut connection)
+            .map_err(|e| entity_loading_error(&search_params, e))?;
+        Ok(count as usize)
+    }
+
+    /// Returns the number of descriptors of each label, sorted by label.
+    pub fn count_descriptors_per_label(
+        &self,
+        search_params: EntityColumnSearchParams,
+    ) -> Result<Vec<(Label, usize)>, LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        let counts = entities::table
+            .filter(entity_column_filter(&search_params))
+            .group_by(entities::label)
+            .select((entities::label, count_star()))
+            .order_by(entities::label)
+            .load::<(String, i64)>(&mut connection)
+            .map_err(|e| entity_loading_error(&search_params, e))?
+            .into_iter()
+            .map(|(label, count)| (label.into(), count as usize))
+            .collect();
+        Ok(counts)
+    }
+
+    pub fn read_entity(
+        &self,
+        label: &Label,
+        audience: Visibility,
+    ) -> Result<Entity, LoreCoreError> {
+        let mut entities = self.read_entities(std::slice::from_ref(label), audience)?;
+        match entities.pop() {
+            Some(entity) => Ok(entity),
+            None => Err(LoreCoreError::InputError(format!(
+                "Entity \"{}\" does not exist.",
+                label
+            ))),
+        }
+    }
+
+    /// Loads the entities with the given labels, using a single query per table.
+    /// Only the parts visible to `audience` are included, and references to hidden entities
+    /// are redacted. Fails if any of the labels has no entity columns visible to `audience`.
+    pub fn read_entities(
+        &self,
+        labels: &[Label],
+        audience: Visibility,
+    ) -> Result<Vec<Entity>, LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        let label_strings: Vec<_> = labels.iter().map(|l| l.to_string()).collect();
+        let mut cols: Vec<_> = entities::table
+            .filter(entities::label.eq_any(&label_strings))
+            .filter(entities::visibility.le(audience.to_int()))
+            .load::<SqlEntityColumn>(&mut connection)
+            .map_err(|e| sql_loading_error("entities", vec![("labels", &label_strings)], e))?
+            .into_iter()
+            .map(|c| c.to_entity_column())
+            .collect();
+        let mut rels: Vec<_> = relationships::table
+            .filter(
+                relationships::parent
+                    .eq_any(&label_strings)
+                    .or(relationships::child.eq_any(&label_strings)),
+            )
+            .filter(relationships::visibility.le(audience.to_int()))
+            .load::<SqlEntityRelationship>(&mut connection)
+            .map_err(|e| sql_loading_error("relationships", vec![("labels", &label_strings)], e))?
+            .into_iter()
+            .map(|rel| rel.to_relationship())
+            .collect();
+        let mut items: Vec<_> = history_items::table
+            .filter(
+                history_items::timestamp.eq_any(
+                    history_item_references::table
+                        .filter(history_item_references::target.eq_any(&label_strings))
+                        .select(history_item_references::timestamp),
+                ),
+            )
+            .load::<SqlHistoryItem>(&mut connection)
+            .map_err(|e| sql_loading_error("history items", vec![("labels", &label_strings)], e))?
+            .into_iter()
+            .map(|item| item.to_history_item())
+            .collect::<Result<_, _>>()?;
+        if let Some(hidden) = self.read_redactions(audience)? {
+            cols = cols
+                .into_iter()
+                .map(|col| redact_entity_column(col, &hidden))
+                .collect();
+            rels.retain(|rel| !concerns_hidden_entity(rel, &hidden));
+            items = items
+                .into_iter()
+                .filter(|item| item.properties.visibility().is_visible_to(audience))
+                .map(|item| redact_history_item(item, &hidden))
+                .collect();
+        }
+
+        let existing_labels = extract_labels(&cols);
+        let mut entities = Vec::new();
+        for label in labels.iter() {
+            if !existing_labels.contains(label) {
+                return Err(LoreCoreError::InputError(format!(
+                    "Entity \"{}\" does not exist.",
+                    label
+                )));
+            }
+            entities.push(Entity::new(label.clone(), &cols, &rels, &items));
+        }
+        Ok(entities)
+    }
+
+    /// Returns the labels visible to `audience` that are similar to `query`, best match first.
+    pub fn suggest_labels(
+        &self,
+        query: &str,
+        max_suggestions: usize,
+        audience: Visibility,
+    ) -> Result<Vec<FuzzyMatch<Label>>, LoreCoreError> {
+        let cols =
+            self.read_entity_columns(EntityColumnSearchParams::empty().for_audience(audience))?;
+        Ok(rank_matches(query, &extract_labels(&cols), max_suggestions))
+    }
+
+    /// Returns the descriptors visible to `audience` that are similar to `query`,
+    /// best match first.
+    pub fn suggest_descriptors(
+        &self,
+        query: &str,
+        max_suggestions: usize,
+        audience: Visibility,
+    ) -> Result<Vec<FuzzyMatch<Descriptor>>, LoreCoreError> {
+        let cols =
+            self.read_entity_columns(EntityColumnSearchParams::empty().for_audience(audience))?;
+        Ok(rank_matches(
+            query,
+            &extract_descriptors(&cols),
+            max_suggestions,
+        ))
+    }
+}
+
+type EntityColumnFilter = Box<dyn BoxableExpression<entities::table, Sqlite, SqlType = Bool>>;
+
+fn entity_column_filter(search_params: &EntityColumnSearchParams) -> EntityColumnFilter {
+    let mut filter: EntityColumnFilter =
+        Box::new(entities::visibility.le(search_params.audience.to_int()));
+    let label = &search_params.label;
+    if label.is_some() {
+        if label.is_exact {
+            filter = Box::new(filter.and(entities::label.eq(label.exact_text())));
+        } else {
+            filter = Box::new(filter.and(entities::label.like(label.search_pattern())));
+        }
+    }
+    let descriptor = &search_params.descriptor;
+    if descriptor.is_some() {
+        if descriptor.is_exact {
+            filter = Box::new(filter.and(entities::descriptor.eq(descriptor.exact_text())));
+        } else {
+            filter = Box::new(filter.and(entities::descriptor.like(descriptor.search_pattern())));
+        }
+    }
+    if let Some(tag) = &search_params.tag {
+        let (from, until) = nested_tag_range(tag);
+        filter = Box::new(
+            filter.and(
+                entities::label.eq_any(
+                    entity_tags::table
+                        .filter(
+                            entity_tags::tag
+                                .eq(tag.to_string())
+                                .or(entity_tags::tag.ge(from).and(entity_tags::tag.lt(until))),
+                        )
+                        .select(entity_tags::label),
+                ),
+            ),
+        );
+    }
+    filter
+}
+
+fn entity_loading_error<E>(search_params: &EntityColumnSearchParams, err: E) -> LoreCoreError
+where
+    E: std::fmt::Display,
+{
+    sql_loading_error(
+        "entities",
+        vec![
+            ("label", &search_params.label),
+            ("descriptor", &search_params.descriptor),
+        ],
+        err,
+    )
 }
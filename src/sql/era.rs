@@ -0,0 +1,169 @@
+use ::diesel::prelude::*;
+
+use crate::{
+    era::{era_for, parse_year, Era},
+    errors::{sql_loading_error, LoreCoreError},
+    types::*,
+};
+
+use super::{
+    lore_database::LoreDatabase, schema::eras, search_params::HistoryItemSearchParams,
+    sql_types::SqlEra,
+};
+
+/// A history item together with its date, formatted for presentation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DatedHistoryItem {
+    pub item: HistoryItem,
+    pub date: String,
+    /// The recurrence of the item, also set on its repetitions.
+    pub recurrence: Option<Recurrence>,
+}
+
+impl LoreDatabase {
+    pub fn write_eras(&self, eras: Vec<Era>) -> Result<(), LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        for era in eras.into_iter() {
+            let era = era.to_sql_era();
+            diesel::insert_into(eras::table)
+                .values(&era)
+                .execute(&mut connection)
+                .map_err(|e| {
+                    LoreCoreError::SqlError(
+                        "Writing era to database failed: ".to_string() + &e.to_string(),
+                    )
+                })?;
+        }
+        Ok(())
+    }
+
+    /// Returns all eras in chronological order of their start. Eras without a start come first.
+    pub fn read_eras(&self) -> Result<Vec<Era>, LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        let mut eras: Vec<_> = eras::table
+            .load::<SqlEra>(&mut connection)
+            .map_err(|e| sql_loading_error("eras", vec![], e))?
+            .iter()
+            .map(|era| era.to_era())
+            .collect();
+        eras.sort_by(|a, b| a.start.cmp(&b.start).then_with(|| a.name.cmp(&b.name)));
+        Ok(eras)
+    }
+
+    pub fn read_era(&self, name: &str) -> Result<Era, LoreCoreError> {
+        self.read_eras()?
+            .into_iter()
+            .find(|era| era.name == name)
+            .ok_or_else(|| LoreCoreError::InputError(format!("Era \"{}\" does not exist.", name)))
+    }
+
+    pub fn delete_era(&self, name: &str) -> Result<(), LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        diesel::delete(eras::table.filter(eras::name.eq(name)))
+            .execute(&mut connection)
+            .map_err(|e| {
+                LoreCoreError::SqlError(
+                    "Deleting era from database failed: ".to_string() + &e.to_string(),
+                )
+            })?;
+        Ok(())
+    }
+
+    /// Formats `year` in the given era, or in the era containing it if no era is given.
+    /// Years outside of all eras are formatted as plain numbers.
+    pub fn format_year(&self, year: Year, era: Option<&str>) -> Result<String, LoreCoreError> {
+        Ok(match self.presentation_era(year, era)? {
+            Some(era) => era.format(year),
+            None => year.to_string(),
+        })
+    }
+
+    /// Parses a year numbered in any of the stored eras, or a plain number.
+    pub fn parse_year(&self, text: &str) -> Result<Year, LoreCoreError> {
+        parse_year(&self.read_eras()?, text)
+    }
+
+    /// Reads history items like [`LoreDatabase::read_history_items`] and renders their dates
+    /// with `markup`. Years are numbered as in [`LoreDatabase::format_year`], uncertain dates
+    /// as in [`DatePrecision::format_with`]. With a calendar, days are formatted as in
+    /// [`crate::calendar::Calendar::format`], otherwise as day of the year.
+    pub fn read_dated_history_items(
+        &self,
+        search_params: HistoryItemSearchParams,
+        era: Option<&str>,
+        calendar: Option<&str>,
+        markup: Markup,
+    ) -> Result<Vec<DatedHistoryItem>, LoreCoreError> {
+        let calendar = match calendar {
+            Some(calendar) => Some(self.read_calendar(calendar)?),
+            None => None,
+        };
+        let eras = self.read_eras()?;
+        let era = match era {
+            Some(era) => Some(self.read_era(era)?),
+            None => None,
+        };
+        let recurrences = self.read_recurrences()?;
+        let mut dated_items = Vec::new();
+        for item in self.read_history_items(search_params)? {
+            let year_text = match era.as_ref().or_else(|| era_for(&eras, item.year)) {
+                Some(era) => item
+                    .precision
+                    .format_with(item.year, markup, |year| era.format(year)),
+                None => item.precision.format(item.year, markup),
+            };
+            let date = match (&calendar, item.day.to_optional_int()) {
+                (Some(calendar), _) => calendar.format_with_year(item.year, item.day, year_text)?,
+                (None, Some(day)) => format!("day {} of {}", day, year_text),
+                (None, None) => year_text,
+            };
+            let recurrence = recurrences.get(&item.timestamp).copied();
+            dated_items.push(DatedHistoryItem {
+                item,
+                date,
+                recurrence,
+            });
+        }
+        Ok(dated_items)
+    }
+
+    fn presentation_era(
+        &self,
+        year: Year,
+        era: Option<&str>,
+    ) -> Result<Option<Era>, LoreCoreError> {
+        match era {
+            Some(era) => Ok(Some(self.read_era(era)?)),
+            None => Ok(era_for(&self.read_eras()?, year).cloned()),
+        }
+    }
+}
+
+/// Renders dated history items as a list, one item per line. Recurring items are followed by
+/// their recurrence in parentheses. LaTeX output is a `description` environment.
+pub fn render_timeline(items: &[DatedHistoryItem], markup: Markup) -> String {
+    let mut lines = Vec::new();
+    if markup == Markup::Latex {
+        lines.push("\\begin{description}".to_string());
+    }
+    for dated in items.iter() {
+        let content = dated.item.content.to_str();
+        let mut line = match markup {
+            Markup::Plain => format!("{}: {}", dated.date, content),
+            Markup::Markdown => format!("- **{}**: {}", dated.date, content),
+            Markup::Latex => format!("  \\item[{}] {}", dated.date, content),
+        };
+        if let Some(recurrence) = dated.recurrence {
+            line += &match markup {
+                Markup::Plain => format!(" ({})", recurrence),
+                Markup::Markdown => format!(" *({})*", recurrence),
+                Markup::Latex => format!(" \\emph{{({})}}", recurrence),
+            };
+        }
+        lines.push(line);
+    }
+    if markup == Markup::Latex {
+        lines.push("\\end{description}".to_string());
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,338 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{errors::LoreCoreError, types::*};
+
+use super::{
+    graph::find_cycles,
+    lore_database::LoreDatabase,
+    search_params::{
+        HistoryItemSearchParams, RelationshipSearchParams, RelationshipTraversalParams,
+    },
+};
+
+/// An ancestor shared by two entities, `depth_from_first` generations above the first
+/// and `depth_from_second` generations above the second one.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CommonAncestor {
+    pub label: Label,
+    pub depth_from_first: u32,
+    pub depth_from_second: u32,
+}
+
+impl LoreDatabase {
+    /// Returns the generation of every entity in the family tree spanned by the relationships
+    /// followed according to `params`. Entities without parents are generation 0, all others
+    /// are one generation below their youngest parent. Only relationships visible to `audience`
+    /// are used.
+    pub fn read_generations(
+        &self,
+        params: RelationshipTraversalParams,
+        audience: Visibility,
+    ) -> Result<BTreeMap<Label, u32>, LoreCoreError> {
+        let rels = self.read_followed_relationships(&params, audience)?;
+        generations(&rels)
+    }
+
+    /// Returns the common ancestors of `first` and `second` that are closest to both of them.
+    /// An entity counts as its own ancestor at depth 0, so if `second` is an ancestor of `first`,
+    /// it is also their lowest common ancestor. Only relationships visible to `audience` are
+    /// followed.
+    pub fn read_lowest_common_ancestors(
+        &self,
+        first: &Label,
+        second: &Label,
+        params: RelationshipTraversalParams,
+        audience: Visibility,
+    ) -> Result<Vec<CommonAncestor>, LoreCoreError> {
+        let first_ancestors = self.read_ancestor_depths(first, &params, audience)?;
+        let second_ancestors = self.read_ancestor_depths(second, &params, audience)?;
+        let common_ancestors: Vec<_> = first_ancestors
+            .iter()
+            .filter_map(|(label, depth_from_first)| {
+                second_ancestors
+                    .get(label)
+                    .map(|depth_from_second| CommonAncestor {
+                        label: label.clone(),
+                        depth_from_first: *depth_from_first,
+                        depth_from_second: *depth_from_second,
+                    })
+            })
+            .collect();
+        let min_distance = common_ancestors
+            .iter()
+            .map(|a| a.depth_from_first + a.depth_from_second)
+            .min();
+        Ok(common_ancestors
+            .into_iter()
+            .filter(|a| Some(a.depth_from_first + a.depth_from_second) == min_distance)
+            .collect())
+    }
+
+    /// Names what `second` is to `first`, for example "cousin once removed".
+    /// Returns `None` if the two are not related as far as `audience` knows.
+    pub fn read_kinship(
+        &self,
+        first: &Label,
+        second: &Label,
+        params: RelationshipTraversalParams,
+        audience: Visibility,
+    ) -> Result<Option<String>, LoreCoreError> {
+        let ancestors = self.read_lowest_common_ancestors(first, second, params, audience)?;
+        Ok(ancestors
+            .first()
+            .map(|a| kinship_name(a.depth_from_first, a.depth_from_second)))
+    }
+
+    /// Returns the year and day of birth of every entity named under `births` in a history item.
+    /// If several history items claim to be the birth of the same entity, the earliest one counts.
+    /// Only history items visible to `audience` are used, and hidden entities are left out.
+    pub fn read_birth_dates(
+        &self,
+        audience: Visibility,
+    ) -> Result<BTreeMap<Label, (Year, Day)>, LoreCoreError> {
+        let items =
+            self.read_history_items(HistoryItemSearchParams::empty().for_audience(audience))?;
+        let mut birth_dates = BTreeMap::new();
+        for item in items.iter() {
+            for label in item.properties.births() {
+                birth_dates.entry(label).or_insert((item.year, item.day));
+            }
+        }
+        Ok(birth_dates)
+    }
+
+    /// Returns the descendants of `label` in order of succession: every child is followed by its
+    /// own line of succession before the next younger sibling. Siblings are ordered by birth date,
+    /// those without a known birth date come last. Only what is visible to `audience` is used.
+    pub fn read_line_of_succession(
+        &self,
+        label: &Label,
+        params: RelationshipTraversalParams,
+        audience: Visibility,
+    ) -> Result<Vec<Label>, LoreCoreError> {
+        let rels = self.read_followed_relationships(&params, audience)?;
+        let birth_dates = self.read_birth_dates(audience)?;
+        let mut children: BTreeMap<Label, Vec<Label>> = BTreeMap::new();
+        for rel in rels.into_iter() {
+            children
+                .entry(rel.parent.into())
+                .or_default()
+                .push(rel.child.into());
+        }
+        for siblings in children.values_mut() {
+            siblings.sort_by_key(|sibling| {
+                let birth_date = birth_dates.get(sibling);
+                (birth_date.is_none(), birth_date.cloned(), sibling.clone())
+            });
+            siblings.dedup();
+        }
+
+        let mut line = Vec::new();
+        let mut visited = BTreeSet::from([label.clone()]);
+        append_successors(label, &children, &mut visited, &mut line);
+        Ok(line)
+    }
+
+    fn read_followed_relationships(
+        &self,
+        params: &RelationshipTraversalParams,
+        audience: Visibility,
+    ) -> Result<Vec<EntityRelationship>, LoreCoreError> {
+        Ok(self
+            .read_relationships(RelationshipSearchParams::empty().for_audience(audience))?
+            .into_iter()
+            .filter(|rel| params.follows(rel))
+            .collect())
+    }
+
+    fn read_ancestor_depths(
+        &self,
+        label: &Label,
+        params: &RelationshipTraversalParams,
+        audience: Visibility,
+    ) -> Result<BTreeMap<Label, u32>, LoreCoreError> {
+        let mut depths: BTreeMap<Label, u32> = self
+            .read_ancestors(label, params.clone(), audience)?
+            .into_iter()
+            .map(|ancestor| (ancestor.label, ancestor.depth))
+            .collect();
+        depths.insert(label.clone(), 0);
+        Ok(depths)
+    }
+}
+
+fn append_successors(
+    label: &Label,
+    children: &BTreeMap<Label, Vec<Label>>,
+    visited: &mut BTreeSet<Label>,
+    line: &mut Vec<Label>,
+) {
+    for child in children.get(label).into_iter().flatten() {
+        if visited.insert(child.clone()) {
+            line.push(child.clone());
+            append_successors(child, children, visited, line);
+        }
+    }
+}
+
+/// Computes the generation of every entity appearing in `rels`, where relationships point from
+/// parents to their children. Fails if the relationships contain a cycle.
+pub fn generations(rels: &[EntityRelationship]) -> Result<BTreeMap<Label, u32>, LoreCoreError> {
+    if let Some(cycle) = find_cycles(rels).first() {
+        let chain: Vec<_> = cycle.iter().map(|label| label.to_string()).collect();
+        return Err(LoreCoreError::InputError(format!(
+            "The family tree contains the cycle {}.",
+            chain.join(" -> ")
+        )));
+    }
+
+    let mut parents: BTreeMap<Label, Vec<Label>> = BTreeMap::new();
+    for rel in rels.iter() {
+        parents.entry(rel.parent.clone().into()).or_default();
+        parents
+            .entry(rel.child.clone().into())
+            .or_default()
+            .push(rel.parent.clone().into());
+    }
+    let mut generations = BTreeMap::new();
+    for label in parents.keys() {
+        generation(label, &parents, &mut generations);
+    }
+    Ok(generations)
+}
+
+fn generation(
+    label: &Label,
+    parents: &BTreeMap<Label, Vec<Label>>,
+    generations: &mut BTreeMap<Label, u32>,
+) -> u32 {
+    if let Some(generation) = generations.get(label) {
+        return *generation;
+    }
+    let mut result = 0;
+    for parent in parents.get(label).into_iter().flatten() {
+        result = result.max(generation(parent, parents, generations) + 1);
+    }
+    generations.insert(label.clone(), result);
+    result
+}
+
+/// Names what an entity is to another one, given how many generations each of them is below
+/// their lowest common ancestor. For example, `kinship_name(2, 3)` is "first cousin once removed".
+pub fn kinship_name(depth_from_first: u32, depth_from_second: u32) -> String {
+    match (depth_from_first, depth_from_second) {
+        (0, 0) => "self".to_string(),
+        (up, 0) => grand_prefix(up - 1) + "parent",
+        (0, down) => grand_prefix(down - 1) + "child",
+        (1, 1) => "sibling".to_string(),
+        (1, down) => {
+            let prefix = grand_prefix(down - 2);
+            format!("{}niece or {}nephew", prefix, prefix)
+        }
+        (up, 1) => {
+            let prefix = grand_prefix(up - 2);
+            format!("{}aunt or {}uncle", prefix, prefix)
+        }
+        (up, down) => {
+            let degree = up.min(down) - 1;
+            let removed = up.abs_diff(down);
+            let cousin = ordinal(degree) + " cousin";
+            match removed {
+                0 => cousin,
+                1 => cousin + " once removed",
+                2 => cousin + " twice removed",
+                n => format!("{} {} times removed", cousin, n),
+            }
+        }
+    }
+}
+
+fn grand_prefix(generations: u32) -> String {
+    match generations {
+        0 => String::new(),
+        n => "great-".repeat(n as usize - 1) + "grand",
+    }
+}
+
+fn ordinal(n: u32) -> String {
+    const WORDS: [&str; 10] = [
+        "first", "second", "third", "fourth", "fifth", "sixth", "seventh", "eighth", "ninth",
+        "tenth",
+    ];
+    if (1..=10).contains(&n) {
+        return WORDS[n as usize - 1].to_string();
+    }
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rel(parent: &str, child: &str) -> EntityRelationship {
+        EntityRelationship {
+            parent: parent.into(),
+            child: child.into(),
+            role: "parent".into(),
+            start: None,
+            end: None,
+        }
+    }
+
+    #[test]
+    fn test_direct_line_kinship_names() {
+        assert_eq!(kinship_name(0, 0), "self");
+        assert_eq!(kinship_name(1, 0), "parent");
+        assert_eq!(kinship_name(2, 0), "grandparent");
+        assert_eq!(kinship_name(4, 0), "great-great-grandparent");
+        assert_eq!(kinship_name(0, 1), "child");
+        assert_eq!(kinship_name(0, 3), "great-grandchild");
+    }
+
+    #[test]
+    fn test_collateral_kinship_names() {
+        assert_eq!(kinship_name(1, 1), "sibling");
+        assert_eq!(kinship_name(1, 2), "niece or nephew");
+        assert_eq!(kinship_name(1, 4), "great-grandniece or great-grandnephew");
+        assert_eq!(kinship_name(2, 1), "aunt or uncle");
+        assert_eq!(kinship_name(3, 1), "grandaunt or granduncle");
+    }
+
+    #[test]
+    fn test_cousin_kinship_names() {
+        assert_eq!(kinship_name(2, 2), "first cousin");
+        assert_eq!(kinship_name(2, 3), "first cousin once removed");
+        assert_eq!(kinship_name(4, 2), "first cousin twice removed");
+        assert_eq!(kinship_name(3, 3), "second cousin");
+        assert_eq!(kinship_name(3, 7), "second cousin 4 times removed");
+        assert_eq!(kinship_name(13, 13), "12th cousin");
+        assert_eq!(kinship_name(22, 22), "21st cousin");
+    }
+
+    #[test]
+    fn test_generations() {
+        let rels = vec![
+            rel("grandmother", "mother"),
+            rel("mother", "daughter"),
+            rel("father", "daughter"),
+        ];
+        let generations = generations(&rels).unwrap();
+        assert_eq!(generations[&"grandmother".into()], 0);
+        assert_eq!(generations[&"father".into()], 0);
+        assert_eq!(generations[&"mother".into()], 1);
+        assert_eq!(generations[&"daughter".into()], 2);
+    }
+
+    #[test]
+    fn test_generations_fail_on_cycles() {
+        let rels = vec![rel("a", "b"), rel("b", "a")];
+        assert!(generations(&rels).is_err());
+    }
+}
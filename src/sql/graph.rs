@@ -0,0 +1,298 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use diesel::{
+    sql_types::{Integer, Text},
+    RunQueryDsl,
+};
+
+use crate::{
+    errors::{sql_loading_error, LoreCoreError},
+    types::*,
+};
+
+use super::{
+    lore_database::LoreDatabase,
+    search_params::{RelationshipSearchParams, RelationshipTraversalParams},
+    sql_types::SqlTraversedRelationship,
+};
+
+/// An entity reached while following relationships, `depth` relationships away from the start.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ReachedEntity {
+    pub label: Label,
+    pub depth: u32,
+}
+
+enum Direction {
+    TowardsParents,
+    TowardsChildren,
+}
+
+impl LoreDatabase {
+    /// Returns all entities reachable by repeatedly going from child to parent, starting at `label`.
+    /// Every ancestor is listed once, at its smallest depth, sorted by depth and label.
+    /// Only relationships visible to `audience` are followed.
+    pub fn read_ancestors(
+        &self,
+        label: &Label,
+        params: RelationshipTraversalParams,
+        audience: Visibility,
+    ) -> Result<Vec<ReachedEntity>, LoreCoreError> {
+        self.traverse(label, &params, audience, Direction::TowardsParents)
+    }
+
+    /// Returns all entities reachable by repeatedly going from parent to child, starting at `label`.
+    /// Every descendant is listed once, at its smallest depth, sorted by depth and label.
+    /// Only relationships visible to `audience` are followed.
+    pub fn read_descendants(
+        &self,
+        label: &Label,
+        params: RelationshipTraversalParams,
+        audience: Visibility,
+    ) -> Result<Vec<ReachedEntity>, LoreCoreError> {
+        self.traverse(label, &params, audience, Direction::TowardsChildren)
+    }
+
+    /// Returns the cycles among the relationships followed according to `params` that are
+    /// visible to `audience`. See [`find_cycles`] for the format.
+    pub fn find_cycles(
+        &self,
+        params: RelationshipTraversalParams,
+        audience: Visibility,
+    ) -> Result<Vec<Vec<Label>>, LoreCoreError> {
+        let rels: Vec<_> = self
+            .read_relationships(RelationshipSearchParams::empty().for_audience(audience))?
+            .into_iter()
+            .filter(|rel| params.follows(rel))
+            .collect();
+        Ok(find_cycles(&rels))
+    }
+
+    fn traverse(
+        &self,
+        label: &Label,
+        params: &RelationshipTraversalParams,
+        audience: Visibility,
+        direction: Direction,
+    ) -> Result<Vec<ReachedEntity>, LoreCoreError> {
+        let (next, current) = match direction {
+            Direction::TowardsParents => ("parent", "child"),
+            Direction::TowardsChildren => ("child", "parent"),
+        };
+        let role_filter = if params.roles.is_empty() {
+            "1".to_string()
+        } else {
+            let placeholders = vec!["?"; params.roles.len()].join(", ");
+            format!("r.role IN ({})", placeholders)
+        };
+        // Like in `read_relationships`, relationships are hidden along with their parent or
+        // child, which is hidden if it has columns but none of them are visible.
+        let visibility_filter = if audience == Visibility::AuthorOnly {
+            "1"
+        } else {
+            "r.visibility <= ?
+                AND r.parent NOT IN hidden
+                AND r.child NOT IN hidden"
+        };
+        // The recursion only collects the reachable labels, each once, so that it stays linear
+        // in the number of relationships even around cycles. Depths are then found in memory.
+        let query = format!(
+            "WITH RECURSIVE hidden(label) AS (
+                SELECT label FROM entities GROUP BY label HAVING MIN(visibility) > ?
+            ),
+            followed(source, target) AS (
+                SELECT r.{current}, r.{next} FROM relationships r
+                WHERE {role_filter} AND {visibility_filter}
+            ),
+            reached(label) AS (
+                SELECT ?
+                UNION
+                SELECT followed.target
+                FROM followed JOIN reached ON followed.source = reached.label
+            )
+            SELECT source, target FROM followed WHERE source IN reached"
+        );
+
+        let mut query = diesel::sql_query(query)
+            .into_boxed()
+            .bind::<Integer, _>(audience.to_int());
+        for role in params.roles.iter() {
+            query = query.bind::<Text, _>(role.to_string());
+        }
+        if audience != Visibility::AuthorOnly {
+            query = query.bind::<Integer, _>(audience.to_int());
+        }
+        query = query.bind::<Text, _>(label.to_string());
+
+        let mut connection = self.db_connection()?;
+        let followed = query
+            .load::<SqlTraversedRelationship>(&mut connection)
+            .map_err(|e| {
+                sql_loading_error(
+                    "related entities",
+                    vec![("label", label), ("params", params)],
+                    e,
+                )
+            })?;
+        let mut targets: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for rel in followed.iter() {
+            targets
+                .entry(rel.source.as_str())
+                .or_default()
+                .push(rel.target.as_str());
+        }
+
+        let mut depths: BTreeMap<&str, u32> = BTreeMap::new();
+        let mut queue = VecDeque::from([(label.to_str(), 0)]);
+        while let Some((current, depth)) = queue.pop_front() {
+            if params.max_depth.is_some_and(|max_depth| depth >= max_depth) {
+                continue;
+            }
+            for target in targets.get(current).into_iter().flatten() {
+                if !depths.contains_key(target) {
+                    depths.insert(target, depth + 1);
+                    queue.push_back((target, depth + 1));
+                }
+            }
+        }
+        let mut reached: Vec<_> = depths
+            .into_iter()
+            .map(|(label, depth)| ReachedEntity {
+                label: label.into(),
+                depth,
+            })
+            .collect();
+        reached.sort_by(|a, b| (a.depth, &a.label).cmp(&(b.depth, &b.label)));
+        Ok(reached)
+    }
+}
+
+/// Finds cycles in the directed graph pointing from parents to their children.
+///
+/// Every cycle is given as the chain of labels along the relationships, starting and ending at its
+/// alphabetically smallest label, e.g. `[a, b, c, a]` for `a -> b -> c -> a`.
+/// Each back edge of a depth-first search yields one cycle, so every entity on a cycle is reported,
+/// but not every combination of overlapping cycles is.
+pub fn find_cycles(rels: &[EntityRelationship]) -> Vec<Vec<Label>> {
+    let mut children: BTreeMap<Label, BTreeSet<Label>> = BTreeMap::new();
+    for rel in rels.iter() {
+        children
+            .entry(rel.parent.clone().into())
+            .or_default()
+            .insert(rel.child.clone().into());
+    }
+
+    let mut visited = BTreeSet::new();
+    let mut cycles = BTreeSet::new();
+    for start in children.keys() {
+        if !visited.insert(start.clone()) {
+            continue;
+        }
+        // A depth-first search with an explicit stack, holding the remaining children of each
+        // label on the current path, and the position of each label in the path.
+        let mut path = vec![start.clone()];
+        let mut positions = BTreeMap::from([(start, 0)]);
+        let mut stack = vec![children.get(start).into_iter().flatten()];
+        while let Some(remaining) = stack.last_mut() {
+            match remaining.next() {
+                Some(child) => {
+                    if let Some(position) = positions.get(child) {
+                        cycles.insert(normalized_cycle(&path[*position..]));
+                    } else if visited.insert(child.clone()) {
+                        positions.insert(child, path.len());
+                        path.push(child.clone());
+                        stack.push(children.get(child).into_iter().flatten());
+                    }
+                }
+                None => {
+                    stack.pop();
+                    if let Some(label) = path.pop() {
+                        positions.remove(&label);
+                    }
+                }
+            }
+        }
+    }
+    cycles.into_iter().collect()
+}
+
+fn normalized_cycle(cycle: &[Label]) -> Vec<Label> {
+    let start = cycle
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, label)| *label)
+        .map(|(i, _)| i)
+        .unwrap_or_default();
+    let mut chain: Vec<_> = cycle[start..]
+        .iter()
+        .chain(cycle[..start].iter())
+        .cloned()
+        .collect();
+    chain.push(cycle[start].clone());
+    chain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rel(parent: &str, child: &str) -> EntityRelationship {
+        EntityRelationship {
+            parent: parent.into(),
+            child: child.into(),
+            role: Role::NONE,
+            start: None,
+            end: None,
+        }
+    }
+
+    #[test]
+    fn test_find_cycles_without_cycles() {
+        let rels = vec![rel("a", "b"), rel("b", "c"), rel("a", "c")];
+        assert!(find_cycles(&rels).is_empty());
+    }
+
+    #[test]
+    fn test_find_cycles_reports_chain() {
+        let rels = vec![rel("c", "a"), rel("a", "b"), rel("b", "c"), rel("c", "d")];
+        let cycles = find_cycles(&rels);
+        assert_eq!(
+            cycles,
+            vec![vec!["a".into(), "b".into(), "c".into(), "a".into()]]
+        );
+    }
+
+    #[test]
+    fn test_find_cycles_reports_self_reference() {
+        let rels = vec![rel("a", "a")];
+        let cycles = find_cycles(&rels);
+        assert_eq!(cycles, vec![vec!["a".into(), "a".into()]]);
+    }
+
+    #[test]
+    fn test_find_cycles_on_long_chain() {
+        let labels: Vec<_> = (0..50_000).map(|i| format!("n{}", i)).collect();
+        let mut rels: Vec<_> = labels
+            .windows(2)
+            .map(|pair| rel(&pair[0], &pair[1]))
+            .collect();
+        assert!(find_cycles(&rels).is_empty());
+        rels.push(rel("n49999", "n0"));
+        let cycles = find_cycles(&rels);
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].len(), 50_001);
+    }
+
+    #[test]
+    fn test_find_cycles_reports_separate_cycles() {
+        let rels = vec![rel("a", "b"), rel("b", "a"), rel("x", "y"), rel("y", "x")];
+        let cycles = find_cycles(&rels);
+        assert_eq!(
+            cycles,
+            vec![
+                vec!["a".into(), "b".into(), "a".into()],
+                vec!["x".into(), "y".into(), "x".into()]
+            ]
+        );
+    }
+}
@@ -1,4 +1,6 @@
 use ::diesel::prelude::*;
+use diesel::{dsl::count_star, sql_types::Bool, sqlite::Sqlite, SqliteConnection};
+use std::collections::BTreeMap;
 
 use crate::{
     errors::{sql_loading_error, LoreCoreError},
@@ -6,62 +8,222 @@ use crate::{
 };
 
 use super::{
-    lore_database::LoreDatabase, schema::history_items, search_params::HistoryItemSearchParams,
+    lore_database::LoreDatabase,
+    recurrence::MAX_OCCURRENCES,
+    reference::{
+        index_history_item_references, reindex_history_item_references,
+        unindex_history_item_references,
+    },
+    schema::{history_anchors, history_item_tags, history_items, recurrences},
+    search_params::HistoryItemSearchParams,
     sql_types::*,
+    tag::nested_tag_range,
+    visibility::redact_history_item,
 };
 
 impl LoreDatabase {
+    /// Fails if the properties of an item use undeclared keys or values of the wrong type,
+    /// see [`LoreDatabase::write_property_declarations`], or if its date is a range of more than
+    /// `i32::MAX` years.
     pub fn write_history_items(&self, cols: Vec<HistoryItem>) -> Result<(), LoreCoreError> {
-        let mut connection = self.db_connection()?;
-        for col in cols.into_iter() {
-            let col = col.to_sql_history_item();
-            diesel::insert_into(history_items::table)
-                .values(&col)
-                .execute(&mut connection)
-                .map_err(|e| {
-                    LoreCoreError::SqlError(
-                        "Writing history item to database failed: ".to_string() + &e.to_string(),
-                    )
-                })?;
+        for col in cols.iter() {
+            if col.is_occurrence {
+                return Err(LoreCoreError::InputError(format!(
+                    "History item {} in year {} is a repetition of a recurring item and cannot be \
+                     stored.",
+                    col.timestamp, col.year
+                )));
+            }
+            check_precision(&col.precision)?;
+            self.validate_properties(&col.properties)?;
         }
-        Ok(())
+        let mut connection = self.db_connection()?;
+        connection
+            .transaction(|connection| {
+                for col in cols.into_iter() {
+                    diesel::insert_into(history_items::table)
+                        .values(&col.to_sql_history_item())
+                        .execute(connection)?;
+                    index_history_item_references(connection, &col)?;
+                }
+                Ok(())
+            })
+            .map_err(|e: diesel::result::Error| {
+                LoreCoreError::SqlError(
+                    "Writing history item to database failed: ".to_string() + &e.to_string(),
+                )
+            })
     }
 
+    /// Moves the history item to an absolute date, detaching it from its anchor.
+    /// Items anchored to it are moved along.
     pub fn redate_history_item(
         &self,
         timestamp: Timestamp,
         year: Year,
         day: Day,
+    ) -> Result<(), LoreCoreError> {
+        let dates = self.anchored_dates(timestamp, year, day)?;
+        let mut connection = self.db_connection()?;
+        connection
+            .transaction(|connection| {
+                diesel::delete(
+                    history_anchors::table
+                        .filter(history_anchors::timestamp.eq(timestamp.to_int())),
+                )
+                .execute(connection)?;
+                set_history_item_dates(connection, dates)
+            })
+            .map_err(|e: diesel::result::Error| {
+                LoreCoreError::SqlError(
+                    "Redating history items in database failed: ".to_string() + &e.to_string(),
+                )
+            })
+    }
+
+    pub fn change_history_item_sort_key(
+        &self,
+        timestamp: Timestamp,
+        sort_key: i64,
     ) -> Result<(), LoreCoreError> {
         let mut connection = self.db_connection()?;
         diesel::update(
             history_items::table.filter(history_items::timestamp.eq(timestamp.to_int())),
         )
-        .set((
-            history_items::year.eq(year.to_int()),
-            history_items::day.eq(day.to_int() as i32),
-        ))
+        .set(history_items::sort_key.eq(sort_key))
         .execute(&mut connection)
         .map_err(|e| {
             LoreCoreError::SqlError(
-                "Redating history item in database failed: ".to_string() + &e.to_string(),
+                "Changing history item sort key in database failed: ".to_string() + &e.to_string(),
             )
         })?;
         Ok(())
     }
 
+    /// Moves the history item at `timestamp` directly before the one at `other`.
+    /// Both have to be dated on the same day. The sort keys of all items on that day are
+    /// renumbered from 0 in their new order.
+    pub fn move_history_item_before(
+        &self,
+        timestamp: Timestamp,
+        other: Timestamp,
+    ) -> Result<(), LoreCoreError> {
+        self.move_history_item(timestamp, other, false)
+    }
+
+    /// Moves the history item at `timestamp` directly after the one at `other`,
+    /// like [`LoreDatabase::move_history_item_before`].
+    pub fn move_history_item_after(
+        &self,
+        timestamp: Timestamp,
+        other: Timestamp,
+    ) -> Result<(), LoreCoreError> {
+        self.move_history_item(timestamp, other, true)
+    }
+
+    fn move_history_item(
+        &self,
+        timestamp: Timestamp,
+        other: Timestamp,
+        after: bool,
+    ) -> Result<(), LoreCoreError> {
+        if timestamp == other {
+            return Err(LoreCoreError::InputError(format!(
+                "History item {} cannot be moved relative to itself.",
+                timestamp
+            )));
+        }
+        let item = self.read_history_item(timestamp)?;
+        let other_item = self.read_history_item(other)?;
+        if (item.year, item.day) != (other_item.year, other_item.day) {
+            return Err(LoreCoreError::InputError(format!(
+                "History items {} and {} are not dated on the same day.",
+                timestamp, other
+            )));
+        }
+        let mut same_day = self.read_history_items(HistoryItemSearchParams::new(
+            Some(item.year),
+            Some(item.day),
+            None,
+            None,
+        ))?;
+        same_day.retain(|item| item.timestamp != timestamp);
+        let position = same_day
+            .iter()
+            .position(|item| item.timestamp == other)
+            .ok_or_else(|| {
+                LoreCoreError::InputError(format!(
+                    "History item {} was not found on its day.",
+                    other
+                ))
+            })?;
+        let position = if after { position + 1 } else { position };
+        same_day.insert(position, item);
+        let mut connection = self.db_connection()?;
+        connection
+            .transaction(|connection| {
+                for (sort_key, item) in same_day.iter().enumerate() {
+                    if item.sort_key != sort_key as i64 {
+                        diesel::update(
+                            history_items::table
+                                .filter(history_items::timestamp.eq(item.timestamp.to_int())),
+                        )
+                        .set(history_items::sort_key.eq(sort_key as i64))
+                        .execute(connection)?;
+                    }
+                }
+                Ok(())
+            })
+            .map_err(|e: diesel::result::Error| {
+                LoreCoreError::SqlError(
+                    "Reordering history items in database failed: ".to_string() + &e.to_string(),
+                )
+            })
+    }
+
+    pub(super) fn read_history_item(
+        &self,
+        timestamp: Timestamp,
+    ) -> Result<HistoryItem, LoreCoreError> {
+        self.read_history_items(HistoryItemSearchParams::new(
+            None,
+            None,
+            Some(timestamp),
+            None,
+        ))?
+        .pop()
+        .ok_or_else(|| {
+            LoreCoreError::InputError(format!("History item {} does not exist.", timestamp))
+        })
+    }
+
+    /// Deletes the history item with its anchor and recurrence. Items anchored to it keep their
+    /// dates, but are reported by [`LoreDatabase::find_anchor_problems`].
     pub fn delete_history_item(&self, timestamp: Timestamp) -> Result<(), LoreCoreError> {
         let mut connection = self.db_connection()?;
-        diesel::delete(
-            history_items::table.filter(history_items::timestamp.eq(timestamp.to_int())),
-        )
-        .execute(&mut connection)
-        .map_err(|e| {
-            LoreCoreError::SqlError(
-                "Deleting history item from database failed: ".to_string() + &e.to_string(),
-            )
-        })?;
-        Ok(())
+        connection
+            .transaction(|connection| {
+                let timestamp = timestamp.to_int();
+                diesel::delete(
+                    history_anchors::table.filter(history_anchors::timestamp.eq(timestamp)),
+                )
+                .execute(connection)?;
+                diesel::delete(recurrences::table.filter(recurrences::timestamp.eq(timestamp)))
+                    .execute(connection)?;
+                diesel::delete(
+                    history_item_tags::table.filter(history_item_tags::timestamp.eq(timestamp)),
+                )
+                .execute(connection)?;
+                unindex_history_item_references(connection, timestamp.into())?;
+                diesel::delete(history_items::table.filter(history_items::timestamp.eq(timestamp)))
+                    .execute(connection)?;
+                Ok(())
+            })
+            .map_err(|e: diesel::result::Error| {
+                LoreCoreError::SqlError(
+                    "Deleting history item from database failed: ".to_string() + &e.to_string(),
+                )
+            })
     }
 
     pub fn change_history_item_content(
@@ -70,17 +232,21 @@ impl LoreDatabase {
         content: &HistoryItemContent,
     ) -> Result<(), LoreCoreError> {
         let mut connection = self.db_connection()?;
-        diesel::update(
-            history_items::table.filter(history_items::timestamp.eq(timestamp.to_int())),
-        )
-        .set(history_items::content.eq(content.to_str()))
-        .execute(&mut connection)
-        .map_err(|e| {
-            LoreCoreError::SqlError(
-                "Changing history item content in database failed: ".to_string() + &e.to_string(),
-            )
-        })?;
-        Ok(())
+        connection
+            .transaction(|connection| {
+                diesel::update(
+                    history_items::table.filter(history_items::timestamp.eq(timestamp.to_int())),
+                )
+                .set(history_items::content.eq(content.to_str()))
+                .execute(connection)?;
+                reindex_history_item_references(connection, timestamp)
+            })
+            .map_err(|e: diesel::result::Error| {
+                LoreCoreError::SqlError(
+                    "Changing history item content in database failed: ".to_string()
+                        + &e.to_string(),
+                )
+            })
     }
 
     pub fn change_history_item_properties(
@@ -88,56 +254,257 @@ impl LoreDatabase {
         timestamp: Timestamp,
         properties: &HistoryItemProperties,
     ) -> Result<(), LoreCoreError> {
+        self.validate_properties(properties)?;
         let mut connection = self.db_connection()?;
-        diesel::update(
-            history_items::table.filter(history_items::timestamp.eq(timestamp.to_int())),
-        )
-        .set(history_items::properties.eq(properties.to_string()))
-        .execute(&mut connection)
-        .map_err(|e| {
-            LoreCoreError::SqlError(
-                "Changing history item properties in database failed: ".to_string()
-                    + &e.to_string(),
-            )
-        })?;
-        Ok(())
+        connection
+            .transaction(|connection| {
+                diesel::update(
+                    history_items::table.filter(history_items::timestamp.eq(timestamp.to_int())),
+                )
+                .set(history_items::properties.eq(properties.to_string()))
+                .execute(connection)?;
+                reindex_history_item_references(connection, timestamp)
+            })
+            .map_err(|e: diesel::result::Error| {
+                LoreCoreError::SqlError(
+                    "Changing history item properties in database failed: ".to_string()
+                        + &e.to_string(),
+                )
+            })
     }
 
+    /// Returns the matching history items in the order of [`HistoryItem`].
+    /// When searching within a period, the repetitions of recurring items in that period are
+    /// included. They are copies of the recurring item with the year changed, marked with
+    /// `is_occurrence`.
+    /// Fails if there are more than 100000 of them.
     pub fn read_history_items(
         &self,
         search_params: HistoryItemSearchParams,
     ) -> Result<Vec<HistoryItem>, LoreCoreError> {
         let mut connection = self.db_connection()?;
-        let mut query = history_items::table.into_boxed();
-        let year = search_params.year;
-        if let Some(year) = year {
-            query = query.filter(history_items::year.eq(year.to_int()));
+        let mut items = history_items::table
+            .filter(history_item_filter(&search_params))
+            .load::<SqlHistoryItem>(&mut connection)
+            .map_err(|e| history_loading_error(&search_params, e))?
+            .into_iter()
+            .map(|item| item.to_history_item())
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some((from, to)) = search_params.period {
+            let recurrences = self.read_recurrences()?;
+            let occurrence_count: u64 = items
+                .iter()
+                .filter_map(|item| {
+                    recurrences
+                        .get(&item.timestamp)
+                        .map(|recurrence| recurrence.count_occurrences(item, from, to))
+                })
+                .sum();
+            if occurrence_count > MAX_OCCURRENCES as u64 {
+                return Err(LoreCoreError::InputError(format!(
+                    "The period from {} to {} contains {} repetitions of recurring history \
+                     items, more than the {} that can be read at once.",
+                    from, to, occurrence_count, MAX_OCCURRENCES
+                )));
+            }
+            let occurrences: Vec<_> = items
+                .iter()
+                .filter_map(|item| {
+                    recurrences
+                        .get(&item.timestamp)
+                        .map(|recurrence| recurrence.occurrences(item, from, to))
+                })
+                .flatten()
+                .collect();
+            items.extend(occurrences);
         }
-        let day = search_params.day;
-        if let Some(day) = day {
-            query = query.filter(history_items::day.eq(day.to_int() as i32));
+        items.retain(|item| {
+            search_params.matches_period(item)
+                && item
+                    .properties
+                    .visibility()
+                    .is_visible_to(search_params.audience)
+        });
+        if let Some(hidden) = self.read_redactions(search_params.audience)? {
+            items = items
+                .into_iter()
+                .map(|item| redact_history_item(item, &hidden))
+                .collect();
         }
-        let timestamp = search_params.timestamp;
-        if let Some(timestamp) = timestamp {
-            query = query.filter(history_items::timestamp.eq(timestamp.to_int()));
+        items.sort();
+        Ok(items)
+    }
+
+    pub fn count_history_items(
+        &self,
+        search_params: HistoryItemSearchParams,
+    ) -> Result<usize, LoreCoreError> {
+        if search_params.period.is_some() || search_params.audience != Visibility::AuthorOnly {
+            return Ok(self.read_history_items(search_params)?.len());
         }
-        let content = search_params.content;
-        if content.is_some() {
-            if content.is_exact {
-                query = query.filter(history_items::content.eq(content.exact_text()));
-            } else {
-                query = query.filter(history_items::content.like(content.search_pattern()));
+        let mut connection = self.db_connection()?;
+        let count = history_items::table
+            .filter(history_item_filter(&search_params))
+            .count()
+            .get_result::<i64>(&mut connection)
+            .map_err(|e| history_loading_error(&search_params, e))?;
+        Ok(count as usize)
+    }
+
+    /// Returns the number of history items in each year, sorted by year.
+    /// Uncertain items are counted in the earliest year they may have happened in.
+    pub fn count_history_items_per_year(
+        &self,
+        search_params: HistoryItemSearchParams,
+    ) -> Result<Vec<(Year, usize)>, LoreCoreError> {
+        if search_params.period.is_some() || search_params.audience != Visibility::AuthorOnly {
+            let mut counts: BTreeMap<Year, usize> = BTreeMap::new();
+            for item in self.read_history_items(search_params)? {
+                *counts.entry(item.earliest_year()).or_default() += 1;
             }
+            return Ok(counts.into_iter().collect());
         }
-        let mut items: Vec<_> = query
-            .load::<SqlHistoryItem>(&mut connection)
-            .map_err(|e| {
-                sql_loading_error("history items", vec![("year", &year), ("day", &day)], e)
-            })?
+        let mut connection = self.db_connection()?;
+        let exact = DatePrecision::Exact.name();
+        let mut counts: BTreeMap<Year, usize> = history_items::table
+            .filter(history_item_filter(&search_params))
+            .filter(history_items::precision.eq(exact))
+            .group_by(history_items::year)
+            .select((history_items::year, count_star()))
+            .load::<(i32, i64)>(&mut connection)
+            .map_err(|e| history_loading_error(&search_params, e))?
             .into_iter()
-            .map(|item| item.to_history_item())
+            .map(|(year, count)| (year.into(), count as usize))
             .collect();
-        items.sort();
-        Ok(items)
+        let uncertain_items = history_items::table
+            .filter(history_item_filter(&search_params))
+            .filter(history_items::precision.ne(exact))
+            .load::<SqlHistoryItem>(&mut connection)
+            .map_err(|e| history_loading_error(&search_params, e))?;
+        for item in uncertain_items.into_iter() {
+            *counts
+                .entry(item.to_history_item()?.earliest_year())
+                .or_default() += 1;
+        }
+        Ok(counts.into_iter().collect())
     }
+
+    pub fn change_history_item_precision(
+        &self,
+        timestamp: Timestamp,
+        precision: DatePrecision,
+    ) -> Result<(), LoreCoreError> {
+        check_precision(&precision)?;
+        let mut connection = self.db_connection()?;
+        diesel::update(
+            history_items::table.filter(history_items::timestamp.eq(timestamp.to_int())),
+        )
+        .set((
+            history_items::precision.eq(precision.name()),
+            history_items::range_years.eq(precision.range_years() as i32),
+        ))
+        .execute(&mut connection)
+        .map_err(|e| {
+            LoreCoreError::SqlError(
+                "Changing history item precision in database failed: ".to_string() + &e.to_string(),
+            )
+        })?;
+        Ok(())
+    }
+}
+
+pub(super) fn set_history_item_dates(
+    connection: &mut SqliteConnection,
+    dates: Vec<(Timestamp, Year, Day)>,
+) -> QueryResult<()> {
+    for (timestamp, year, day) in dates.into_iter() {
+        diesel::update(
+            history_items::table.filter(history_items::timestamp.eq(timestamp.to_int())),
+        )
+        .set((
+            history_items::year.eq(year.to_int()),
+            history_items::day.eq(day.to_int() as i32),
+        ))
+        .execute(connection)?;
+    }
+    Ok(())
+}
+
+/// Fails for ranges longer than the database can store.
+fn check_precision(precision: &DatePrecision) -> Result<(), LoreCoreError> {
+    if precision.range_years() > i32::MAX as u32 {
+        return Err(LoreCoreError::InputError(format!(
+            "A history item cannot span a {}, at most {} years.",
+            precision,
+            i32::MAX
+        )));
+    }
+    Ok(())
+}
+
+type HistoryItemFilter = Box<dyn BoxableExpression<history_items::table, Sqlite, SqlType = Bool>>;
+
+fn history_item_filter(search_params: &HistoryItemSearchParams) -> HistoryItemFilter {
+    let mut filter: HistoryItemFilter = Box::new(true.into_sql::<Bool>());
+    if let Some(year) = search_params.year {
+        filter = Box::new(filter.and(history_items::year.eq(year.to_int())));
+    }
+    if let Some(day) = search_params.day {
+        filter = Box::new(filter.and(history_items::day.eq(day.to_int() as i32)));
+    }
+    if let Some(timestamp) = search_params.timestamp {
+        filter = Box::new(filter.and(history_items::timestamp.eq(timestamp.to_int())));
+    }
+    let content = &search_params.content;
+    if content.is_some() {
+        if content.is_exact {
+            filter = Box::new(filter.and(history_items::content.eq(content.exact_text())));
+        } else {
+            filter = Box::new(filter.and(history_items::content.like(content.search_pattern())));
+        }
+    }
+    if let Some((from, to)) = search_params.period {
+        // Uncertain items may have happened up to 99 years before their stored year and
+        // recurring items repeat after it, so this only narrows down the candidates.
+        let latest_year = history_items::year + history_items::range_years;
+        filter = Box::new(
+            filter.and(
+                history_items::year
+                    .le(to.to_int().saturating_add(99))
+                    .and(latest_year.ge(from.to_int().saturating_sub(99)))
+                    .or(history_items::timestamp
+                        .eq_any(recurrences::table.select(recurrences::timestamp))),
+            ),
+        );
+    }
+    if let Some(tag) = &search_params.tag {
+        let (from, until) = nested_tag_range(tag);
+        filter = Box::new(
+            filter.and(
+                history_items::timestamp.eq_any(
+                    history_item_tags::table
+                        .filter(
+                            history_item_tags::tag
+                                .eq(tag.to_string())
+                                .or(history_item_tags::tag
+                                    .ge(from)
+                                    .and(history_item_tags::tag.lt(until))),
+                        )
+                        .select(history_item_tags::timestamp),
+                ),
+            ),
+        );
+    }
+    filter
+}
+
+fn history_loading_error<E>(search_params: &HistoryItemSearchParams, err: E) -> LoreCoreError
+where
+    E: std::fmt::Display,
+{
+    sql_loading_error(
+        "history items",
+        vec![("year", &search_params.year), ("day", &search_params.day)],
+        err,
+    )
 }
@@ -0,0 +1,90 @@
+use std::collections::BTreeMap;
+
+use crate::{errors::LoreCoreError, types::*};
+
+use super::{lore_database::LoreDatabase, search_params::HistoryItemSearchParams};
+
+pub(super) const LIFE_EVENT_KINDS: [LifeEventKind; 4] = [
+    LifeEventKind::Birth,
+    LifeEventKind::Founding,
+    LifeEventKind::Death,
+    LifeEventKind::Dissolution,
+];
+
+impl LoreDatabase {
+    /// Derives the lifespan of every entity listed under `births`, `foundings`, `deaths` or
+    /// `dissolutions` in the properties of a history item.
+    /// If several history items claim the start or end of the same entity, the earliest counts.
+    /// Only history items visible to `audience` are used, and hidden entities are left out.
+    pub fn read_lifespans(
+        &self,
+        audience: Visibility,
+    ) -> Result<BTreeMap<Label, Lifespan>, LoreCoreError> {
+        let items =
+            self.read_history_items(HistoryItemSearchParams::empty().for_audience(audience))?;
+        Ok(lifespans(&items))
+    }
+
+    /// The lifespan of `label`, which is empty if no history item visible to `audience` records
+    /// its start or end.
+    pub fn read_lifespan(
+        &self,
+        label: &Label,
+        audience: Visibility,
+    ) -> Result<Lifespan, LoreCoreError> {
+        Ok(self
+            .read_lifespans(audience)?
+            .remove(label)
+            .unwrap_or_default())
+    }
+
+    /// The age of `label` on `day` of `year`, see [`Lifespan::age_at`].
+    pub fn read_age(
+        &self,
+        label: &Label,
+        year: Year,
+        day: Day,
+        audience: Visibility,
+    ) -> Result<Option<u32>, LoreCoreError> {
+        Ok(self.read_lifespan(label, audience)?.age_at(year, day))
+    }
+
+    /// Returns the sorted labels of all entities existing in `year`, see [`Lifespan::exists_at`].
+    pub fn read_entities_existing_at(
+        &self,
+        year: Year,
+        audience: Visibility,
+    ) -> Result<Vec<Label>, LoreCoreError> {
+        Ok(self
+            .read_lifespans(audience)?
+            .into_iter()
+            .filter(|(_, lifespan)| lifespan.exists_at(year))
+            .map(|(label, _)| label)
+            .collect())
+    }
+}
+
+/// Derives lifespans from `items`, which are expected in chronological order.
+pub fn lifespans(items: &[HistoryItem]) -> BTreeMap<Label, Lifespan> {
+    let mut lifespans: BTreeMap<Label, Lifespan> = BTreeMap::new();
+    for item in items.iter() {
+        for kind in LIFE_EVENT_KINDS {
+            let event = LifeEvent {
+                kind,
+                year: item.year,
+                day: item.day,
+                timestamp: item.timestamp,
+            };
+            for label in item.properties.labels(kind.property_key()) {
+                let lifespan = lifespans.entry(label).or_default();
+                let slot = if kind.is_start() {
+                    &mut lifespan.start
+                } else {
+                    &mut lifespan.end
+                };
+                slot.get_or_insert(event);
+            }
+        }
+    }
+    lifespans
+}
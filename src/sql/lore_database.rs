@@ -10,16 +10,25 @@ pub struct LoreDatabase {
 
 const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
 
+/// The migration creating the reference index, which has to be filled for existing databases.
+const ENTITY_REFERENCES_MIGRATION: &str = "20261019230000";
+
 impl LoreDatabase {
     pub fn open(path: PathBuf) -> Result<Self, LoreCoreError> {
         let db = LoreDatabase { path };
-        db.db_connection()?
+        let rebuild_references = db
+            .db_connection()?
             .run_pending_migrations(MIGRATIONS)
             .map_err(|e| {
                 LoreCoreError::SqlError(
                     "Failed to run SQL database migrations: ".to_string() + &e.to_string(),
                 )
-            })?;
+            })?
+            .iter()
+            .any(|version| version.to_string() == ENTITY_REFERENCES_MIGRATION);
+        if rebuild_references {
+            db.rebuild_entity_references()?;
+        }
         Ok(db)
     }
 
@@ -1,7 +1,27 @@
+pub mod analytics;
+pub mod anchor;
+pub mod calendar;
+pub mod consistency;
 pub mod entity;
+pub mod era;
+pub mod genealogy;
+pub mod graph;
 pub mod history;
+pub mod lifespan;
 pub mod lore_database;
+pub mod path;
+pub mod property_schema;
+pub mod recurrence;
+pub mod reference;
 pub mod relationship;
+pub mod role;
+pub mod role_constraint;
 pub(super) mod schema;
 pub mod search_params;
+mod settings;
 mod sql_types;
+pub mod tag;
+pub mod timeline;
+pub mod validation;
+pub mod visibility;
+pub mod world;
@@ -0,0 +1,295 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BinaryHeap},
+};
+
+use crate::{errors::LoreCoreError, types::*};
+
+use super::{
+    lore_database::LoreDatabase,
+    search_params::{PathSearchParams, RelationshipSearchParams},
+};
+
+/// The most paths of equal cost returned by [`find_shortest_paths`], since their number can
+/// grow exponentially with the size of the graph.
+pub(crate) const MAX_PATHS: usize = 1000;
+
+/// A chain of relationships leading from `start` to `end`.
+/// Consecutive relationships share an entity, but may point in either direction
+/// if the path was searched for without regard to direction.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ConnectionPath {
+    pub start: Label,
+    pub end: Label,
+    pub relationships: Vec<EntityRelationship>,
+    /// The sum of the role weights along the path.
+    pub cost: u64,
+}
+
+impl ConnectionPath {
+    /// Describes the path as a chain of arrows pointing from parent to child,
+    /// for example `Boromir <-[parent]- Denethor -[ruler]-> Gondor`.
+    pub fn explanation(&self) -> String {
+        let mut explanation = self.start.to_string();
+        let mut current = self.start.to_str();
+        for rel in self.relationships.iter() {
+            let role = rel.role.to_str();
+            if rel.parent.to_str() == current {
+                current = rel.child.to_str();
+                explanation += &format!(" -[{}]-> {}", role, current);
+            } else {
+                current = rel.parent.to_str();
+                explanation += &format!(" <-[{}]- {}", role, current);
+            }
+        }
+        explanation
+    }
+}
+
+impl LoreDatabase {
+    /// Finds all cheapest paths from `start` to `end` through the relationships visible to
+    /// `audience`, sorted. See [`find_shortest_paths`].
+    pub fn read_shortest_paths(
+        &self,
+        start: &Label,
+        end: &Label,
+        params: PathSearchParams,
+        audience: Visibility,
+    ) -> Result<Vec<ConnectionPath>, LoreCoreError> {
+        let rels =
+            self.read_relationships(RelationshipSearchParams::empty().for_audience(audience))?;
+        Ok(find_shortest_paths(&rels, start, end, &params))
+    }
+}
+
+/// Finds all paths from `start` to `end` through `rels` with the smallest total role weight.
+/// Returns nothing if `end` cannot be reached, and a single empty path if `start` is `end`.
+/// At most 1000 paths are returned.
+pub fn find_shortest_paths(
+    rels: &[EntityRelationship],
+    start: &Label,
+    end: &Label,
+    params: &PathSearchParams,
+) -> Vec<ConnectionPath> {
+    let mut edges: BTreeMap<&str, Vec<(&str, usize, u32)>> = BTreeMap::new();
+    for (i, rel) in rels.iter().enumerate() {
+        if let Some(weight) = params.weight(rel) {
+            let (parent, child) = (rel.parent.to_str(), rel.child.to_str());
+            edges.entry(parent).or_default().push((child, i, weight));
+            if params.undirected {
+                edges.entry(child).or_default().push((parent, i, weight));
+            }
+        }
+    }
+
+    let mut costs: BTreeMap<&str, u64> = BTreeMap::from([(start.to_str(), 0)]);
+    let mut predecessors: BTreeMap<&str, Vec<(&str, usize)>> = BTreeMap::new();
+    let mut queue = BinaryHeap::from([Reverse((0, start.to_str()))]);
+    while let Some(Reverse((cost, node))) = queue.pop() {
+        if costs.get(node).is_some_and(|c| *c < cost) {
+            continue;
+        }
+        for &(next, rel_index, weight) in edges.get(node).into_iter().flatten() {
+            // Weights are at least 1, so predecessors of equal cost can never form a cycle.
+            let next_cost = cost + weight as u64;
+            match costs.get(next) {
+                Some(c) if *c < next_cost => {}
+                Some(c) if *c == next_cost => {
+                    predecessors
+                        .entry(next)
+                        .or_default()
+                        .push((node, rel_index));
+                }
+                _ => {
+                    costs.insert(next, next_cost);
+                    predecessors.insert(next, vec![(node, rel_index)]);
+                    queue.push(Reverse((next_cost, next)));
+                }
+            }
+        }
+    }
+
+    let Some(cost) = costs.get(end.to_str()).copied() else {
+        return Vec::new();
+    };
+    let chains = collect_chains(end.to_str(), start.to_str(), &predecessors);
+    let mut paths: Vec<_> = chains
+        .into_iter()
+        .map(|chain| ConnectionPath {
+            start: start.clone(),
+            end: end.clone(),
+            relationships: chain.into_iter().rev().map(|i| rels[i].clone()).collect(),
+            cost,
+        })
+        .collect();
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+/// Walks the predecessors back from `end` to `start`, yielding at most [`MAX_PATHS`] chains of
+/// relationship indices in reverse order.
+fn collect_chains(
+    end: &str,
+    start: &str,
+    predecessors: &BTreeMap<&str, Vec<(&str, usize)>>,
+) -> Vec<Vec<usize>> {
+    let mut chains = Vec::new();
+    let mut chain = Vec::new();
+    // The nodes on the current chain, each with the index of its next predecessor to try.
+    let mut stack = vec![(end, 0)];
+    while let Some((node, next)) = stack.last().copied() {
+        if node == start {
+            chains.push(chain.clone());
+            if chains.len() >= MAX_PATHS {
+                break;
+            }
+        }
+        match predecessors
+            .get(node)
+            .and_then(|previous| previous.get(next))
+        {
+            Some(&(previous, rel_index)) if node != start => {
+                if let Some(top) = stack.last_mut() {
+                    top.1 += 1;
+                }
+                chain.push(rel_index);
+                stack.push((previous, 0));
+            }
+            _ => {
+                stack.pop();
+                chain.pop();
+            }
+        }
+    }
+    chains
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rel(parent: &str, child: &str, role: &str) -> EntityRelationship {
+        EntityRelationship::new(parent.into(), child.into(), role.into())
+    }
+
+    #[test]
+    fn test_directed_path() {
+        let rels = vec![rel("a", "b", "r"), rel("b", "c", "r"), rel("a", "c", "s")];
+        let paths =
+            find_shortest_paths(&rels, &"a".into(), &"c".into(), &PathSearchParams::empty());
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].relationships, vec![rel("a", "c", "s")]);
+        assert_eq!(paths[0].explanation(), "a -[s]-> c");
+
+        let paths =
+            find_shortest_paths(&rels, &"c".into(), &"a".into(), &PathSearchParams::empty());
+        assert!(paths.is_empty());
+    }
+
+    #[test]
+    fn test_undirected_path_explanation() {
+        let rels = vec![rel("b", "a", "r"), rel("b", "c", "s")];
+        let paths = find_shortest_paths(
+            &rels,
+            &"a".into(),
+            &"c".into(),
+            &PathSearchParams::new(true),
+        );
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].explanation(), "a <-[r]- b -[s]-> c");
+    }
+
+    #[test]
+    fn test_weights_and_exclusions() {
+        let rels = vec![rel("a", "b", "r"), rel("b", "c", "r"), rel("a", "c", "s")];
+        let params = PathSearchParams::empty().with_role_weight("s".into(), 3);
+        let paths = find_shortest_paths(&rels, &"a".into(), &"c".into(), &params);
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].cost, 2);
+        assert_eq!(paths[0].relationships.len(), 2);
+
+        let params = PathSearchParams::empty().excluding_role("r".into());
+        let paths = find_shortest_paths(&rels, &"a".into(), &"c".into(), &params);
+        assert_eq!(paths[0].relationships, vec![rel("a", "c", "s")]);
+    }
+
+    #[test]
+    fn test_huge_weights_are_summed() {
+        let rels = vec![rel("a", "b", "r"), rel("b", "c", "r")];
+        let params = PathSearchParams::empty().with_role_weight("r".into(), u32::MAX);
+        let paths = find_shortest_paths(&rels, &"a".into(), &"c".into(), &params);
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].cost, 2 * u32::MAX as u64);
+    }
+
+    #[test]
+    fn test_huge_weights_on_undirected_cycle() {
+        let rels = vec![
+            rel("a", "b", "far"),
+            rel("a", "c", "far"),
+            rel("b", "c", "near"),
+        ];
+        let params = PathSearchParams::new(true).with_role_weight("far".into(), u32::MAX);
+        let paths = find_shortest_paths(&rels, &"a".into(), &"b".into(), &params);
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].relationships, vec![rel("a", "b", "far")]);
+        assert_eq!(paths[0].cost, u32::MAX as u64);
+    }
+
+    #[test]
+    fn test_number_of_paths_is_bounded() {
+        // A chain of 12 diamonds has 2^12 equally short paths.
+        let mut rels = Vec::new();
+        for i in 0..12 {
+            let (from, to) = (format!("n{}", i), format!("n{}", i + 1));
+            for middle in ["x", "y"] {
+                let middle = format!("{}{}", middle, i);
+                rels.push(rel(&from, &middle, "r"));
+                rels.push(rel(&middle, &to, "r"));
+            }
+        }
+        let paths = find_shortest_paths(
+            &rels,
+            &"n0".into(),
+            &"n12".into(),
+            &PathSearchParams::empty(),
+        );
+        assert_eq!(paths.len(), MAX_PATHS);
+        assert!(paths.iter().all(|path| path.cost == 24));
+    }
+
+    #[test]
+    fn test_all_equally_short_paths_are_returned() {
+        let rels = vec![
+            rel("a", "b", "r"),
+            rel("a", "c", "r"),
+            rel("b", "d", "r"),
+            rel("c", "d", "r"),
+            rel("a", "d", "r"),
+            rel("a", "d", "s"),
+        ];
+        let params = PathSearchParams::empty().with_role_weight("r".into(), 2);
+        let paths = find_shortest_paths(&rels, &"a".into(), &"d".into(), &params);
+        assert_eq!(paths.len(), 1);
+        let paths =
+            find_shortest_paths(&rels, &"a".into(), &"d".into(), &PathSearchParams::empty());
+        assert_eq!(paths.len(), 2);
+        let paths = find_shortest_paths(
+            &rels[..4],
+            &"a".into(),
+            &"d".into(),
+            &PathSearchParams::empty(),
+        );
+        assert_eq!(paths.len(), 2);
+        assert!(paths.iter().all(|path| path.cost == 2));
+    }
+
+    #[test]
+    fn test_path_to_self_is_empty() {
+        let paths = find_shortest_paths(&[], &"a".into(), &"a".into(), &PathSearchParams::empty());
+        assert_eq!(paths.len(), 1);
+        assert!(paths[0].relationships.is_empty());
+        assert_eq!(paths[0].explanation(), "a");
+    }
+}
@@ -0,0 +1,84 @@
+use ::diesel::prelude::*;
+
+use crate::{
+    errors::{sql_loading_error, LoreCoreError},
+    types::{property_schema::*, *},
+};
+
+use super::{
+    lore_database::LoreDatabase, schema::property_declarations,
+    search_params::HistoryItemSearchParams, sql_types::SqlPropertyDeclaration,
+};
+
+impl LoreDatabase {
+    /// Declares keys that history item properties may use, replacing earlier declarations of
+    /// the same keys. Fails for built-in keys such as `is_secret`.
+    pub fn write_property_declarations(
+        &self,
+        declarations: Vec<PropertyDeclaration>,
+    ) -> Result<(), LoreCoreError> {
+        if let Some(declaration) = declarations.iter().find(|declaration| {
+            BUILT_IN_PROPERTIES
+                .iter()
+                .any(|(key, _)| *key == declaration.key)
+        }) {
+            return Err(LoreCoreError::InputError(format!(
+                "The property \"{}\" is built in and cannot be declared.",
+                declaration.key
+            )));
+        }
+        let mut connection = self.db_connection()?;
+        for declaration in declarations.iter() {
+            diesel::replace_into(property_declarations::table)
+                .values(&declaration.to_sql_property_declaration())
+                .execute(&mut connection)
+                .map_err(|e| {
+                    LoreCoreError::SqlError(
+                        "Writing property declaration to database failed: ".to_string()
+                            + &e.to_string(),
+                    )
+                })?;
+        }
+        Ok(())
+    }
+
+    /// Returns the declared properties, sorted by key. Built-in keys are not included.
+    pub fn read_property_declarations(&self) -> Result<Vec<PropertyDeclaration>, LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        property_declarations::table
+            .order(property_declarations::key)
+            .load::<SqlPropertyDeclaration>(&mut connection)
+            .map_err(|e| sql_loading_error("property declarations", vec![], e))?
+            .iter()
+            .map(|declaration| declaration.to_property_declaration())
+            .collect()
+    }
+
+    /// Removes the declaration of `key`. Fails while history items still use the key.
+    pub fn delete_property_declaration(&self, key: &str) -> Result<(), LoreCoreError> {
+        let items = self.read_history_items(HistoryItemSearchParams::empty())?;
+        if let Some(item) = items.iter().find(|item| item.properties.get(key).is_some()) {
+            return Err(LoreCoreError::InputError(format!(
+                "The property \"{}\" is still used by history item {}.",
+                key, item.timestamp
+            )));
+        }
+        let mut connection = self.db_connection()?;
+        diesel::delete(property_declarations::table.filter(property_declarations::key.eq(key)))
+            .execute(&mut connection)
+            .map_err(|e| {
+                LoreCoreError::SqlError(
+                    "Deleting property declaration from database failed: ".to_string()
+                        + &e.to_string(),
+                )
+            })?;
+        Ok(())
+    }
+
+    pub(super) fn validate_properties(
+        &self,
+        properties: &HistoryItemProperties,
+    ) -> Result<(), LoreCoreError> {
+        validate_properties(properties, &self.read_property_declarations()?)
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::BTreeMap;
+
+use ::diesel::prelude::*;
+
+use crate::{
+    errors::{sql_loading_error, LoreCoreError},
+    types::*,
+};
+
+use super::{
+    lore_database::LoreDatabase, schema::recurrences, search_params::HistoryItemSearchParams,
+    sql_types::SqlRecurrence,
+};
+
+/// The most repetitions of recurring history items that a single read may create.
+pub(crate) const MAX_OCCURRENCES: usize = 100_000;
+
+impl LoreDatabase {
+    /// Makes the history item at `timestamp` recur, replacing any previous recurrence.
+    /// Its repetitions are not stored, but added by [`LoreDatabase::read_history_items`]
+    /// when searching within a period.
+    pub fn write_recurrence(
+        &self,
+        timestamp: Timestamp,
+        recurrence: Recurrence,
+    ) -> Result<(), LoreCoreError> {
+        if recurrence.every_years == 0 {
+            return Err(LoreCoreError::InputError(
+                "A history item cannot recur every 0 years.".to_string(),
+            ));
+        }
+        let params = HistoryItemSearchParams::new(None, None, Some(timestamp), None);
+        let item = match self.read_history_items(params)?.into_iter().next() {
+            Some(item) => item,
+            None => {
+                return Err(LoreCoreError::InputError(format!(
+                    "History item {} does not exist.",
+                    timestamp
+                )))
+            }
+        };
+        if let Some(until) = recurrence.until.filter(|until| *until < item.year) {
+            return Err(LoreCoreError::InputError(format!(
+                "History item {} cannot recur until {}, before its year {}.",
+                timestamp, until, item.year
+            )));
+        }
+        let mut connection = self.db_connection()?;
+        diesel::replace_into(recurrences::table)
+            .values(&recurrence.to_sql_recurrence(timestamp))
+            .execute(&mut connection)
+            .map_err(|e| {
+                LoreCoreError::SqlError(
+                    "Writing recurrence to database failed: ".to_string() + &e.to_string(),
+                )
+            })?;
+        Ok(())
+    }
+
+    /// Returns the recurrences of all recurring history items, by timestamp.
+    pub fn read_recurrences(&self) -> Result<BTreeMap<Timestamp, Recurrence>, LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        let recurrences = recurrences::table
+            .load::<SqlRecurrence>(&mut connection)
+            .map_err(|e| sql_loading_error("recurrences", vec![], e))?
+            .iter()
+            .map(|recurrence| recurrence.to_recurrence())
+            .collect();
+        Ok(recurrences)
+    }
+
+    pub fn delete_recurrence(&self, timestamp: Timestamp) -> Result<(), LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        diesel::delete(recurrences::table.filter(recurrences::timestamp.eq(timestamp.to_int())))
+            .execute(&mut connection)
+            .map_err(|e| {
+                LoreCoreError::SqlError(
+                    "Deleting recurrence from database failed: ".to_string() + &e.to_string(),
+                )
+            })?;
+        Ok(())
+    }
+}
@@ -0,0 +1,321 @@
+use ::diesel::prelude::*;
+use diesel::{dsl::exists, sql_types::Bool, sqlite::Sqlite, SqliteConnection};
+
+use crate::{
+    errors::{sql_loading_error, LoreCoreError},
+    extractions::extract_entity_references,
+    types::*,
+};
+
+use super::{
+    lore_database::LoreDatabase,
+    schema::{description_references, entities, history_item_references, history_items},
+    sql_types::{
+        SqlDescriptionReference, SqlEntityColumn, SqlHistoryItem, SqlHistoryItemReference,
+    },
+    validation::ReferenceSource,
+    visibility::redact_history_item,
+};
+
+impl LoreDatabase {
+    /// Returns the places that reference `label` via `\entityref{label}`, descriptions of
+    /// entity columns first, then history items by timestamp. Only places visible to `audience`
+    /// are returned, and none if `label` is hidden from it.
+    pub fn read_references_to(
+        &self,
+        label: &Label,
+        audience: Visibility,
+    ) -> Result<Vec<ReferenceSource>, LoreCoreError> {
+        let hidden = self.read_redactions(audience)?;
+        if hidden.as_ref().is_some_and(|hidden| hidden.contains(label)) {
+            return Ok(Vec::new());
+        }
+        let mut connection = self.db_connection()?;
+        let mut sources: Vec<_> = description_references::table
+            .filter(description_references::target.eq(label.to_str()))
+            .filter(visible_description_filter(audience))
+            .order((
+                description_references::label,
+                description_references::descriptor,
+            ))
+            .load::<SqlDescriptionReference>(&mut connection)
+            .map_err(|e| sql_loading_error("description references", vec![("target", label)], e))?
+            .into_iter()
+            .map(|reference| {
+                ReferenceSource::EntityColumn(reference.label.into(), reference.descriptor.into())
+            })
+            .collect();
+        let timestamps = match hidden {
+            None => self.read_referencing_timestamps(label)?,
+            Some(_) => {
+                let mut timestamps: Vec<_> = self
+                    .read_history_items_referencing(label, audience)?
+                    .into_iter()
+                    .map(|item| item.timestamp)
+                    .collect();
+                timestamps.sort();
+                timestamps
+            }
+        };
+        sources.extend(timestamps.into_iter().map(ReferenceSource::HistoryItem));
+        Ok(sources)
+    }
+
+    /// Returns the history items mentioning `label` in their content or additional concerns,
+    /// in the order of [`HistoryItem`]. Only items visible to `audience` are returned, with
+    /// references to hidden entities redacted, and none if `label` is hidden from it.
+    pub fn read_history_items_referencing(
+        &self,
+        label: &Label,
+        audience: Visibility,
+    ) -> Result<Vec<HistoryItem>, LoreCoreError> {
+        let hidden = self.read_redactions(audience)?;
+        if hidden.as_ref().is_some_and(|hidden| hidden.contains(label)) {
+            return Ok(Vec::new());
+        }
+        let timestamps: Vec<_> = self
+            .read_referencing_timestamps(label)?
+            .iter()
+            .map(|timestamp| timestamp.to_int())
+            .collect();
+        let mut connection = self.db_connection()?;
+        let mut items = history_items::table
+            .filter(history_items::timestamp.eq_any(timestamps))
+            .load::<SqlHistoryItem>(&mut connection)
+            .map_err(|e| sql_loading_error("history items", vec![("referencing", label)], e))?
+            .into_iter()
+            .map(|item| item.to_history_item())
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(hidden) = hidden {
+            items = items
+                .into_iter()
+                .filter(|item| item.properties.visibility().is_visible_to(audience))
+                .map(|item| redact_history_item(item, &hidden))
+                .collect();
+        }
+        items.sort();
+        Ok(items)
+    }
+
+    /// Returns the labels referenced in any description of the entity `label` visible to
+    /// `audience`, sorted. Labels hidden from `audience` are left out.
+    pub fn read_references_from_entity(
+        &self,
+        label: &Label,
+        audience: Visibility,
+    ) -> Result<Vec<Label>, LoreCoreError> {
+        let hidden = self.read_redactions(audience)?.unwrap_or_default();
+        if hidden.contains(label) {
+            return Ok(Vec::new());
+        }
+        let mut connection = self.db_connection()?;
+        let targets = description_references::table
+            .filter(description_references::label.eq(label.to_str()))
+            .filter(visible_description_filter(audience))
+            .select(description_references::target)
+            .distinct()
+            .order(description_references::target)
+            .load::<String>(&mut connection)
+            .map_err(|e| sql_loading_error("description references", vec![("label", label)], e))?
+            .into_iter()
+            .map(Label::from)
+            .filter(|target| !hidden.contains(target))
+            .collect();
+        Ok(targets)
+    }
+
+    /// Recreates the reference index from all descriptions and history items.
+    /// It is kept in sync by every method changing them, so this is only needed for databases
+    /// edited by other means. History items with unparsable properties are indexed by their
+    /// content only, so that stored data cannot make this fail.
+    pub fn rebuild_entity_references(&self) -> Result<(), LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        connection
+            .transaction(|connection| {
+                diesel::delete(description_references::table).execute(connection)?;
+                diesel::delete(history_item_references::table).execute(connection)?;
+                for col in entities::table.load::<SqlEntityColumn>(connection)? {
+                    let col = col.to_entity_column();
+                    index_description_references(
+                        connection,
+                        &col.label,
+                        &col.descriptor,
+                        &col.description,
+                    )?;
+                }
+                for row in history_items::table.load::<SqlHistoryItem>(connection)? {
+                    index_history_item_references(connection, &indexable_history_item(row))?;
+                }
+                Ok(())
+            })
+            .map_err(|e: diesel::result::Error| {
+                LoreCoreError::SqlError(
+                    "Rebuilding entity references in database failed: ".to_string()
+                        + &e.to_string(),
+                )
+            })
+    }
+
+    fn read_referencing_timestamps(&self, label: &Label) -> Result<Vec<Timestamp>, LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        let timestamps = history_item_references::table
+            .filter(history_item_references::target.eq(label.to_str()))
+            .select(history_item_references::timestamp)
+            .order(history_item_references::timestamp)
+            .load::<i64>(&mut connection)
+            .map_err(|e| sql_loading_error("history item references", vec![("target", label)], e))?
+            .into_iter()
+            .map(|timestamp| timestamp.into())
+            .collect();
+        Ok(timestamps)
+    }
+}
+
+/// Replaces the indexed references of the column `(label, descriptor)` by those in
+/// `description`.
+pub(super) fn index_description_references(
+    connection: &mut SqliteConnection,
+    label: &Label,
+    descriptor: &Descriptor,
+    description: &Description,
+) -> QueryResult<()> {
+    unindex_description_references(connection, label, Some(descriptor))?;
+    let mut targets = extract_entity_references(description.to_str());
+    targets.sort();
+    targets.dedup();
+    let references: Vec<_> = targets
+        .into_iter()
+        .map(|target| SqlDescriptionReference {
+            label: label.to_string(),
+            descriptor: descriptor.to_string(),
+            target: target.to_string(),
+        })
+        .collect();
+    diesel::insert_into(description_references::table)
+        .values(&references)
+        .execute(connection)?;
+    Ok(())
+}
+
+/// Removes the indexed references of the column `(label, descriptor)`, or of all columns
+/// of the entity if `descriptor` is `None`.
+pub(super) fn unindex_description_references(
+    connection: &mut SqliteConnection,
+    label: &Label,
+    descriptor: Option<&Descriptor>,
+) -> QueryResult<()> {
+    match descriptor {
+        Some(descriptor) => diesel::delete(
+            description_references::table
+                .filter(description_references::label.eq(label.to_str()))
+                .filter(description_references::descriptor.eq(descriptor.to_str())),
+        )
+        .execute(connection)?,
+        None => diesel::delete(
+            description_references::table.filter(description_references::label.eq(label.to_str())),
+        )
+        .execute(connection)?,
+    };
+    Ok(())
+}
+
+/// Moves the indexed references of the entity `old_label`, or of its column
+/// `old_descriptor`, to their new source.
+pub(super) fn move_description_references(
+    connection: &mut SqliteConnection,
+    (old_label, old_descriptor): (&Label, Option<&Descriptor>),
+    (new_label, new_descriptor): (&Label, Option<&Descriptor>),
+) -> QueryResult<()> {
+    match (old_descriptor, new_descriptor) {
+        (Some(old_descriptor), Some(new_descriptor)) => diesel::update(
+            description_references::table
+                .filter(description_references::label.eq(old_label.to_str()))
+                .filter(description_references::descriptor.eq(old_descriptor.to_str())),
+        )
+        .set((
+            description_references::label.eq(new_label.to_str()),
+            description_references::descriptor.eq(new_descriptor.to_str()),
+        ))
+        .execute(connection)?,
+        _ => diesel::update(
+            description_references::table
+                .filter(description_references::label.eq(old_label.to_str())),
+        )
+        .set(description_references::label.eq(new_label.to_str()))
+        .execute(connection)?,
+    };
+    Ok(())
+}
+
+/// Replaces the indexed references of the history item by those it currently contains.
+pub(super) fn index_history_item_references(
+    connection: &mut SqliteConnection,
+    item: &HistoryItem,
+) -> QueryResult<()> {
+    unindex_history_item_references(connection, item.timestamp)?;
+    let references: Vec<_> = item
+        .entity_references()
+        .into_iter()
+        .map(|target| SqlHistoryItemReference {
+            timestamp: item.timestamp.to_int(),
+            target: target.to_string(),
+        })
+        .collect();
+    diesel::insert_into(history_item_references::table)
+        .values(&references)
+        .execute(connection)?;
+    Ok(())
+}
+
+pub(super) fn unindex_history_item_references(
+    connection: &mut SqliteConnection,
+    timestamp: Timestamp,
+) -> QueryResult<()> {
+    diesel::delete(
+        history_item_references::table
+            .filter(history_item_references::timestamp.eq(timestamp.to_int())),
+    )
+    .execute(connection)?;
+    Ok(())
+}
+
+/// Re-indexes the history item at `timestamp` after a change, if it exists.
+pub(super) fn reindex_history_item_references(
+    connection: &mut SqliteConnection,
+    timestamp: Timestamp,
+) -> QueryResult<()> {
+    let row = history_items::table
+        .filter(history_items::timestamp.eq(timestamp.to_int()))
+        .first::<SqlHistoryItem>(connection)
+        .optional()?;
+    match row {
+        Some(row) => index_history_item_references(connection, &indexable_history_item(row)),
+        None => unindex_history_item_references(connection, timestamp),
+    }
+}
+
+/// Converts a stored history item for indexing, ignoring unparsable properties.
+fn indexable_history_item(row: SqlHistoryItem) -> HistoryItem {
+    let properties = HistoryItemProperties::try_from(&row.properties)
+        .unwrap_or_else(|_| HistoryItemProperties::none());
+    HistoryItem::new(
+        row.timestamp.into(),
+        row.year.into(),
+        row.day.into(),
+        row.content.as_str().into(),
+    )
+    .with_properties(properties)
+}
+
+type DescriptionReferenceFilter =
+    Box<dyn BoxableExpression<description_references::table, Sqlite, SqlType = Bool>>;
+
+/// Matches the references made in entity columns visible to `audience`.
+fn visible_description_filter(audience: Visibility) -> DescriptionReferenceFilter {
+    Box::new(exists(
+        entities::table
+            .filter(entities::label.eq(description_references::label))
+            .filter(entities::descriptor.eq(description_references::descriptor))
+            .filter(entities::visibility.le(audience.to_int())),
+    ))
+}
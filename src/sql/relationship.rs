@@ -1,15 +1,26 @@
 use ::diesel::prelude::*;
-use diesel::{QueryDsl, RunQueryDsl};
+use diesel::{dsl::count_star, sql_types::Bool, sqlite::Sqlite, QueryDsl, RunQueryDsl};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+};
 
 use crate::errors::{sql_loading_error, LoreCoreError};
 use crate::types::*;
 
 use super::search_params::RelationshipSearchParams;
 use super::sql_types::*;
+use super::visibility::concerns_hidden_entity;
 use super::{lore_database::LoreDatabase, schema::relationships};
 
 impl LoreDatabase {
+    /// Fails without writing anything if a relationship ends before it starts, if the role
+    /// strictness is strict and a role is missing from the role registry, or if the
+    /// relationships would break any role constraints.
     pub fn write_relationships(&self, rels: Vec<EntityRelationship>) -> Result<(), LoreCoreError> {
+        rels.iter().try_for_each(check_validity)?;
+        self.check_roles(&rels)?;
+        self.check_role_constraints(&rels, &[])?;
         let mut connection = self.db_connection()?;
         for rel in rels.into_iter() {
             let rel = rel.to_sql_entity_relationship();
@@ -25,26 +36,187 @@ impl LoreDatabase {
         Ok(())
     }
 
+    /// Handles undeclared roles and role constraints like [`LoreDatabase::write_relationships`].
+    /// Fails if a relationship with the new role already exists between the same entities.
     pub fn change_relationship_role(
         &self,
         old_relationship: EntityRelationship,
         new_role: &Role,
     ) -> Result<(), LoreCoreError> {
+        let new_relationship = EntityRelationship {
+            role: new_role.clone(),
+            ..old_relationship.clone()
+        };
+        self.check_roles(std::slice::from_ref(&new_relationship))?;
+        self.apply_relationship_changes(vec![(old_relationship, new_relationship)])
+    }
+
+    /// Points the relationship away from its parent to `new_parent`.
+    /// Fails if an identical relationship from `new_parent` already exists.
+    pub fn change_relationship_parent(
+        &self,
+        old_relationship: EntityRelationship,
+        new_parent: &Parent,
+    ) -> Result<(), LoreCoreError> {
+        let new_relationship = EntityRelationship {
+            parent: new_parent.clone(),
+            ..old_relationship.clone()
+        };
+        self.apply_relationship_changes(vec![(old_relationship, new_relationship)])
+    }
+
+    /// Points the relationship away from its child to `new_child`.
+    /// Fails if an identical relationship to `new_child` already exists.
+    pub fn change_relationship_child(
+        &self,
+        old_relationship: EntityRelationship,
+        new_child: &Child,
+    ) -> Result<(), LoreCoreError> {
+        let new_relationship = EntityRelationship {
+            child: new_child.clone(),
+            ..old_relationship.clone()
+        };
+        self.apply_relationship_changes(vec![(old_relationship, new_relationship)])
+    }
+
+    /// Replaces `from` by `to` as parent and child of all relationships and returns how many
+    /// relationships were moved. Nothing is moved if any of them would collide with an existing one.
+    pub fn move_relationships(&self, from: &Label, to: &Label) -> Result<usize, LoreCoreError> {
+        if from == to {
+            return Ok(0);
+        }
+        let replace = |label: &str| -> String {
+            if label == from.to_str() {
+                to.to_string()
+            } else {
+                label.to_string()
+            }
+        };
+        let changes: Vec<_> = self
+            .read_relationships(RelationshipSearchParams::empty())?
+            .into_iter()
+            .filter(|rel| {
+                rel.parent.to_str() == from.to_str() || rel.child.to_str() == from.to_str()
+            })
+            .map(|rel| {
+                let new_relationship = EntityRelationship {
+                    parent: replace(rel.parent.to_str()).into(),
+                    child: replace(rel.child.to_str()).into(),
+                    ..rel.clone()
+                };
+                (rel, new_relationship)
+            })
+            .collect();
+        let count = changes.len();
+        self.apply_relationship_changes(changes)?;
+        Ok(count)
+    }
+
+    /// Gives all relationships with role `old_role` the role `new_role` instead, with the same
+    /// handling of undeclared roles as [`LoreDatabase::write_relationships`].
+    /// Nothing is renamed if any relationship would collide with an existing one.
+    pub fn rename_role(&self, old_role: &Role, new_role: &Role) -> Result<(), LoreCoreError> {
+        if old_role == new_role {
+            return Ok(());
+        }
+        let changes: Vec<_> = self
+            .read_relationships(RelationshipSearchParams::empty())?
+            .into_iter()
+            .filter(|rel| &rel.role == old_role)
+            .map(|rel| {
+                let new_relationship = EntityRelationship {
+                    role: new_role.clone(),
+                    ..rel.clone()
+                };
+                (rel, new_relationship)
+            })
+            .collect();
+        let new_rels: Vec<_> = changes.iter().map(|(_, new)| new.clone()).collect();
+        self.check_roles(&new_rels)?;
+        self.apply_relationship_changes(changes)
+    }
+
+    /// Replaces each first relationship by the second one, after making sure that no two
+    /// relationships end up with the same parent, child and role. Either all changes are applied
+    /// or none, so relationships may also swap their keys.
+    fn apply_relationship_changes(
+        &self,
+        changes: Vec<(EntityRelationship, EntityRelationship)>,
+    ) -> Result<(), LoreCoreError> {
+        let existing = self.read_relationships(RelationshipSearchParams::empty())?;
+        let collisions = find_collisions(&existing, &changes);
+        if !collisions.is_empty() {
+            let messages: Vec<_> = collisions.iter().map(|c| c.to_string()).collect();
+            return Err(LoreCoreError::InputError(messages.join("\n")));
+        }
+        let (old_rels, new_rels): (Vec<_>, Vec<_>) = changes.into_iter().unzip();
+        self.check_role_constraints(&new_rels, &old_rels)?;
+
+        let mut connection = self.db_connection()?;
+        connection
+            .transaction(|connection| {
+                let mut rows = Vec::new();
+                for (old_relationship, new_relationship) in old_rels.iter().zip(new_rels.iter()) {
+                    let old_relationship = old_relationship.to_sql_entity_relationship();
+                    let filter = relationships::parent
+                        .eq(old_relationship.parent)
+                        .and(relationships::child.eq(old_relationship.child))
+                        .and(relationships::role.eq(old_relationship.role));
+                    let stored = relationships::table
+                        .filter(filter.clone())
+                        .load::<SqlEntityRelationship>(connection)?;
+                    diesel::delete(relationships::table.filter(filter)).execute(connection)?;
+                    let new_relationship = new_relationship.to_sql_entity_relationship();
+                    rows.extend(stored.into_iter().map(|row| SqlEntityRelationship {
+                        parent: new_relationship.parent.clone(),
+                        child: new_relationship.child.clone(),
+                        role: new_relationship.role.clone(),
+                        ..row
+                    }));
+                }
+                diesel::insert_into(relationships::table)
+                    .values(&rows)
+                    .execute(connection)
+            })
+            .map_err(|e: diesel::result::Error| {
+                LoreCoreError::SqlError(
+                    "Changing relationship in database failed: ".to_string() + &e.to_string(),
+                )
+            })?;
+        Ok(())
+    }
+
+    /// Sets the years and days between which the relationship holds,
+    /// with the same checks as [`LoreDatabase::write_relationships`].
+    pub fn change_relationship_validity(
+        &self,
+        relationship: EntityRelationship,
+        start: Option<(Year, Day)>,
+        end: Option<(Year, Day)>,
+    ) -> Result<(), LoreCoreError> {
+        let new_relationship = relationship.clone().valid_between(start, end);
+        check_validity(&new_relationship)?;
+        self.check_role_constraints(std::slice::from_ref(&new_relationship), &[relationship])?;
         let mut connection = self.db_connection()?;
-        let old_relationship = old_relationship.to_sql_entity_relationship();
+        let new_relationship = new_relationship.to_sql_entity_relationship();
         diesel::update(
             relationships::table.filter(
                 relationships::parent
-                    .eq(old_relationship.parent)
-                    .and(relationships::child.eq(old_relationship.child))
-                    .and(relationships::role.eq(old_relationship.role)),
+                    .eq(&new_relationship.parent)
+                    .and(relationships::child.eq(&new_relationship.child))
+                    .and(relationships::role.eq(&new_relationship.role)),
             ),
         )
-        .set(relationships::role.eq(new_role.to_string()))
+        .set((
+            relationships::start_year.eq(new_relationship.start_year),
+            relationships::start_day.eq(new_relationship.start_day),
+            relationships::end_year.eq(new_relationship.end_year),
+            relationships::end_day.eq(new_relationship.end_day),
+        ))
         .execute(&mut connection)
         .map_err(|e| {
             LoreCoreError::SqlError(
-                "Changing relationship role in database failed: ".to_string() + &e.to_string(),
+                "Changing relationship validity in database failed: ".to_string() + &e.to_string(),
             )
         })?;
         Ok(())
@@ -73,42 +245,216 @@ impl LoreDatabase {
         Ok(())
     }
 
+    /// If the search params include inverses, the inverses of all stored relationships whose
+    /// roles have a declared inverse are returned as well.
     pub fn read_relationships(
         &self,
         search_params: RelationshipSearchParams,
     ) -> Result<Vec<EntityRelationship>, LoreCoreError> {
-        let mut connection = self.db_connection()?;
-        let mut query = relationships::table.into_boxed();
-        let parent = search_params.parent;
-        if parent.is_some() {
-            if parent.is_exact {
-                query = query.filter(relationships::parent.eq(parent.exact_text()));
-            } else {
-                query = query.filter(relationships::parent.like(parent.search_pattern()));
-            }
+        let mut rels = self.read_stored_relationships(&search_params)?;
+        if search_params.include_inverses {
+            let registry = self.read_role_registry()?;
+            let swapped_params = RelationshipSearchParams {
+                parent: search_params.child.clone(),
+                child: search_params.parent.clone(),
+                include_inverses: false,
+                valid_at: search_params.valid_at,
+                audience: search_params.audience,
+            };
+            let inverses: Vec<_> = self
+                .read_stored_relationships(&swapped_params)?
+                .iter()
+                .filter_map(|rel| registry.inverse_relationship(rel))
+                .collect();
+            rels.extend(inverses);
+            rels.sort();
+            rels.dedup();
         }
-        let child = search_params.child;
-        if child.is_some() {
-            if child.is_exact {
-                query = query.filter(relationships::child.eq(child.exact_text()));
-            } else {
-                query = query.filter(relationships::child.like(child.search_pattern()));
-            }
-        }
-        let rels = query
+        Ok(rels)
+    }
+
+    fn read_stored_relationships(
+        &self,
+        search_params: &RelationshipSearchParams,
+    ) -> Result<Vec<EntityRelationship>, LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        let rels = relationships::table
+            .filter(relationship_filter(search_params))
             .load::<SqlEntityRelationship>(&mut connection)
-            .map_err(|e| {
-                sql_loading_error(
-                    "relationships",
-                    vec![("parent", &parent), ("child", &child)],
-                    e,
-                )
-            })?;
+            .map_err(|e| relationship_loading_error(search_params, e))?;
         let mut rels: Vec<EntityRelationship> =
             rels.into_iter().map(|rel| rel.to_relationship()).collect();
+        if let Some(hidden) = self.read_redactions(search_params.audience)? {
+            rels.retain(|rel| !concerns_hidden_entity(rel, &hidden));
+        }
         rels.sort();
         Ok(rels)
     }
+
+    pub fn count_relationships(
+        &self,
+        search_params: RelationshipSearchParams,
+    ) -> Result<usize, LoreCoreError> {
+        if search_params.include_inverses || search_params.audience != Visibility::AuthorOnly {
+            return Ok(self.read_relationships(search_params)?.len());
+        }
+        let mut connection = self.db_connection()?;
+        let count = relationships::table
+            .filter(relationship_filter(&search_params))
+            .count()
+            .get_result::<i64>(&mut connection)
+            .map_err(|e| relationship_loading_error(&search_params, e))?;
+        Ok(count as usize)
+    }
+
+    /// Returns the number of relationships with each role, sorted by role.
+    pub fn count_relationships_per_role(
+        &self,
+        search_params: RelationshipSearchParams,
+    ) -> Result<Vec<(Role, usize)>, LoreCoreError> {
+        if search_params.include_inverses || search_params.audience != Visibility::AuthorOnly {
+            let mut counts: BTreeMap<Role, usize> = BTreeMap::new();
+            for rel in self.read_relationships(search_params)? {
+                *counts.entry(rel.role).or_default() += 1;
+            }
+            return Ok(counts.into_iter().collect());
+        }
+        let mut connection = self.db_connection()?;
+        let counts = relationships::table
+            .filter(relationship_filter(&search_params))
+            .group_by(relationships::role)
+            .select((relationships::role, count_star()))
+            .order_by(relationships::role)
+            .load::<(String, i64)>(&mut connection)
+            .map_err(|e| relationship_loading_error(&search_params, e))?
+            .into_iter()
+            .map(|(role, count)| (role.into(), count as usize))
+            .collect();
+        Ok(counts)
+    }
+}
+
+type RelationshipFilter = Box<dyn BoxableExpression<relationships::table, Sqlite, SqlType = Bool>>;
+
+fn relationship_filter(search_params: &RelationshipSearchParams) -> RelationshipFilter {
+    let mut filter: RelationshipFilter =
+        Box::new(relationships::visibility.le(search_params.audience.to_int()));
+    let parent = &search_params.parent;
+    if parent.is_some() {
+        if parent.is_exact {
+            filter = Box::new(filter.and(relationships::parent.eq(parent.exact_text())));
+        } else {
+            filter = Box::new(filter.and(relationships::parent.like(parent.search_pattern())));
+        }
+    }
+    let child = &search_params.child;
+    if child.is_some() {
+        if child.is_exact {
+            filter = Box::new(filter.and(relationships::child.eq(child.exact_text())));
+        } else {
+            filter = Box::new(filter.and(relationships::child.like(child.search_pattern())));
+        }
+    }
+    if let Some(year) = search_params.valid_at {
+        let year = year.to_int();
+        filter = Box::new(
+            filter
+                .and(
+                    relationships::start_year
+                        .is_null()
+                        .or(relationships::start_year.assume_not_null().le(year)),
+                )
+                .and(
+                    relationships::end_year
+                        .is_null()
+                        .or(relationships::end_year.assume_not_null().ge(year)),
+                ),
+        );
+    }
+    filter
+}
+
+fn relationship_loading_error<E>(search_params: &RelationshipSearchParams, err: E) -> LoreCoreError
+where
+    E: std::fmt::Display,
+{
+    sql_loading_error(
+        "relationships",
+        vec![
+            ("parent", &search_params.parent),
+            ("child", &search_params.child),
+            ("valid_at", &search_params.valid_at),
+        ],
+        err,
+    )
+}
+
+/// A change that would give a relationship the same parent, child and role as another one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RelationshipCollision {
+    pub old_relationship: EntityRelationship,
+    pub new_relationship: EntityRelationship,
+}
+
+impl Display for RelationshipCollision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (old, new) = (&self.old_relationship, &self.new_relationship);
+        write!(
+            f,
+            "Changing the relationship from \"{}\" to \"{}\" with role \"{}\" would duplicate the relationship from \"{}\" to \"{}\" with role \"{}\".",
+            old.parent, old.child, old.role, new.parent, new.child, new.role
+        )
+    }
+}
+
+fn check_validity(rel: &EntityRelationship) -> Result<(), LoreCoreError> {
+    if let (true, Some(start), Some(end)) = (rel.ends_before_it_starts(), rel.start, rel.end) {
+        return Err(LoreCoreError::InputError(format!(
+            "The relationship of \"{}\" to \"{}\" as \"{}\" ends in {} before it starts in {}.",
+            rel.child,
+            rel.parent,
+            rel.role,
+            format_date(end),
+            format_date(start)
+        )));
+    }
+    Ok(())
+}
+
+fn format_date((year, day): (Year, Day)) -> String {
+    match day == Day::NONE {
+        true => year.to_string(),
+        false => format!("day {} of {}", day, year),
+    }
+}
+
+fn relationship_key(rel: &EntityRelationship) -> (&str, &str, &str) {
+    (rel.parent.to_str(), rel.child.to_str(), rel.role.to_str())
+}
+
+/// Finds the changes whose new relationship has the same key as an unchanged relationship
+/// in `existing`, or as the new relationship of an earlier change.
+fn find_collisions(
+    existing: &[EntityRelationship],
+    changes: &[(EntityRelationship, EntityRelationship)],
+) -> Vec<RelationshipCollision> {
+    let changed_keys: BTreeSet<_> = changes
+        .iter()
+        .map(|(old, _)| relationship_key(old))
+        .collect();
+    let mut keys: BTreeSet<_> = existing
+        .iter()
+        .map(relationship_key)
+        .filter(|key| !changed_keys.contains(key))
+        .collect();
+    changes
+        .iter()
+        .filter(|(_, new)| !keys.insert(relationship_key(new)))
+        .map(|(old, new)| RelationshipCollision {
+            old_relationship: old.clone(),
+            new_relationship: new.clone(),
+        })
+        .collect()
 }
 
 pub fn extract_parents(rels: &[EntityRelationship]) -> Vec<Parent> {
@@ -140,6 +486,50 @@ pub fn extract_roles(rels: &[EntityRelationship]) -> Vec<Role> {
 mod tests {
     use super::*;
 
+    fn rel(parent: &str, child: &str, role: &str) -> EntityRelationship {
+        EntityRelationship::new(parent.into(), child.into(), role.into())
+    }
+
+    #[test]
+    fn test_find_collisions_with_unchanged_relationships() {
+        let existing = vec![rel("a", "b", "r"), rel("c", "b", "r")];
+        let changes = vec![(rel("a", "b", "r"), rel("c", "b", "r"))];
+        let collisions = find_collisions(&existing, &changes);
+        assert_eq!(
+            collisions,
+            vec![RelationshipCollision {
+                old_relationship: rel("a", "b", "r"),
+                new_relationship: rel("c", "b", "r"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_find_collisions_among_changes() {
+        let existing = vec![rel("a", "x", "r"), rel("b", "x", "r"), rel("c", "x", "s")];
+        let changes = vec![
+            (rel("a", "x", "r"), rel("a", "x", "s")),
+            (rel("b", "x", "r"), rel("b", "x", "s")),
+        ];
+        assert!(find_collisions(&existing, &changes).is_empty());
+
+        let changes = vec![
+            (rel("a", "x", "r"), rel("d", "x", "r")),
+            (rel("b", "x", "r"), rel("d", "x", "r")),
+        ];
+        assert_eq!(find_collisions(&existing, &changes).len(), 1);
+    }
+
+    #[test]
+    fn test_changes_may_take_over_vacated_keys() {
+        let existing = vec![rel("a", "x", "r"), rel("b", "x", "r")];
+        let changes = vec![
+            (rel("b", "x", "r"), rel("c", "x", "r")),
+            (rel("a", "x", "r"), rel("b", "x", "r")),
+        ];
+        assert!(find_collisions(&existing, &changes).is_empty());
+    }
+
     #[test]
     fn test_extract_parents() {
         let rels = vec![
@@ -147,16 +537,22 @@ mod tests {
                 parent: "b".into(),
                 child: "c".into(),
                 role: Role::NONE,
+                start: None,
+                end: None,
             },
             EntityRelationship {
                 parent: "a".into(),
                 child: "b".into(),
                 role: Role::NONE,
+                start: None,
+                end: None,
             },
             EntityRelationship {
                 parent: "a".into(),
                 child: "c".into(),
                 role: Role::NONE,
+                start: None,
+                end: None,
             },
         ];
         let parents = extract_parents(&rels);
@@ -170,16 +566,22 @@ mod tests {
                 parent: "b".into(),
                 child: "c".into(),
                 role: Role::NONE,
+                start: None,
+                end: None,
             },
             EntityRelationship {
                 parent: "a".into(),
                 child: "b".into(),
                 role: Role::NONE,
+                start: None,
+                end: None,
             },
             EntityRelationship {
                 parent: "a".into(),
                 child: "c".into(),
                 role: Role::NONE,
+                start: None,
+                end: None,
             },
         ];
         let children = extract_children(&rels);
@@ -193,16 +595,22 @@ mod tests {
                 parent: "b".into(),
                 child: "c".into(),
                 role: "r1".into(),
+                start: None,
+                end: None,
             },
             EntityRelationship {
                 parent: "a".into(),
                 child: "b".into(),
                 role: "r2".into(),
+                start: None,
+                end: None,
             },
             EntityRelationship {
                 parent: "a".into(),
                 child: "c".into(),
                 role: "r1".into(),
+                start: None,
+                end: None,
             },
         ];
         let roles = extract_roles(&rels);
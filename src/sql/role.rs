@@ -0,0 +1,310 @@
+use ::diesel::prelude::*;
+use std::{collections::BTreeMap, fmt::Display};
+
+use crate::{
+    errors::{sql_loading_error, LoreCoreError},
+    fuzzy::rank_matches,
+    types::*,
+};
+
+use super::{
+    lore_database::LoreDatabase, schema::roles, search_params::RelationshipSearchParams,
+    sql_types::SqlRoleDeclaration, validation::MAX_SUGGESTIONS,
+};
+
+const ROLE_STRICTNESS_KEY: &str = "role_strictness";
+
+/// The declared roles, with the inverses of declared inverses filled in.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RoleRegistry {
+    inverses: BTreeMap<Role, Option<Role>>,
+}
+
+impl RoleRegistry {
+    /// Fails if the declarations contradict each other, for example by declaring "liege" as the
+    /// inverse of "vassal" and "serf" as the inverse of "liege".
+    pub fn new(declarations: &[RoleDeclaration]) -> Result<Self, LoreCoreError> {
+        let mut inverses: BTreeMap<Role, Option<Role>> = BTreeMap::new();
+        for declaration in declarations.iter() {
+            inverses.entry(declaration.role.clone()).or_insert(None);
+        }
+        for declaration in declarations.iter() {
+            if let Some(inverse) = &declaration.inverse {
+                Self::insert_inverse(&mut inverses, &declaration.role, inverse)?;
+                Self::insert_inverse(&mut inverses, inverse, &declaration.role)?;
+            }
+        }
+        Ok(Self { inverses })
+    }
+
+    fn insert_inverse(
+        inverses: &mut BTreeMap<Role, Option<Role>>,
+        role: &Role,
+        inverse: &Role,
+    ) -> Result<(), LoreCoreError> {
+        let entry = inverses.entry(role.clone()).or_insert(None);
+        match entry {
+            Some(existing) if existing != inverse => Err(LoreCoreError::InputError(format!(
+                "Role \"{}\" cannot have both \"{}\" and \"{}\" as inverse.",
+                role, existing, inverse
+            ))),
+            _ => {
+                *entry = Some(inverse.clone());
+                Ok(())
+            }
+        }
+    }
+
+    /// Roles are declared if they appear in a declaration, either as role or as inverse.
+    /// The empty role is always considered declared.
+    pub fn is_declared(&self, role: &Role) -> bool {
+        role == &Role::NONE || self.inverses.contains_key(role)
+    }
+
+    pub fn inverse(&self, role: &Role) -> Option<&Role> {
+        self.inverses.get(role).and_then(|inverse| inverse.as_ref())
+    }
+
+    pub fn roles(&self) -> Vec<Role> {
+        self.inverses.keys().cloned().collect()
+    }
+
+    /// The same relationship seen from the child's point of view, if the role has an inverse.
+    pub fn inverse_relationship(&self, rel: &EntityRelationship) -> Option<EntityRelationship> {
+        self.inverse(&rel.role).map(|inverse| EntityRelationship {
+            parent: rel.child.to_str().into(),
+            child: rel.parent.to_str().into(),
+            role: inverse.clone(),
+            start: rel.start,
+            end: rel.end,
+        })
+    }
+
+    pub fn undeclared_roles(&self, rels: &[EntityRelationship]) -> Vec<UndeclaredRole> {
+        let roles = self.roles();
+        rels.iter()
+            .filter(|rel| !self.is_declared(&rel.role))
+            .map(|rel| UndeclaredRole {
+                relationship: rel.clone(),
+                suggestions: rank_matches(rel.role.to_str(), &roles, MAX_SUGGESTIONS)
+                    .into_iter()
+                    .map(|m| m.candidate)
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+/// A relationship whose role is missing from the role registry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UndeclaredRole {
+    pub relationship: EntityRelationship,
+    pub suggestions: Vec<Role>,
+}
+
+impl Display for UndeclaredRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Role \"{}\" of the relationship from \"{}\" to \"{}\" is not declared.",
+            self.relationship.role, self.relationship.parent, self.relationship.child
+        )?;
+        if !self.suggestions.is_empty() {
+            let suggestions: Vec<_> = self
+                .suggestions
+                .iter()
+                .map(|role| format!("\"{}\"", role))
+                .collect();
+            write!(f, " Did you mean {}?", suggestions.join(" or "))?;
+        }
+        Ok(())
+    }
+}
+
+impl LoreDatabase {
+    /// Adds roles to the registry. Fails if a role is already declared,
+    /// or if the declarations would contradict each other.
+    pub fn write_role_declarations(
+        &self,
+        declarations: Vec<RoleDeclaration>,
+    ) -> Result<(), LoreCoreError> {
+        let mut all_declarations = self.read_role_declarations()?;
+        all_declarations.extend(declarations.iter().cloned());
+        RoleRegistry::new(&all_declarations)?;
+
+        let mut connection = self.db_connection()?;
+        for declaration in declarations.into_iter() {
+            let declaration = declaration.to_sql_role_declaration();
+            diesel::insert_into(roles::table)
+                .values(&declaration)
+                .execute(&mut connection)
+                .map_err(|e| {
+                    LoreCoreError::SqlError(
+                        "Writing role declaration to database failed: ".to_string()
+                            + &e.to_string(),
+                    )
+                })?;
+        }
+        Ok(())
+    }
+
+    pub fn read_role_declarations(&self) -> Result<Vec<RoleDeclaration>, LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        let mut declarations: Vec<_> = roles::table
+            .load::<SqlRoleDeclaration>(&mut connection)
+            .map_err(|e| sql_loading_error("roles", vec![], e))?
+            .into_iter()
+            .map(|declaration| declaration.to_role_declaration())
+            .collect();
+        declarations.sort();
+        Ok(declarations)
+    }
+
+    pub fn read_role_registry(&self) -> Result<RoleRegistry, LoreCoreError> {
+        RoleRegistry::new(&self.read_role_declarations()?)
+    }
+
+    pub fn delete_role_declaration(&self, role: &Role) -> Result<(), LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        diesel::delete(roles::table.filter(roles::role.eq(role.to_str())))
+            .execute(&mut connection)
+            .map_err(|e| {
+                LoreCoreError::SqlError(
+                    "Deleting role declaration from database failed: ".to_string() + &e.to_string(),
+                )
+            })?;
+        Ok(())
+    }
+
+    pub fn set_role_strictness(&self, strictness: RoleStrictness) -> Result<(), LoreCoreError> {
+        self.write_setting(ROLE_STRICTNESS_KEY, &strictness.to_string())
+    }
+
+    pub fn read_role_strictness(&self) -> Result<RoleStrictness, LoreCoreError> {
+        match self.read_setting(ROLE_STRICTNESS_KEY)? {
+            Some(strictness) => RoleStrictness::try_from(strictness.as_str()),
+            None => Ok(RoleStrictness::default()),
+        }
+    }
+
+    /// Returns the stored relationships whose roles are missing from the role registry,
+    /// unless the role strictness is lenient.
+    pub fn find_undeclared_roles(&self) -> Result<Vec<UndeclaredRole>, LoreCoreError> {
+        if self.read_role_strictness()? == RoleStrictness::Lenient {
+            return Ok(Vec::new());
+        }
+        let rels = self.read_relationships(RelationshipSearchParams::empty())?;
+        Ok(self.read_role_registry()?.undeclared_roles(&rels))
+    }
+
+    /// Fails if the role strictness is strict and any role of `rels` is missing from the registry.
+    pub(super) fn check_roles(&self, rels: &[EntityRelationship]) -> Result<(), LoreCoreError> {
+        if self.read_role_strictness()? != RoleStrictness::Strict {
+            return Ok(());
+        }
+        let undeclared_roles = self.read_role_registry()?.undeclared_roles(rels);
+        if !undeclared_roles.is_empty() {
+            let messages: Vec<_> = undeclared_roles.iter().map(|u| u.to_string()).collect();
+            return Err(LoreCoreError::InputError(messages.join("\n")));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rel(parent: &str, child: &str, role: &str) -> EntityRelationship {
+        EntityRelationship {
+            parent: parent.into(),
+            child: child.into(),
+            role: role.into(),
+            start: None,
+            end: None,
+        }
+    }
+
+    #[test]
+    fn test_registry_derives_inverses_in_both_directions() {
+        let registry = RoleRegistry::new(&[
+            RoleDeclaration::with_inverse("liege".into(), "vassal".into()),
+            RoleDeclaration::symmetric("sibling".into()),
+            RoleDeclaration::plain("capital".into()),
+        ])
+        .unwrap();
+        assert_eq!(registry.inverse(&"liege".into()), Some(&"vassal".into()));
+        assert_eq!(registry.inverse(&"vassal".into()), Some(&"liege".into()));
+        assert_eq!(registry.inverse(&"sibling".into()), Some(&"sibling".into()));
+        assert_eq!(registry.inverse(&"capital".into()), None);
+        assert!(registry.is_declared(&"vassal".into()));
+        assert!(registry.is_declared(&Role::NONE));
+        assert!(!registry.is_declared(&"ruler".into()));
+    }
+
+    #[test]
+    fn test_registry_rejects_contradicting_inverses() {
+        let result = RoleRegistry::new(&[
+            RoleDeclaration::with_inverse("liege".into(), "vassal".into()),
+            RoleDeclaration::with_inverse("serf".into(), "liege".into()),
+        ]);
+        assert!(result.is_err());
+
+        let result = RoleRegistry::new(&[
+            RoleDeclaration::symmetric("sibling".into()),
+            RoleDeclaration::with_inverse("brother".into(), "sibling".into()),
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_registry_accepts_redundant_inverses() {
+        let result = RoleRegistry::new(&[
+            RoleDeclaration::with_inverse("liege".into(), "vassal".into()),
+            RoleDeclaration::with_inverse("vassal".into(), "liege".into()),
+        ]);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_inverse_relationship() {
+        let registry = RoleRegistry::new(&[
+            RoleDeclaration::with_inverse("liege".into(), "vassal".into()),
+            RoleDeclaration::plain("capital".into()),
+        ])
+        .unwrap();
+        assert_eq!(
+            registry.inverse_relationship(&rel("king", "duke", "liege")),
+            Some(rel("duke", "king", "vassal"))
+        );
+        assert_eq!(
+            registry.inverse_relationship(&rel("kingdom", "city", "capital")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_undeclared_roles_come_with_suggestions() {
+        let registry = RoleRegistry::new(&[RoleDeclaration::with_inverse(
+            "liege".into(),
+            "vassal".into(),
+        )])
+        .unwrap();
+        let undeclared = registry.undeclared_roles(&[
+            rel("king", "duke", "liege"),
+            rel("duke", "king", "vasal"),
+            rel("duke", "city", ""),
+        ]);
+        assert_eq!(
+            undeclared,
+            vec![UndeclaredRole {
+                relationship: rel("duke", "king", "vasal"),
+                suggestions: vec!["vassal".into()],
+            }]
+        );
+        assert_eq!(
+            undeclared[0].to_string(),
+            "Role \"vasal\" of the relationship from \"duke\" to \"king\" is not declared. Did you mean \"vassal\"?"
+        );
+    }
+}
@@ -0,0 +1,354 @@
+use ::diesel::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    errors::{sql_loading_error, LoreCoreError},
+    types::*,
+};
+
+use super::{
+    lore_database::LoreDatabase,
+    schema::role_constraints,
+    search_params::{EntityColumnSearchParams, RelationshipSearchParams},
+    sql_types::SqlRoleConstraint,
+};
+
+impl LoreDatabase {
+    /// Adds constraints. A maximum replaces any previous maximum of the same kind for the role.
+    pub fn write_role_constraints(
+        &self,
+        constraints: Vec<RoleConstraint>,
+    ) -> Result<(), LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        for constraint in constraints.into_iter() {
+            let constraint = constraint.to_sql_role_constraint();
+            diesel::replace_into(role_constraints::table)
+                .values(&constraint)
+                .execute(&mut connection)
+                .map_err(|e| {
+                    LoreCoreError::SqlError(
+                        "Writing role constraint to database failed: ".to_string() + &e.to_string(),
+                    )
+                })?;
+        }
+        Ok(())
+    }
+
+    pub fn read_role_constraints(&self) -> Result<Vec<RoleConstraint>, LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        let mut constraints = role_constraints::table
+            .load::<SqlRoleConstraint>(&mut connection)
+            .map_err(|e| sql_loading_error("role constraints", vec![], e))?
+            .into_iter()
+            .map(|constraint| constraint.to_role_constraint())
+            .collect::<Result<Vec<_>, _>>()?;
+        constraints.sort();
+        Ok(constraints)
+    }
+
+    pub fn delete_role_constraint(&self, constraint: RoleConstraint) -> Result<(), LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        let constraint = constraint.to_sql_role_constraint();
+        diesel::delete(
+            role_constraints::table.filter(
+                role_constraints::role
+                    .eq(constraint.role)
+                    .and(role_constraints::rule.eq(constraint.rule))
+                    .and(role_constraints::descriptor.eq(constraint.descriptor))
+                    .and(role_constraints::description.eq(constraint.description)),
+            ),
+        )
+        .execute(&mut connection)
+        .map_err(|e| {
+            LoreCoreError::SqlError(
+                "Deleting role constraint from database failed: ".to_string() + &e.to_string(),
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Checks all stored relationships against the role constraints.
+    pub fn find_constraint_violations(&self) -> Result<Vec<ConstraintViolation>, LoreCoreError> {
+        let constraints = self.read_role_constraints()?;
+        if constraints.is_empty() {
+            return Ok(Vec::new());
+        }
+        let rels = self.read_relationships(RelationshipSearchParams::empty())?;
+        let cols = self.read_entity_columns(EntityColumnSearchParams::empty())?;
+        Ok(constraint_violations(&constraints, &rels, &cols))
+    }
+
+    /// Fails with all violations that the relationships in `added` would be involved in,
+    /// if they replaced those in `removed`.
+    pub(super) fn check_role_constraints(
+        &self,
+        added: &[EntityRelationship],
+        removed: &[EntityRelationship],
+    ) -> Result<(), LoreCoreError> {
+        let constraints = self.read_role_constraints()?;
+        if constraints.is_empty() {
+            return Ok(());
+        }
+        let mut rels: Vec<_> = self
+            .read_relationships(RelationshipSearchParams::empty())?
+            .into_iter()
+            .filter(|rel| !removed.iter().any(|r| is_same_relationship(r, rel)))
+            .collect();
+        rels.extend(added.iter().cloned());
+        let cols = self.read_entity_columns(EntityColumnSearchParams::empty())?;
+        let violations: Vec<_> = constraint_violations(&constraints, &rels, &cols)
+            .into_iter()
+            .filter(|violation| added.iter().any(|rel| violation.concerns(rel)))
+            .collect();
+        if !violations.is_empty() {
+            return Err(LoreCoreError::ConstraintError(violations));
+        }
+        Ok(())
+    }
+}
+
+fn is_same_relationship(a: &EntityRelationship, b: &EntityRelationship) -> bool {
+    a.parent == b.parent && a.child == b.child && a.role == b.role
+}
+
+/// The largest number of relationships in `rels` that are valid in the same year.
+fn max_simultaneous(rels: &[&EntityRelationship]) -> usize {
+    rels.iter()
+        .map(|rel| {
+            rels.iter()
+                .filter(|other| starts_within(rel, other))
+                .count()
+        })
+        .max()
+        .unwrap_or_default()
+}
+
+/// Whether `other` is valid at the start of `rel`. The start of a set of overlapping intervals
+/// that begins last lies within all of them, so checking starts suffices.
+fn starts_within(rel: &EntityRelationship, other: &EntityRelationship) -> bool {
+    match rel.start {
+        Some((year, _)) => other.is_valid_at(year),
+        None => other.start.is_none(),
+    }
+}
+
+/// Checks `rels` against `constraints`, looking up the kinds of entities in `cols`.
+/// Violations are sorted, and a parent or child with too many relationships is reported once.
+/// Maximum counts only apply to relationships that are valid at the same time.
+pub fn constraint_violations(
+    constraints: &[RoleConstraint],
+    rels: &[EntityRelationship],
+    cols: &[EntityColumn],
+) -> Vec<ConstraintViolation> {
+    let mut kinds: BTreeMap<Label, BTreeSet<(Descriptor, Description)>> = BTreeMap::new();
+    for col in cols.iter() {
+        kinds
+            .entry(col.label.clone())
+            .or_default()
+            .insert((col.descriptor.clone(), col.description.clone()));
+    }
+    let has_any_kind = |label: &str, allowed: &[(Descriptor, Description)]| {
+        kinds
+            .get(&label.into())
+            .is_some_and(|entity_kinds| allowed.iter().any(|kind| entity_kinds.contains(kind)))
+    };
+
+    let mut violations = Vec::new();
+    for (role, rules) in rules_per_role(constraints).iter() {
+        let rels: Vec<_> = rels.iter().filter(|rel| &rel.role == role).collect();
+        if let Some(max) = rules.max_per_parent {
+            let mut groups: BTreeMap<&Parent, Vec<&EntityRelationship>> = BTreeMap::new();
+            for rel in rels.iter() {
+                groups.entry(&rel.parent).or_default().push(rel);
+            }
+            let counts = groups
+                .into_iter()
+                .map(|(parent, group)| (parent, max_simultaneous(&group)));
+            for (parent, count) in counts.filter(|(_, c)| *c > max as usize) {
+                violations.push(ConstraintViolation::TooManyPerParent {
+                    parent: parent.clone(),
+                    role: role.clone(),
+                    count,
+                    max,
+                });
+            }
+        }
+        if let Some(max) = rules.max_per_child {
+            let mut groups: BTreeMap<&Child, Vec<&EntityRelationship>> = BTreeMap::new();
+            for rel in rels.iter() {
+                groups.entry(&rel.child).or_default().push(rel);
+            }
+            let counts = groups
+                .into_iter()
+                .map(|(child, group)| (child, max_simultaneous(&group)));
+            for (child, count) in counts.filter(|(_, c)| *c > max as usize) {
+                violations.push(ConstraintViolation::TooManyPerChild {
+                    child: child.clone(),
+                    role: role.clone(),
+                    count,
+                    max,
+                });
+            }
+        }
+        for rel in rels.iter() {
+            if !rules.parent_kinds.is_empty()
+                && !has_any_kind(rel.parent.to_str(), &rules.parent_kinds)
+            {
+                violations.push(ConstraintViolation::WrongParentKind {
+                    relationship: (*rel).clone(),
+                    allowed: rules.parent_kinds.clone(),
+                });
+            }
+            if !rules.child_kinds.is_empty()
+                && !has_any_kind(rel.child.to_str(), &rules.child_kinds)
+            {
+                violations.push(ConstraintViolation::WrongChildKind {
+                    relationship: (*rel).clone(),
+                    allowed: rules.child_kinds.clone(),
+                });
+            }
+        }
+    }
+    violations.sort();
+    violations
+}
+
+#[derive(Default)]
+struct RoleRules {
+    max_per_parent: Option<u32>,
+    max_per_child: Option<u32>,
+    parent_kinds: Vec<(Descriptor, Description)>,
+    child_kinds: Vec<(Descriptor, Description)>,
+}
+
+fn rules_per_role(constraints: &[RoleConstraint]) -> BTreeMap<Role, RoleRules> {
+    let mut rules: BTreeMap<Role, RoleRules> = BTreeMap::new();
+    for constraint in constraints.iter() {
+        let role_rules = rules.entry(constraint.role.clone()).or_default();
+        match &constraint.rule {
+            RoleRule::MaxPerParent(max) => {
+                role_rules.max_per_parent =
+                    Some(role_rules.max_per_parent.map_or(*max, |m| m.min(*max)))
+            }
+            RoleRule::MaxPerChild(max) => {
+                role_rules.max_per_child =
+                    Some(role_rules.max_per_child.map_or(*max, |m| m.min(*max)))
+            }
+            RoleRule::ParentKind(descriptor, description) => role_rules
+                .parent_kinds
+                .push((descriptor.clone(), description.clone())),
+            RoleRule::ChildKind(descriptor, description) => role_rules
+                .child_kinds
+                .push((descriptor.clone(), description.clone())),
+        }
+    }
+    rules
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rel(parent: &str, child: &str, role: &str) -> EntityRelationship {
+        EntityRelationship {
+            parent: parent.into(),
+            child: child.into(),
+            role: role.into(),
+            start: None,
+            end: None,
+        }
+    }
+
+    fn col(label: &str, descriptor: &str, description: &str) -> EntityColumn {
+        EntityColumn {
+            label: label.into(),
+            descriptor: descriptor.into(),
+            description: description.into(),
+        }
+    }
+
+    #[test]
+    fn test_too_many_per_child() {
+        let constraints = vec![RoleConstraint::max_per_child("parent".into(), 2)];
+        let rels = vec![
+            rel("a", "child", "parent"),
+            rel("b", "child", "parent"),
+            rel("c", "child", "parent"),
+            rel("a", "other_child", "parent"),
+            rel("a", "third_child", "friend"),
+        ];
+        let violations = constraint_violations(&constraints, &rels, &[]);
+        assert_eq!(
+            violations,
+            vec![ConstraintViolation::TooManyPerChild {
+                child: "child".into(),
+                role: "parent".into(),
+                count: 3,
+                max: 2
+            }]
+        );
+        assert!(violations[0].concerns(&rels[0]));
+        assert!(!violations[0].concerns(&rels[3]));
+    }
+
+    #[test]
+    fn test_one_to_one() {
+        let constraints = RoleConstraint::one_to_one("capital".into());
+        let rels = vec![
+            rel("kingdom", "city", "capital"),
+            rel("kingdom", "town", "capital"),
+            rel("empire", "town", "capital"),
+        ];
+        let violations = constraint_violations(&constraints, &rels, &[]);
+        assert_eq!(
+            violations,
+            vec![
+                ConstraintViolation::TooManyPerParent {
+                    parent: "kingdom".into(),
+                    role: "capital".into(),
+                    count: 2,
+                    max: 1
+                },
+                ConstraintViolation::TooManyPerChild {
+                    child: "town".into(),
+                    role: "capital".into(),
+                    count: 2,
+                    max: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_child_kind_allows_any_listed_kind() {
+        let constraints = vec![
+            RoleConstraint::child_kind("located in".into(), "type".into(), "place".into()),
+            RoleConstraint::child_kind("located in".into(), "type".into(), "region".into()),
+        ];
+        let cols = vec![
+            col("inn", "type", "building"),
+            col("town", "type", "place"),
+            col("shire", "type", "region"),
+            col("dragon", "type", "creature"),
+        ];
+        let rels = vec![
+            rel("inn", "town", "located in"),
+            rel("town", "shire", "located in"),
+            rel("inn", "dragon", "located in"),
+            rel("inn", "nowhere", "located in"),
+        ];
+        let violations = constraint_violations(&constraints, &rels, &cols);
+        let violating: Vec<_> = violations
+            .iter()
+            .map(|v| match v {
+                ConstraintViolation::WrongChildKind { relationship, .. } => relationship.clone(),
+                _ => panic!("Unexpected violation {:?}", v),
+            })
+            .collect();
+        assert_eq!(violating, vec![rels[2].clone(), rels[3].clone()]);
+        assert_eq!(
+            violations[0].to_string(),
+            "The relationship with role \"located in\" from \"inn\" to \"dragon\" requires the child to have \"type\" \"place\" or \"type\" \"region\"."
+        );
+    }
+}
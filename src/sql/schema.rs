@@ -1,10 +1,68 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    calendars (name) {
+        name -> Text,
+        definition -> Text,
+    }
+}
+
+diesel::table! {
+    description_references (label, descriptor, target) {
+        label -> Text,
+        descriptor -> Text,
+        target -> Text,
+    }
+}
+
 diesel::table! {
     entities (label, descriptor) {
         label -> Text,
         descriptor -> Text,
         description -> Text,
+        visibility -> Integer,
+    }
+}
+
+diesel::table! {
+    entity_tags (label, tag) {
+        label -> Text,
+        tag -> Text,
+    }
+}
+
+diesel::table! {
+    eras (name) {
+        name -> Text,
+        abbreviation -> Text,
+        epoch -> Integer,
+        counts_backwards -> Integer,
+        has_year_zero -> Integer,
+        start_year -> Nullable<Integer>,
+        end_year -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    history_anchors (timestamp) {
+        timestamp -> BigInt,
+        anchor -> BigInt,
+        year_offset -> Integer,
+        day_offset -> Integer,
+    }
+}
+
+diesel::table! {
+    history_item_references (timestamp, target) {
+        timestamp -> BigInt,
+        target -> Text,
+    }
+}
+
+diesel::table! {
+    history_item_tags (timestamp, tag) {
+        timestamp -> BigInt,
+        tag -> Text,
     }
 }
 
@@ -15,6 +73,24 @@ diesel::table! {
         day -> Integer,
         content -> Text,
         properties -> Text,
+        precision -> Text,
+        range_years -> Integer,
+        sort_key -> BigInt,
+    }
+}
+
+diesel::table! {
+    property_declarations (key) {
+        key -> Text,
+        value_type -> Text,
+    }
+}
+
+diesel::table! {
+    recurrences (timestamp) {
+        timestamp -> BigInt,
+        every_years -> Integer,
+        until_year -> Nullable<Integer>,
     }
 }
 
@@ -23,7 +99,63 @@ diesel::table! {
         parent -> Text,
         child -> Text,
         role -> Text,
+        start_year -> Nullable<Integer>,
+        start_day -> Nullable<Integer>,
+        end_year -> Nullable<Integer>,
+        end_day -> Nullable<Integer>,
+        visibility -> Integer,
+    }
+}
+
+diesel::table! {
+    role_constraints (role, rule, descriptor, description) {
+        role -> Text,
+        rule -> Text,
+        max_count -> Integer,
+        descriptor -> Text,
+        description -> Text,
+    }
+}
+
+diesel::table! {
+    roles (role) {
+        role -> Text,
+        inverse -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    settings (key) {
+        key -> Text,
+        value -> Text,
+    }
+}
+
+diesel::table! {
+    timed_entity_columns (label, descriptor, start_year) {
+        label -> Text,
+        descriptor -> Text,
+        description -> Text,
+        start_year -> Nullable<Integer>,
+        end_year -> Nullable<Integer>,
     }
 }
 
-diesel::allow_tables_to_appear_in_same_query!(entities, history_items, relationships,);
+diesel::allow_tables_to_appear_in_same_query!(
+    calendars,
+    description_references,
+    entities,
+    entity_tags,
+    eras,
+    history_anchors,
+    history_item_references,
+    history_item_tags,
+    history_items,
+    property_declarations,
+    recurrences,
+    relationships,
+    role_constraints,
+    roles,
+    settings,
+    timed_entity_columns,
+);
@@ -1,6 +1,8 @@
+use std::collections::BTreeMap;
+
 use crate::types::*;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct SqlSearchText {
     text: Option<String>,
     pub(crate) is_exact: bool,
@@ -51,6 +53,8 @@ impl SqlSearchText {
 pub struct EntityColumnSearchParams {
     pub(crate) label: SqlSearchText,
     pub(crate) descriptor: SqlSearchText,
+    pub(crate) audience: Visibility,
+    pub(crate) tag: Option<Tag>,
 }
 
 impl EntityColumnSearchParams {
@@ -63,13 +67,34 @@ impl EntityColumnSearchParams {
             Some(descriptor) => descriptor,
             None => SqlSearchText::empty(),
         };
-        Self { label, descriptor }
+        Self {
+            label,
+            descriptor,
+            audience: Visibility::AuthorOnly,
+            tag: None,
+        }
     }
 
     pub fn empty() -> Self {
         Self {
             label: SqlSearchText::empty(),
             descriptor: SqlSearchText::empty(),
+            audience: Visibility::AuthorOnly,
+            tag: None,
+        }
+    }
+
+    /// Only returns columns visible to `audience`, with references to hidden entities redacted.
+    /// By default, everything is visible.
+    pub fn for_audience(self, audience: Visibility) -> Self {
+        Self { audience, ..self }
+    }
+
+    /// Only searches the columns of entities tagged with `tag` or a tag nested below it.
+    pub fn with_tag(self, tag: Tag) -> Self {
+        Self {
+            tag: Some(tag),
+            ..self
         }
     }
 }
@@ -80,6 +105,9 @@ pub struct HistoryItemSearchParams {
     pub(crate) day: Option<Day>,
     pub(crate) timestamp: Option<Timestamp>,
     pub(crate) content: SqlSearchText,
+    pub(crate) period: Option<(Year, Year)>,
+    pub(crate) audience: Visibility,
+    pub(crate) tag: Option<Tag>,
 }
 
 impl HistoryItemSearchParams {
@@ -98,6 +126,9 @@ impl HistoryItemSearchParams {
             day,
             timestamp,
             content,
+            period: None,
+            audience: Visibility::AuthorOnly,
+            tag: None,
         }
     }
 
@@ -107,14 +138,45 @@ impl HistoryItemSearchParams {
             day: None,
             timestamp: None,
             content: SqlSearchText::empty(),
+            period: None,
+            audience: Visibility::AuthorOnly,
+            tag: None,
         }
     }
+
+    /// Restricts the search to history items that may have happened between the years `from`
+    /// and `to`, inclusively. Uncertain items match if any of their possible years does.
+    pub fn in_period(mut self, from: Year, to: Year) -> Self {
+        self.period = Some((from, to));
+        self
+    }
+
+    /// Only returns history items visible to `audience`, with references to hidden entities
+    /// redacted. By default, everything is visible.
+    pub fn for_audience(mut self, audience: Visibility) -> Self {
+        self.audience = audience;
+        self
+    }
+
+    /// Only searches history items tagged with `tag` or a tag nested below it.
+    pub fn with_tag(mut self, tag: Tag) -> Self {
+        self.tag = Some(tag);
+        self
+    }
+
+    pub(crate) fn matches_period(&self, item: &HistoryItem) -> bool {
+        self.period
+            .is_none_or(|(from, to)| item.precision.overlaps(item.year, from, to))
+    }
 }
 
 #[derive(Debug)]
 pub struct RelationshipSearchParams {
     pub(crate) parent: SqlSearchText,
     pub(crate) child: SqlSearchText,
+    pub(crate) include_inverses: bool,
+    pub(crate) valid_at: Option<Year>,
+    pub(crate) audience: Visibility,
 }
 
 impl RelationshipSearchParams {
@@ -127,13 +189,111 @@ impl RelationshipSearchParams {
             Some(child) => child,
             None => SqlSearchText::empty(),
         };
-        Self { parent, child }
+        Self {
+            parent,
+            child,
+            include_inverses: false,
+            valid_at: None,
+            audience: Visibility::AuthorOnly,
+        }
     }
 
     pub fn empty() -> Self {
         Self {
             parent: SqlSearchText::empty(),
             child: SqlSearchText::empty(),
+            include_inverses: false,
+            valid_at: None,
+            audience: Visibility::AuthorOnly,
+        }
+    }
+
+    /// Additionally searches the inverses derived from the role registry.
+    pub fn including_inverses(self) -> Self {
+        Self {
+            include_inverses: true,
+            ..self
+        }
+    }
+
+    /// Only searches relationships that are valid in the given year.
+    pub fn valid_at(self, year: Year) -> Self {
+        Self {
+            valid_at: Some(year),
+            ..self
+        }
+    }
+
+    /// Only searches relationships visible to `audience` between entities that are not hidden
+    /// from it. By default, everything is visible.
+    pub fn for_audience(self, audience: Visibility) -> Self {
+        Self { audience, ..self }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RelationshipTraversalParams {
+    pub(crate) roles: Vec<Role>,
+    pub(crate) max_depth: Option<u32>,
+}
+
+impl RelationshipTraversalParams {
+    /// Only relationships with one of the given `roles` are followed, unless the list is empty.
+    /// With a `max_depth`, entities further than that many relationships away are not reached.
+    pub fn new(roles: Vec<Role>, max_depth: Option<u32>) -> Self {
+        Self { roles, max_depth }
+    }
+
+    pub fn empty() -> Self {
+        Self {
+            roles: Vec::new(),
+            max_depth: None,
+        }
+    }
+
+    pub(crate) fn follows(&self, rel: &EntityRelationship) -> bool {
+        self.roles.is_empty() || self.roles.contains(&rel.role)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PathSearchParams {
+    pub(crate) undirected: bool,
+    pub(crate) role_weights: BTreeMap<Role, u32>,
+    pub(crate) excluded_roles: Vec<Role>,
+}
+
+impl PathSearchParams {
+    /// If `undirected` is set, relationships may also be followed from child to parent.
+    pub fn new(undirected: bool) -> Self {
+        Self {
+            undirected,
+            role_weights: BTreeMap::new(),
+            excluded_roles: Vec::new(),
+        }
+    }
+
+    pub fn empty() -> Self {
+        Self::new(false)
+    }
+
+    /// Following a relationship with `role` costs `weight` instead of 1.
+    /// A weight of 0 is treated as 1, so that every path has a cost.
+    pub fn with_role_weight(mut self, role: Role, weight: u32) -> Self {
+        self.role_weights.insert(role, weight.max(1));
+        self
+    }
+
+    /// Relationships with `role` are never followed.
+    pub fn excluding_role(mut self, role: Role) -> Self {
+        self.excluded_roles.push(role);
+        self
+    }
+
+    pub(crate) fn weight(&self, rel: &EntityRelationship) -> Option<u32> {
+        if self.excluded_roles.contains(&rel.role) {
+            return None;
         }
+        Some(self.role_weights.get(&rel.role).copied().unwrap_or(1))
     }
 }
@@ -0,0 +1,35 @@
+use ::diesel::prelude::*;
+
+use crate::errors::{sql_loading_error, LoreCoreError};
+
+use super::{lore_database::LoreDatabase, schema::settings, sql_types::SqlSetting};
+
+impl LoreDatabase {
+    pub(crate) fn read_setting(&self, key: &str) -> Result<Option<String>, LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        let setting = settings::table
+            .filter(settings::key.eq(key))
+            .load::<SqlSetting>(&mut connection)
+            .map_err(|e| sql_loading_error("settings", vec![("key", &key)], e))?
+            .pop()
+            .map(|setting| setting.value);
+        Ok(setting)
+    }
+
+    pub(crate) fn write_setting(&self, key: &str, value: &str) -> Result<(), LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        let setting = SqlSetting {
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+        diesel::replace_into(settings::table)
+            .values(&setting)
+            .execute(&mut connection)
+            .map_err(|e| {
+                LoreCoreError::SqlError(
+                    "Writing setting to database failed: ".to_string() + &e.to_string(),
+                )
+            })?;
+        Ok(())
+    }
+}
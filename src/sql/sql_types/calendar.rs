@@ -0,0 +1,36 @@
+use diesel::{Insertable, Queryable};
+
+use crate::{calendar::Calendar, errors::LoreCoreError, sql::schema::calendars};
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Insertable, Queryable)]
+#[diesel(table_name = calendars)]
+pub(crate) struct SqlCalendar {
+    pub name: String,
+    pub definition: String,
+}
+
+impl Calendar {
+    pub(crate) fn to_sql_calendar(&self) -> Result<SqlCalendar, LoreCoreError> {
+        let definition = serde_json::to_string(self).map_err(|e| {
+            LoreCoreError::InputError(format!(
+                "Unable to serialize calendar \"{}\": {}",
+                self.name, e
+            ))
+        })?;
+        Ok(SqlCalendar {
+            name: self.name.clone(),
+            definition,
+        })
+    }
+}
+
+impl SqlCalendar {
+    pub(crate) fn to_calendar(&self) -> Result<Calendar, LoreCoreError> {
+        serde_json::from_str(&self.definition).map_err(|e| {
+            LoreCoreError::SqlError(format!(
+                "Calendar \"{}\" in database is malformed: {}",
+                self.name, e
+            ))
+        })
+    }
+}
@@ -8,6 +8,7 @@ pub(crate) struct SqlEntityColumn {
     pub label: String,
     pub descriptor: String,
     pub description: String,
+    pub visibility: i32,
 }
 
 impl EntityColumn {
@@ -16,6 +17,7 @@ impl EntityColumn {
             label: self.label.to_string(),
             descriptor: self.descriptor.to_string(),
             description: self.description.to_string(),
+            visibility: Visibility::default().to_int(),
         }
     }
 }
@@ -0,0 +1,43 @@
+use diesel::{Insertable, Queryable};
+
+use crate::{era::Era, sql::schema::eras};
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Insertable, Queryable)]
+#[diesel(table_name = eras)]
+pub(crate) struct SqlEra {
+    pub name: String,
+    pub abbreviation: String,
+    pub epoch: i32,
+    pub counts_backwards: i32,
+    pub has_year_zero: i32,
+    pub start_year: Option<i32>,
+    pub end_year: Option<i32>,
+}
+
+impl Era {
+    pub(crate) fn to_sql_era(&self) -> SqlEra {
+        SqlEra {
+            name: self.name.clone(),
+            abbreviation: self.abbreviation.clone(),
+            epoch: self.epoch.to_int(),
+            counts_backwards: self.counts_backwards as i32,
+            has_year_zero: self.has_year_zero as i32,
+            start_year: self.start.map(|year| year.to_int()),
+            end_year: self.end.map(|year| year.to_int()),
+        }
+    }
+}
+
+impl SqlEra {
+    pub(crate) fn to_era(&self) -> Era {
+        Era {
+            name: self.name.clone(),
+            abbreviation: self.abbreviation.clone(),
+            epoch: self.epoch.into(),
+            counts_backwards: self.counts_backwards != 0,
+            has_year_zero: self.has_year_zero != 0,
+            start: self.start_year.map(|year| year.into()),
+            end: self.end_year.map(|year| year.into()),
+        }
+    }
+}
@@ -0,0 +1,11 @@
+use diesel::{sql_types::Text, QueryableByName};
+
+/// A relationship followed during a traversal, pointing from `source` to `target` in the
+/// direction of the traversal.
+#[derive(Clone, Debug, PartialEq, Eq, QueryableByName)]
+pub(crate) struct SqlTraversedRelationship {
+    #[diesel(sql_type = Text)]
+    pub source: String,
+    #[diesel(sql_type = Text)]
+    pub target: String,
+}
@@ -1,6 +1,6 @@
 use diesel::{Insertable, Queryable};
 
-use crate::{sql::schema::history_items, types::*};
+use crate::{errors::LoreCoreError, sql::schema::history_items, types::*};
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Insertable, Queryable)]
 #[diesel(table_name = history_items)]
@@ -10,6 +10,9 @@ pub(crate) struct SqlHistoryItem {
     pub day: i32,
     pub content: String,
     pub properties: String,
+    pub precision: String,
+    pub range_years: i32,
+    pub sort_key: i64,
 }
 
 impl PartialEq<&SqlHistoryItem> for SqlHistoryItem {
@@ -19,6 +22,9 @@ impl PartialEq<&SqlHistoryItem> for SqlHistoryItem {
             && self.day == other.day
             && self.content == other.content
             && self.properties == other.properties
+            && self.precision == other.precision
+            && self.range_years == other.range_years
+            && self.sort_key == other.sort_key
     }
 }
 
@@ -30,18 +36,24 @@ impl HistoryItem {
             day: self.day.to_int() as i32,
             content: self.content.to_string(),
             properties: self.properties.to_string(),
+            precision: self.precision.name().to_string(),
+            range_years: self.precision.range_years() as i32,
+            sort_key: self.sort_key,
         }
     }
 }
 
 impl SqlHistoryItem {
-    pub(crate) fn to_history_item(&self) -> HistoryItem {
-        HistoryItem {
+    pub(crate) fn to_history_item(&self) -> Result<HistoryItem, LoreCoreError> {
+        Ok(HistoryItem {
             timestamp: self.timestamp.into(),
             year: self.year.into(),
             day: self.day.into(),
             content: self.content.as_str().into(),
-            properties: (&self.properties).into(),
-        }
+            properties: HistoryItemProperties::try_from(&self.properties)?,
+            precision: DatePrecision::from_name(&self.precision, self.range_years as u32)?,
+            sort_key: self.sort_key,
+            is_occurrence: false,
+        })
     }
 }
@@ -0,0 +1,36 @@
+use diesel::{Insertable, Queryable};
+
+use crate::{sql::schema::history_anchors, types::*};
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Insertable, Queryable)]
+#[diesel(table_name = history_anchors)]
+pub(crate) struct SqlHistoryAnchor {
+    pub timestamp: i64,
+    pub anchor: i64,
+    pub year_offset: i32,
+    pub day_offset: i32,
+}
+
+impl HistoryAnchor {
+    pub(crate) fn to_sql_history_anchor(self, timestamp: Timestamp) -> SqlHistoryAnchor {
+        SqlHistoryAnchor {
+            timestamp: timestamp.to_int(),
+            anchor: self.anchor.to_int(),
+            year_offset: self.years,
+            day_offset: self.days,
+        }
+    }
+}
+
+impl SqlHistoryAnchor {
+    pub(crate) fn to_history_anchor(&self) -> (Timestamp, HistoryAnchor) {
+        (
+            self.timestamp.into(),
+            HistoryAnchor {
+                anchor: self.anchor.into(),
+                years: self.year_offset,
+                days: self.day_offset,
+            },
+        )
+    }
+}
@@ -1,10 +1,34 @@
 //! This module contains the types used for reading and writing to and from the database.
 //! The only types for members are integers, Strings, and Optionals of these.
 
+pub(crate) mod calendar;
 pub(crate) mod entity;
+pub(crate) mod era;
+pub(crate) mod graph;
 pub(crate) mod history;
+pub(crate) mod history_anchor;
+pub(crate) mod property_declaration;
+pub(crate) mod recurrence;
+pub(crate) mod reference;
 pub(crate) mod relationship;
+pub(crate) mod role;
+pub(crate) mod role_constraint;
+pub(crate) mod settings;
+pub(crate) mod tag;
+pub(crate) mod timed_entity;
 
+pub(crate) use calendar::SqlCalendar;
 pub(crate) use entity::SqlEntityColumn;
+pub(crate) use era::SqlEra;
+pub(crate) use graph::SqlTraversedRelationship;
 pub(crate) use history::SqlHistoryItem;
+pub(crate) use history_anchor::SqlHistoryAnchor;
+pub(crate) use property_declaration::SqlPropertyDeclaration;
+pub(crate) use recurrence::SqlRecurrence;
+pub(crate) use reference::{SqlDescriptionReference, SqlHistoryItemReference};
 pub(crate) use relationship::SqlEntityRelationship;
+pub(crate) use role::SqlRoleDeclaration;
+pub(crate) use role_constraint::SqlRoleConstraint;
+pub(crate) use settings::SqlSetting;
+pub(crate) use tag::{SqlEntityTag, SqlHistoryItemTag};
+pub(crate) use timed_entity::SqlTimedEntityColumn;
@@ -0,0 +1,28 @@
+use diesel::{Insertable, Queryable};
+
+use crate::{errors::LoreCoreError, sql::schema::property_declarations, types::*};
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Insertable, Queryable)]
+#[diesel(table_name = property_declarations)]
+pub(crate) struct SqlPropertyDeclaration {
+    pub key: String,
+    pub value_type: String,
+}
+
+impl PropertyDeclaration {
+    pub(crate) fn to_sql_property_declaration(&self) -> SqlPropertyDeclaration {
+        SqlPropertyDeclaration {
+            key: self.key.clone(),
+            value_type: self.value_type.to_string(),
+        }
+    }
+}
+
+impl SqlPropertyDeclaration {
+    pub(crate) fn to_property_declaration(&self) -> Result<PropertyDeclaration, LoreCoreError> {
+        Ok(PropertyDeclaration {
+            key: self.key.clone(),
+            value_type: PropertyType::try_from(self.value_type.as_str())?,
+        })
+    }
+}
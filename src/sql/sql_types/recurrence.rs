@@ -0,0 +1,33 @@
+use diesel::{Insertable, Queryable};
+
+use crate::{sql::schema::recurrences, types::*};
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Insertable, Queryable)]
+#[diesel(table_name = recurrences)]
+pub(crate) struct SqlRecurrence {
+    pub timestamp: i64,
+    pub every_years: i32,
+    pub until_year: Option<i32>,
+}
+
+impl Recurrence {
+    pub(crate) fn to_sql_recurrence(self, timestamp: Timestamp) -> SqlRecurrence {
+        SqlRecurrence {
+            timestamp: timestamp.to_int(),
+            every_years: self.every_years as i32,
+            until_year: self.until.map(|year| year.to_int()),
+        }
+    }
+}
+
+impl SqlRecurrence {
+    pub(crate) fn to_recurrence(&self) -> (Timestamp, Recurrence) {
+        (
+            self.timestamp.into(),
+            Recurrence {
+                every_years: self.every_years as u32,
+                until: self.until_year.map(|year| year.into()),
+            },
+        )
+    }
+}
@@ -0,0 +1,20 @@
+use diesel::{Insertable, Queryable};
+
+use crate::sql::schema::{description_references, history_item_references};
+
+/// An `\entityref{target}` in the description of the column `(label, descriptor)`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Insertable, Queryable)]
+#[diesel(table_name = description_references)]
+pub(crate) struct SqlDescriptionReference {
+    pub label: String,
+    pub descriptor: String,
+    pub target: String,
+}
+
+/// An `\entityref{target}` in the content or additional concerns of a history item.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Insertable, Queryable)]
+#[diesel(table_name = history_item_references)]
+pub(crate) struct SqlHistoryItemReference {
+    pub timestamp: i64,
+    pub target: String,
+}
@@ -8,14 +8,40 @@ pub(crate) struct SqlEntityRelationship {
     pub parent: String,
     pub child: String,
     pub role: String,
+    pub start_year: Option<i32>,
+    pub start_day: Option<i32>,
+    pub end_year: Option<i32>,
+    pub end_day: Option<i32>,
+    pub visibility: i32,
+}
+
+fn to_sql_date(date: &Option<(Year, Day)>) -> (Option<i32>, Option<i32>) {
+    match date {
+        Some((year, day)) => (
+            Some(year.to_int()),
+            day.to_optional_int().map(|day| day as i32),
+        ),
+        None => (None, None),
+    }
+}
+
+fn to_date(year: Option<i32>, day: Option<i32>) -> Option<(Year, Day)> {
+    year.map(|year| (year.into(), day.into()))
 }
 
 impl EntityRelationship {
     pub(crate) fn to_sql_entity_relationship(&self) -> SqlEntityRelationship {
+        let (start_year, start_day) = to_sql_date(&self.start);
+        let (end_year, end_day) = to_sql_date(&self.end);
         SqlEntityRelationship {
             parent: self.parent.to_string(),
             child: self.child.to_string(),
             role: self.role.to_string(),
+            start_year,
+            start_day,
+            end_year,
+            end_day,
+            visibility: Visibility::default().to_int(),
         }
     }
 }
@@ -26,6 +52,8 @@ impl SqlEntityRelationship {
             parent: self.parent.as_str().into(),
             child: self.child.as_str().into(),
             role: self.role.as_str().into(),
+            start: to_date(self.start_year, self.start_day),
+            end: to_date(self.end_year, self.end_day),
         }
     }
 }
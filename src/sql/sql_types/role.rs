@@ -0,0 +1,28 @@
+use diesel::{Insertable, Queryable};
+
+use crate::{sql::schema::roles, types::*};
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Insertable, Queryable)]
+#[diesel(table_name = roles)]
+pub(crate) struct SqlRoleDeclaration {
+    pub role: String,
+    pub inverse: Option<String>,
+}
+
+impl RoleDeclaration {
+    pub(crate) fn to_sql_role_declaration(&self) -> SqlRoleDeclaration {
+        SqlRoleDeclaration {
+            role: self.role.to_string(),
+            inverse: self.inverse.as_ref().map(|inverse| inverse.to_string()),
+        }
+    }
+}
+
+impl SqlRoleDeclaration {
+    pub(crate) fn to_role_declaration(&self) -> RoleDeclaration {
+        RoleDeclaration {
+            role: self.role.as_str().into(),
+            inverse: self.inverse.as_deref().map(|inverse| inverse.into()),
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use diesel::{Insertable, Queryable};
+
+use crate::{errors::LoreCoreError, sql::schema::role_constraints, types::*};
+
+const MAX_PER_PARENT: &str = "max_per_parent";
+const MAX_PER_CHILD: &str = "max_per_child";
+const PARENT_KIND: &str = "parent_kind";
+const CHILD_KIND: &str = "child_kind";
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Insertable, Queryable)]
+#[diesel(table_name = role_constraints)]
+pub(crate) struct SqlRoleConstraint {
+    pub role: String,
+    pub rule: String,
+    pub max_count: i32,
+    pub descriptor: String,
+    pub description: String,
+}
+
+impl RoleConstraint {
+    pub(crate) fn to_sql_role_constraint(&self) -> SqlRoleConstraint {
+        let (rule, max_count, descriptor, description) = match &self.rule {
+            RoleRule::MaxPerParent(max) => (MAX_PER_PARENT, *max as i32, "", ""),
+            RoleRule::MaxPerChild(max) => (MAX_PER_CHILD, *max as i32, "", ""),
+            RoleRule::ParentKind(descriptor, description) => {
+                (PARENT_KIND, 0, descriptor.to_str(), description.to_str())
+            }
+            RoleRule::ChildKind(descriptor, description) => {
+                (CHILD_KIND, 0, descriptor.to_str(), description.to_str())
+            }
+        };
+        SqlRoleConstraint {
+            role: self.role.to_string(),
+            rule: rule.to_string(),
+            max_count,
+            descriptor: descriptor.to_string(),
+            description: description.to_string(),
+        }
+    }
+}
+
+impl SqlRoleConstraint {
+    pub(crate) fn to_role_constraint(&self) -> Result<RoleConstraint, LoreCoreError> {
+        let descriptor = self.descriptor.as_str().into();
+        let description = self.description.as_str().into();
+        let rule = match self.rule.as_str() {
+            MAX_PER_PARENT => RoleRule::MaxPerParent(self.max_count as u32),
+            MAX_PER_CHILD => RoleRule::MaxPerChild(self.max_count as u32),
+            PARENT_KIND => RoleRule::ParentKind(descriptor, description),
+            CHILD_KIND => RoleRule::ChildKind(descriptor, description),
+            unknown => {
+                return Err(LoreCoreError::SqlError(format!(
+                    "Unknown rule \"{}\" for role \"{}\" in database.",
+                    unknown, self.role
+                )))
+            }
+        };
+        Ok(RoleConstraint {
+            role: self.role.as_str().into(),
+            rule,
+        })
+    }
+}
@@ -0,0 +1,10 @@
+use diesel::{Insertable, Queryable};
+
+use crate::sql::schema::settings;
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Insertable, Queryable)]
+#[diesel(table_name = settings)]
+pub(crate) struct SqlSetting {
+    pub key: String,
+    pub value: String,
+}
@@ -0,0 +1,17 @@
+use diesel::{Insertable, Queryable};
+
+use crate::sql::schema::{entity_tags, history_item_tags};
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Insertable, Queryable)]
+#[diesel(table_name = entity_tags)]
+pub(crate) struct SqlEntityTag {
+    pub label: String,
+    pub tag: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Insertable, Queryable)]
+#[diesel(table_name = history_item_tags)]
+pub(crate) struct SqlHistoryItemTag {
+    pub timestamp: i64,
+    pub tag: String,
+}
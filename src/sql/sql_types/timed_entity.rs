@@ -0,0 +1,37 @@
+use diesel::{Insertable, Queryable};
+
+use crate::{sql::schema::timed_entity_columns, types::*};
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Insertable, Queryable)]
+#[diesel(table_name = timed_entity_columns)]
+pub(crate) struct SqlTimedEntityColumn {
+    pub label: String,
+    pub descriptor: String,
+    pub description: String,
+    pub start_year: Option<i32>,
+    pub end_year: Option<i32>,
+}
+
+impl TimedEntityColumn {
+    pub(crate) fn to_sql_timed_entity_column(&self) -> SqlTimedEntityColumn {
+        SqlTimedEntityColumn {
+            label: self.label.to_string(),
+            descriptor: self.descriptor.to_string(),
+            description: self.description.to_string(),
+            start_year: self.start.map(|year| year.to_int()),
+            end_year: self.end.map(|year| year.to_int()),
+        }
+    }
+}
+
+impl SqlTimedEntityColumn {
+    pub(crate) fn to_timed_entity_column(&self) -> TimedEntityColumn {
+        TimedEntityColumn {
+            label: self.label.clone().into(),
+            descriptor: self.descriptor.as_str().into(),
+            description: self.description.clone().into(),
+            start: self.start_year.map(|year| year.into()),
+            end: self.end_year.map(|year| year.into()),
+        }
+    }
+}
@@ -0,0 +1,407 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use ::diesel::prelude::*;
+use diesel::{sql_types::Bool, sqlite::Sqlite};
+
+use crate::{
+    errors::{sql_loading_error, LoreCoreError},
+    types::*,
+};
+
+use super::{
+    lore_database::LoreDatabase,
+    schema::{
+        description_references, entities, entity_tags, history_item_tags, timed_entity_columns,
+    },
+    search_params::{EntityColumnSearchParams, HistoryItemSearchParams, SqlSearchText},
+    sql_types::{SqlEntityTag, SqlHistoryItemTag},
+};
+
+impl LoreDatabase {
+    /// Tags the entity, which has to have at least one column. Existing tags are kept.
+    pub fn write_entity_tags(&self, label: &Label, tags: Vec<Tag>) -> Result<(), LoreCoreError> {
+        let params =
+            EntityColumnSearchParams::new(Some(SqlSearchText::exact(label.to_str())), None);
+        if self.count_entity_columns(params)? == 0 {
+            return Err(LoreCoreError::InputError(format!(
+                "Entity \"{}\" does not exist.",
+                label
+            )));
+        }
+        let tags: Vec<_> = tags
+            .into_iter()
+            .map(|tag| SqlEntityTag {
+                label: label.to_string(),
+                tag: tag.to_string(),
+            })
+            .collect();
+        self.insert_entity_tags(&tags)
+    }
+
+    /// Returns the tags of the entity, sorted, or none if it is hidden from `audience`.
+    pub fn read_entity_tags(
+        &self,
+        label: &Label,
+        audience: Visibility,
+    ) -> Result<Vec<Tag>, LoreCoreError> {
+        if self
+            .read_redactions(audience)?
+            .is_some_and(|hidden| hidden.contains(label))
+        {
+            return Ok(Vec::new());
+        }
+        let mut connection = self.db_connection()?;
+        entity_tags::table
+            .filter(entity_tags::label.eq(label.to_str()))
+            .select(entity_tags::tag)
+            .order(entity_tags::tag)
+            .load::<String>(&mut connection)
+            .map_err(|e| sql_loading_error("entity tags", vec![("label", label)], e))?
+            .into_iter()
+            .map(Tag::try_from)
+            .collect()
+    }
+
+    pub fn delete_entity_tag(&self, label: &Label, tag: &Tag) -> Result<(), LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        diesel::delete(
+            entity_tags::table
+                .filter(entity_tags::label.eq(label.to_str()))
+                .filter(entity_tags::tag.eq(tag.to_str())),
+        )
+        .execute(&mut connection)
+        .map_err(|e| {
+            LoreCoreError::SqlError(
+                "Deleting entity tag from database failed: ".to_string() + &e.to_string(),
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Tags the history item, which has to exist. Existing tags are kept.
+    pub fn write_history_item_tags(
+        &self,
+        timestamp: Timestamp,
+        tags: Vec<Tag>,
+    ) -> Result<(), LoreCoreError> {
+        self.read_history_item(timestamp)?;
+        let tags: Vec<_> = tags
+            .into_iter()
+            .map(|tag| SqlHistoryItemTag {
+                timestamp: timestamp.to_int(),
+                tag: tag.to_string(),
+            })
+            .collect();
+        self.insert_history_item_tags(&tags)
+    }
+
+    /// Returns the tags of the history item, sorted, or none if it is hidden from `audience`.
+    pub fn read_history_item_tags(
+        &self,
+        timestamp: Timestamp,
+        audience: Visibility,
+    ) -> Result<Vec<Tag>, LoreCoreError> {
+        if audience != Visibility::AuthorOnly
+            && !self
+                .read_history_item(timestamp)?
+                .properties
+                .visibility()
+                .is_visible_to(audience)
+        {
+            return Ok(Vec::new());
+        }
+        let mut connection = self.db_connection()?;
+        history_item_tags::table
+            .filter(history_item_tags::timestamp.eq(timestamp.to_int()))
+            .select(history_item_tags::tag)
+            .order(history_item_tags::tag)
+            .load::<String>(&mut connection)
+            .map_err(|e| {
+                sql_loading_error("history item tags", vec![("timestamp", &timestamp)], e)
+            })?
+            .into_iter()
+            .map(Tag::try_from)
+            .collect()
+    }
+
+    pub fn delete_history_item_tag(
+        &self,
+        timestamp: Timestamp,
+        tag: &Tag,
+    ) -> Result<(), LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        diesel::delete(
+            history_item_tags::table
+                .filter(history_item_tags::timestamp.eq(timestamp.to_int()))
+                .filter(history_item_tags::tag.eq(tag.to_str())),
+        )
+        .execute(&mut connection)
+        .map_err(|e| {
+            LoreCoreError::SqlError(
+                "Deleting history item tag from database failed: ".to_string() + &e.to_string(),
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Returns every tag in use with the number of entities and history items visible to
+    /// `audience` carrying it, sorted by tag. Tags only used as parents of nested tags are not
+    /// listed.
+    pub fn read_tags(&self, audience: Visibility) -> Result<Vec<(Tag, usize)>, LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        let entity_rows = entity_tags::table
+            .load::<SqlEntityTag>(&mut connection)
+            .map_err(|e| sql_loading_error("entity tags", vec![], e))?;
+        let item_rows = history_item_tags::table
+            .load::<SqlHistoryItemTag>(&mut connection)
+            .map_err(|e| sql_loading_error("history item tags", vec![], e))?;
+        let mut tags = Vec::new();
+        match self.read_redactions(audience)? {
+            None => {
+                tags.extend(entity_rows.into_iter().map(|row| row.tag));
+                tags.extend(item_rows.into_iter().map(|row| row.tag));
+            }
+            Some(hidden) => {
+                let visible_items: BTreeSet<_> = self
+                    .read_history_items(HistoryItemSearchParams::empty().for_audience(audience))?
+                    .into_iter()
+                    .map(|item| item.timestamp.to_int())
+                    .collect();
+                tags.extend(
+                    entity_rows
+                        .into_iter()
+                        .filter(|row| !hidden.contains(&row.label.as_str().into()))
+                        .map(|row| row.tag),
+                );
+                tags.extend(
+                    item_rows
+                        .into_iter()
+                        .filter(|row| visible_items.contains(&row.timestamp))
+                        .map(|row| row.tag),
+                );
+            }
+        }
+        let mut counts: BTreeMap<Tag, usize> = BTreeMap::new();
+        for tag in tags.into_iter() {
+            *counts.entry(Tag::try_from(tag)?).or_default() += 1;
+        }
+        Ok(counts.into_iter().collect())
+    }
+
+    /// Renames `old` to `new` on all entities and history items, including the tags nested
+    /// below it, so that renaming "war" to "conflict" turns "war/naval" into "conflict/naval".
+    /// Either all tags are renamed or, if writing fails, none.
+    pub fn rename_tag(&self, old: &Tag, new: &Tag) -> Result<(), LoreCoreError> {
+        if old == new {
+            return Ok(());
+        }
+        let mut connection = self.db_connection()?;
+        let entity_rows = entity_tags::table
+            .filter(entity_tag_filter(old))
+            .load::<SqlEntityTag>(&mut connection)
+            .map_err(|e| sql_loading_error("entity tags", vec![("tag", old)], e))?;
+        let item_rows = history_item_tags::table
+            .filter(history_item_tag_filter(old))
+            .load::<SqlHistoryItemTag>(&mut connection)
+            .map_err(|e| sql_loading_error("history item tags", vec![("tag", old)], e))?;
+
+        let mut moved_entity_rows = Vec::new();
+        for row in entity_rows.into_iter() {
+            if let Some(tag) = Tag::try_from(row.tag)?.moved(old, new) {
+                moved_entity_rows.push(SqlEntityTag {
+                    tag: tag.to_string(),
+                    ..row
+                });
+            }
+        }
+        let mut moved_item_rows = Vec::new();
+        for row in item_rows.into_iter() {
+            if let Some(tag) = Tag::try_from(row.tag)?.moved(old, new) {
+                moved_item_rows.push(SqlHistoryItemTag {
+                    tag: tag.to_string(),
+                    ..row
+                });
+            }
+        }
+        connection
+            .transaction(|connection| {
+                diesel::delete(entity_tags::table.filter(entity_tag_filter(old)))
+                    .execute(connection)?;
+                diesel::delete(history_item_tags::table.filter(history_item_tag_filter(old)))
+                    .execute(connection)?;
+                for row in moved_entity_rows.iter() {
+                    diesel::replace_into(entity_tags::table)
+                        .values(row)
+                        .execute(connection)?;
+                }
+                for row in moved_item_rows.iter() {
+                    diesel::replace_into(history_item_tags::table)
+                        .values(row)
+                        .execute(connection)?;
+                }
+                Ok(())
+            })
+            .map_err(|e: diesel::result::Error| {
+                LoreCoreError::SqlError(
+                    "Renaming tag in database failed: ".to_string() + &e.to_string(),
+                )
+            })
+    }
+
+    /// Removes `tag` and all tags nested below it from all entities and history items.
+    pub fn delete_tag(&self, tag: &Tag) -> Result<(), LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        diesel::delete(entity_tags::table.filter(entity_tag_filter(tag)))
+            .execute(&mut connection)
+            .map_err(|e| {
+                LoreCoreError::SqlError(
+                    "Deleting entity tags from database failed: ".to_string() + &e.to_string(),
+                )
+            })?;
+        diesel::delete(history_item_tags::table.filter(history_item_tag_filter(tag)))
+            .execute(&mut connection)
+            .map_err(|e| {
+                LoreCoreError::SqlError(
+                    "Deleting history item tags from database failed: ".to_string()
+                        + &e.to_string(),
+                )
+            })?;
+        Ok(())
+    }
+
+    /// Turns the entity columns with `descriptor`, whose descriptions hold comma-separated tags,
+    /// into tags of their entities and deletes the columns. Entities without other columns keep
+    /// theirs and are not tagged, since they would cease to exist. Returns the number of tagged
+    /// entities. Fails without changing anything if any of the tags is invalid or writing fails.
+    pub fn convert_descriptor_to_tags(
+        &self,
+        descriptor: &Descriptor,
+    ) -> Result<usize, LoreCoreError> {
+        let params =
+            EntityColumnSearchParams::new(None, Some(SqlSearchText::exact(descriptor.to_str())));
+        let column_counts: BTreeMap<_, _> = self
+            .count_descriptors_per_label(EntityColumnSearchParams::empty())?
+            .into_iter()
+            .collect();
+        let cols: Vec<_> = self
+            .read_entity_columns(params)?
+            .into_iter()
+            .filter(|col| {
+                column_counts
+                    .get(&col.label)
+                    .is_some_and(|count| *count > 1)
+            })
+            .collect();
+        let mut tags = Vec::new();
+        for col in cols.iter() {
+            for tag in col.description.to_str().split(',') {
+                if !tag.trim().is_empty() {
+                    tags.push(SqlEntityTag {
+                        label: col.label.to_string(),
+                        tag: Tag::try_from(tag)?.to_string(),
+                    });
+                }
+            }
+        }
+        let labels: Vec<_> = cols.iter().map(|col| col.label.to_string()).collect();
+        let mut connection = self.db_connection()?;
+        connection
+            .transaction(|connection| {
+                for tag in tags.iter() {
+                    diesel::replace_into(entity_tags::table)
+                        .values(tag)
+                        .execute(connection)?;
+                }
+                diesel::delete(
+                    entities::table
+                        .filter(entities::descriptor.eq(descriptor.to_str()))
+                        .filter(entities::label.eq_any(&labels)),
+                )
+                .execute(connection)?;
+                diesel::delete(
+                    timed_entity_columns::table
+                        .filter(timed_entity_columns::descriptor.eq(descriptor.to_str()))
+                        .filter(timed_entity_columns::label.eq_any(&labels)),
+                )
+                .execute(connection)?;
+                diesel::delete(
+                    description_references::table
+                        .filter(description_references::descriptor.eq(descriptor.to_str()))
+                        .filter(description_references::label.eq_any(&labels)),
+                )
+                .execute(connection)?;
+                Ok(())
+            })
+            .map_err(|e: diesel::result::Error| {
+                LoreCoreError::SqlError(
+                    "Converting entity columns to tags in database failed: ".to_string()
+                        + &e.to_string(),
+                )
+            })?;
+        Ok(cols.len())
+    }
+
+    fn insert_entity_tags(&self, tags: &[SqlEntityTag]) -> Result<(), LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        for tag in tags.iter() {
+            diesel::replace_into(entity_tags::table)
+                .values(tag)
+                .execute(&mut connection)
+                .map_err(|e| {
+                    LoreCoreError::SqlError(
+                        "Writing entity tag to database failed: ".to_string() + &e.to_string(),
+                    )
+                })?;
+        }
+        Ok(())
+    }
+
+    fn insert_history_item_tags(&self, tags: &[SqlHistoryItemTag]) -> Result<(), LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        for tag in tags.iter() {
+            diesel::replace_into(history_item_tags::table)
+                .values(tag)
+                .execute(&mut connection)
+                .map_err(|e| {
+                    LoreCoreError::SqlError(
+                        "Writing history item tag to database failed: ".to_string()
+                            + &e.to_string(),
+                    )
+                })?;
+        }
+        Ok(())
+    }
+}
+
+type EntityTagFilter = Box<dyn BoxableExpression<entity_tags::table, Sqlite, SqlType = Bool>>;
+
+/// Matches the rows tagged with `tag` or a tag nested below it.
+fn entity_tag_filter(tag: &Tag) -> EntityTagFilter {
+    let (from, until) = nested_tag_range(tag);
+    Box::new(
+        entity_tags::tag
+            .eq(tag.to_string())
+            .or(entity_tags::tag.ge(from).and(entity_tags::tag.lt(until))),
+    )
+}
+
+type HistoryItemTagFilter =
+    Box<dyn BoxableExpression<history_item_tags::table, Sqlite, SqlType = Bool>>;
+
+/// Matches the rows tagged with `tag` or a tag nested below it.
+fn history_item_tag_filter(tag: &Tag) -> HistoryItemTagFilter {
+    let (from, until) = nested_tag_range(tag);
+    Box::new(
+        history_item_tags::tag
+            .eq(tag.to_string())
+            .or(history_item_tags::tag
+                .ge(from)
+                .and(history_item_tags::tag.lt(until))),
+    )
+}
+
+/// The range of texts containing exactly the tags nested below `tag`, like "war/" up to
+/// "war0" for "war", since '0' directly follows '/'. Unlike LIKE, this is case-sensitive.
+pub(super) fn nested_tag_range(tag: &Tag) -> (String, String) {
+    (tag.to_string() + "/", tag.to_string() + "0")
+}
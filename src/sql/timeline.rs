@@ -0,0 +1,187 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::Display,
+};
+
+use crate::{errors::LoreCoreError, types::*};
+
+use super::{
+    lore_database::LoreDatabase,
+    path::ConnectionPath,
+    search_params::{RelationshipSearchParams, RelationshipTraversalParams},
+};
+
+/// Why a history item belongs to the timeline of an entity.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TimelineReason {
+    /// The item references the entity itself.
+    Mentioned,
+    /// The item references the entity at the end of `path`, which leads from the entity
+    /// whose timeline is read through the followed relationships.
+    Related(ConnectionPath),
+}
+
+impl Display for TimelineReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimelineReason::Mentioned => write!(f, "mentioned"),
+            TimelineReason::Related(path) => {
+                write!(f, "mentions {} ({})", path.end, path.explanation())
+            }
+        }
+    }
+}
+
+/// A history item on the timeline of an entity, with all reasons for including it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimelineEntry {
+    pub item: HistoryItem,
+    pub reasons: Vec<TimelineReason>,
+}
+
+impl LoreDatabase {
+    /// Returns the history items concerning `label` in chronological order. These are the items
+    /// referencing it via `\entityref`, and those referencing an entity reached from it through
+    /// the relationships followed according to `params`, in either direction.
+    /// Use a `max_depth` of 0 to only include items mentioning the entity itself.
+    /// Only items and relationships visible to `audience` are used, with references to hidden
+    /// entities redacted. The timeline of an entity hidden from `audience` is empty.
+    pub fn read_timeline(
+        &self,
+        label: &Label,
+        params: RelationshipTraversalParams,
+        audience: Visibility,
+    ) -> Result<Vec<TimelineEntry>, LoreCoreError> {
+        let hidden = self.read_redactions(audience)?;
+        if hidden.as_ref().is_some_and(|hidden| hidden.contains(label)) {
+            return Ok(Vec::new());
+        }
+        let rels =
+            self.read_relationships(RelationshipSearchParams::empty().for_audience(audience))?;
+        let mut reasons = vec![(label.clone(), TimelineReason::Mentioned)];
+        reasons.extend(
+            related_entities(label, &rels, &params)
+                .into_iter()
+                .map(|path| (path.end.clone(), TimelineReason::Related(path))),
+        );
+
+        let mut entries: BTreeMap<Timestamp, TimelineEntry> = BTreeMap::new();
+        for (related, reason) in reasons.into_iter() {
+            for item in self.read_history_items_referencing(&related, audience)? {
+                entries
+                    .entry(item.timestamp)
+                    .or_insert_with(|| TimelineEntry {
+                        item,
+                        reasons: Vec::new(),
+                    })
+                    .reasons
+                    .push(reason.clone());
+            }
+        }
+        let mut entries: Vec<_> = entries.into_values().collect();
+        entries.sort_by(|a, b| a.item.cmp(&b.item));
+        Ok(entries)
+    }
+}
+
+/// Finds a shortest path from `label` to every other entity reachable through the relationships
+/// followed according to `params`, ignoring their direction. Among paths of equal length, the
+/// one through the smaller labels is chosen. The paths are sorted by length, then by end.
+pub fn related_entities(
+    label: &Label,
+    rels: &[EntityRelationship],
+    params: &RelationshipTraversalParams,
+) -> Vec<ConnectionPath> {
+    let mut neighbours: BTreeMap<&str, Vec<(&str, &EntityRelationship)>> = BTreeMap::new();
+    for rel in rels.iter().filter(|rel| params.follows(rel)) {
+        let (parent, child) = (rel.parent.to_str(), rel.child.to_str());
+        neighbours.entry(parent).or_default().push((child, rel));
+        neighbours.entry(child).or_default().push((parent, rel));
+    }
+    for edges in neighbours.values_mut() {
+        edges.sort();
+    }
+
+    let mut paths: BTreeMap<&str, Vec<EntityRelationship>> =
+        BTreeMap::from([(label.to_str(), Vec::new())]);
+    let mut queue = VecDeque::from([label.to_str()]);
+    let mut related = Vec::new();
+    while let Some(node) = queue.pop_front() {
+        let path = paths[node].clone();
+        if params
+            .max_depth
+            .is_some_and(|max_depth| path.len() as u32 >= max_depth)
+        {
+            continue;
+        }
+        for &(next, rel) in neighbours.get(node).into_iter().flatten() {
+            if paths.contains_key(next) {
+                continue;
+            }
+            let mut next_path = path.clone();
+            next_path.push(rel.clone());
+            related.push(ConnectionPath {
+                start: label.clone(),
+                end: next.into(),
+                cost: next_path.len() as u64,
+                relationships: next_path.clone(),
+            });
+            paths.insert(next, next_path);
+            queue.push_back(next);
+        }
+    }
+    related.sort_by(|a, b| a.cost.cmp(&b.cost).then_with(|| a.end.cmp(&b.end)));
+    related
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rel(parent: &str, child: &str, role: &str) -> EntityRelationship {
+        EntityRelationship::new(parent.into(), child.into(), role.into())
+    }
+
+    fn ends(paths: &[ConnectionPath]) -> Vec<String> {
+        paths.iter().map(|path| path.end.to_string()).collect()
+    }
+
+    #[test]
+    fn test_related_entities_ignore_direction() {
+        let rels = vec![
+            rel("Gondor", "Aragorn", "member"),
+            rel("Aragorn", "Arathorn", "son"),
+            rel("Gondor", "Minas Tirith", "capital"),
+        ];
+        let params = RelationshipTraversalParams::new(vec!["member".into(), "son".into()], None);
+        let paths = related_entities(&"Gondor".into(), &rels, &params);
+        assert_eq!(ends(&paths), vec!["Aragorn", "Arathorn"]);
+        assert_eq!(
+            paths[1].explanation(),
+            "Gondor -[member]-> Aragorn -[son]-> Arathorn"
+        );
+        let paths = related_entities(&"Arathorn".into(), &rels, &params);
+        assert_eq!(
+            paths[1].explanation(),
+            "Arathorn <-[son]- Aragorn <-[member]- Gondor"
+        );
+    }
+
+    #[test]
+    fn test_related_entities_respect_max_depth() {
+        let rels = vec![rel("a", "b", "r"), rel("b", "c", "r"), rel("c", "a", "r")];
+        let paths = related_entities(
+            &"a".into(),
+            &rels,
+            &RelationshipTraversalParams::new(vec![], Some(1)),
+        );
+        assert_eq!(ends(&paths), vec!["b", "c"]);
+        assert!(paths.iter().all(|path| path.cost == 1));
+        let paths = related_entities(
+            &"a".into(),
+            &rels,
+            &RelationshipTraversalParams::new(vec![], Some(0)),
+        );
+        assert!(paths.is_empty());
+    }
+}
@@ -0,0 +1,104 @@
+use std::fmt::Display;
+
+use crate::{
+    errors::LoreCoreError,
+    extractions::{extract_entity_references, extract_labels},
+    fuzzy::rank_matches,
+    types::*,
+};
+
+use super::{
+    lore_database::LoreDatabase,
+    search_params::{EntityColumnSearchParams, HistoryItemSearchParams},
+};
+
+/// The number of "did you mean" suggestions attached to each finding.
+pub(crate) const MAX_SUGGESTIONS: usize = 3;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ReferenceSource {
+    EntityColumn(Label, Descriptor),
+    HistoryItem(Timestamp),
+}
+
+impl Display for ReferenceSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReferenceSource::EntityColumn(label, descriptor) => {
+                write!(f, "description of \"{}\" for \"{}\"", descriptor, label)
+            }
+            ReferenceSource::HistoryItem(timestamp) => write!(f, "history item {}", timestamp),
+        }
+    }
+}
+
+/// An `\entityref{label}` whose label does not belong to any entity.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownEntityReference {
+    pub label: Label,
+    pub source: ReferenceSource,
+    pub suggestions: Vec<Label>,
+}
+
+impl Display for UnknownEntityReference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Unknown entity \"{}\" referenced in {}.",
+            self.label, self.source
+        )?;
+        if !self.suggestions.is_empty() {
+            let suggestions: Vec<_> = self
+                .suggestions
+                .iter()
+                .map(|label| format!("\"{}\"", label))
+                .collect();
+            write!(f, " Did you mean {}?", suggestions.join(" or "))?;
+        }
+        Ok(())
+    }
+}
+
+impl LoreDatabase {
+    /// Finds all `\entityref{label}` occurrences in entity descriptions and history items
+    /// that do not refer to an existing entity, together with suggested corrections.
+    pub fn find_unknown_entity_references(
+        &self,
+    ) -> Result<Vec<UnknownEntityReference>, LoreCoreError> {
+        let cols = self.read_entity_columns(EntityColumnSearchParams::empty())?;
+        let items = self.read_history_items(HistoryItemSearchParams::empty())?;
+        let labels = extract_labels(&cols);
+
+        let mut references = Vec::new();
+        for col in cols.iter() {
+            for label in extract_entity_references(col.description.to_str()) {
+                references.push((
+                    label,
+                    ReferenceSource::EntityColumn(col.label.clone(), col.descriptor.clone()),
+                ));
+            }
+        }
+        for item in items.iter() {
+            for label in item.entity_references() {
+                references.push((label, ReferenceSource::HistoryItem(item.timestamp)));
+            }
+        }
+
+        let unknown_references = references
+            .into_iter()
+            .filter(|(label, _)| !labels.contains(label))
+            .map(|(label, source)| {
+                let suggestions = rank_matches(label.to_str(), &labels, MAX_SUGGESTIONS)
+                    .into_iter()
+                    .map(|m| m.candidate)
+                    .collect();
+                UnknownEntityReference {
+                    label,
+                    source,
+                    suggestions,
+                }
+            })
+            .collect();
+        Ok(unknown_references)
+    }
+}
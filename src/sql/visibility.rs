@@ -0,0 +1,256 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use ::diesel::prelude::*;
+use serde_json::Value;
+
+use crate::{
+    errors::{sql_loading_error, LoreCoreError},
+    extractions::redact_entity_references,
+    types::*,
+};
+
+use super::{
+    lifespan::LIFE_EVENT_KINDS,
+    lore_database::LoreDatabase,
+    schema::{entities, relationships},
+};
+
+impl LoreDatabase {
+    /// Sets who may see the entity column. An entity is hidden from an audience if none of its
+    /// columns are visible to it.
+    pub fn change_entity_column_visibility(
+        &self,
+        (label, descriptor): (&Label, &Descriptor),
+        visibility: Visibility,
+    ) -> Result<(), LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        let changed = diesel::update(
+            entities::table
+                .filter(entities::label.eq(label.to_str()))
+                .filter(entities::descriptor.eq(descriptor.to_str())),
+        )
+        .set(entities::visibility.eq(visibility.to_int()))
+        .execute(&mut connection)
+        .map_err(|e| {
+            LoreCoreError::SqlError(
+                "Changing entity column visibility in database failed: ".to_string()
+                    + &e.to_string(),
+            )
+        })?;
+        if changed == 0 {
+            return Err(LoreCoreError::InputError(format!(
+                "Entity column \"{}\" of \"{}\" does not exist.",
+                descriptor, label
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn read_entity_column_visibility(
+        &self,
+        (label, descriptor): (&Label, &Descriptor),
+    ) -> Result<Visibility, LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        let visibility = entities::table
+            .filter(entities::label.eq(label.to_str()))
+            .filter(entities::descriptor.eq(descriptor.to_str()))
+            .select(entities::visibility)
+            .load::<i32>(&mut connection)
+            .map_err(|e| {
+                sql_loading_error(
+                    "entity column visibility",
+                    vec![("label", label), ("descriptor", descriptor)],
+                    e,
+                )
+            })?
+            .pop();
+        match visibility {
+            Some(visibility) => Visibility::from_int(visibility),
+            None => Err(LoreCoreError::InputError(format!(
+                "Entity column \"{}\" of \"{}\" does not exist.",
+                descriptor, label
+            ))),
+        }
+    }
+
+    /// Sets who may see the relationship. Relationships are also hidden from audiences that
+    /// their parent or child is hidden from.
+    pub fn change_relationship_visibility(
+        &self,
+        relationship: &EntityRelationship,
+        visibility: Visibility,
+    ) -> Result<(), LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        let changed = diesel::update(
+            relationships::table.filter(
+                relationships::parent
+                    .eq(relationship.parent.to_str())
+                    .and(relationships::child.eq(relationship.child.to_str()))
+                    .and(relationships::role.eq(relationship.role.to_str())),
+            ),
+        )
+        .set(relationships::visibility.eq(visibility.to_int()))
+        .execute(&mut connection)
+        .map_err(|e| {
+            LoreCoreError::SqlError(
+                "Changing relationship visibility in database failed: ".to_string()
+                    + &e.to_string(),
+            )
+        })?;
+        if changed == 0 {
+            return Err(LoreCoreError::InputError(format!(
+                "Relationship from \"{}\" to \"{}\" with role \"{}\" does not exist.",
+                relationship.parent, relationship.child, relationship.role
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn read_relationship_visibility(
+        &self,
+        relationship: &EntityRelationship,
+    ) -> Result<Visibility, LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        let visibility = relationships::table
+            .filter(
+                relationships::parent
+                    .eq(relationship.parent.to_str())
+                    .and(relationships::child.eq(relationship.child.to_str()))
+                    .and(relationships::role.eq(relationship.role.to_str())),
+            )
+            .select(relationships::visibility)
+            .load::<i32>(&mut connection)
+            .map_err(|e| {
+                sql_loading_error(
+                    "relationship visibility",
+                    vec![("relationship", relationship)],
+                    e,
+                )
+            })?
+            .pop();
+        match visibility {
+            Some(visibility) => Visibility::from_int(visibility),
+            None => Err(LoreCoreError::InputError(format!(
+                "Relationship from \"{}\" to \"{}\" with role \"{}\" does not exist.",
+                relationship.parent, relationship.child, relationship.role
+            ))),
+        }
+    }
+
+    /// Sets who may see the history item by setting its `visibility` property.
+    pub fn change_history_item_visibility(
+        &self,
+        timestamp: Timestamp,
+        visibility: Visibility,
+    ) -> Result<(), LoreCoreError> {
+        let mut properties = self.read_history_item(timestamp)?.properties;
+        properties.set_visibility(visibility);
+        self.change_history_item_properties(timestamp, &properties)
+    }
+
+    /// Returns the labels of the entities with columns, none of which are visible to `audience`.
+    pub fn read_hidden_labels(
+        &self,
+        audience: Visibility,
+    ) -> Result<BTreeSet<Label>, LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        let mut lowest_visibilities: BTreeMap<String, i32> = BTreeMap::new();
+        for (label, visibility) in entities::table
+            .select((entities::label, entities::visibility))
+            .load::<(String, i32)>(&mut connection)
+            .map_err(|e| sql_loading_error("entity visibilities", vec![], e))?
+        {
+            let lowest = lowest_visibilities.entry(label).or_insert(visibility);
+            *lowest = (*lowest).min(visibility);
+        }
+        let hidden = lowest_visibilities
+            .into_iter()
+            .filter(|(_, visibility)| *visibility > audience.to_int())
+            .map(|(label, _)| label.into())
+            .collect();
+        Ok(hidden)
+    }
+
+    /// The labels hidden from `audience`, or `None` if it sees everything anyway.
+    pub(super) fn read_redactions(
+        &self,
+        audience: Visibility,
+    ) -> Result<Option<BTreeSet<Label>>, LoreCoreError> {
+        if audience == Visibility::AuthorOnly {
+            return Ok(None);
+        }
+        Ok(Some(self.read_hidden_labels(audience)?))
+    }
+}
+
+/// Replaces references to `hidden` entities in the description.
+pub fn redact_entity_column(col: EntityColumn, hidden: &BTreeSet<Label>) -> EntityColumn {
+    let description =
+        redact_entity_references(col.description.to_str(), |label| hidden.contains(label));
+    EntityColumn {
+        description: description.into(),
+        ..col
+    }
+}
+
+/// Replaces references to `hidden` entities in the content and additional concerns,
+/// and removes them from the lists of births, deaths, foundings and dissolutions.
+pub fn redact_history_item(item: HistoryItem, hidden: &BTreeSet<Label>) -> HistoryItem {
+    let is_hidden = |label: &Label| hidden.contains(label);
+    let content = redact_entity_references(item.content.to_str(), is_hidden);
+    let mut properties = item.properties.clone();
+    if properties.get("additional_concerns").is_some() {
+        let concerns = properties
+            .additional_concerns()
+            .iter()
+            .map(|concern| redact_entity_references(concern, is_hidden))
+            .collect();
+        properties.set_additional_concerns(concerns);
+    }
+    for key in LIFE_EVENT_KINDS.iter().map(|kind| kind.property_key()) {
+        if properties.get(key).is_some() {
+            let labels = properties
+                .labels(key)
+                .into_iter()
+                .filter(|label| !is_hidden(label))
+                .map(|label| Value::String(label.to_string()))
+                .collect();
+            properties.set(key, Value::Array(labels));
+        }
+    }
+    HistoryItem {
+        content: content.into(),
+        properties,
+        ..item
+    }
+}
+
+/// Whether the relationship connects an entity in `hidden`.
+pub fn concerns_hidden_entity(rel: &EntityRelationship, hidden: &BTreeSet<Label>) -> bool {
+    hidden.contains(&rel.parent.to_str().into()) || hidden.contains(&rel.child.to_str().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_history_item() {
+        let mut item = HistoryItem::new(
+            1.into(),
+            300.into(),
+            Day::NONE,
+            "\\entityref{Spy} meets \\entityref{King}.".into(),
+        );
+        item.properties
+            .set_additional_concerns(vec!["\\entityref{Spy}".to_string()]);
+        item.properties
+            .set("births", serde_json::json!(["Spy", "Heir"]));
+        let hidden = BTreeSet::from(["Spy".into()]);
+        let item = redact_history_item(item, &hidden);
+        assert_eq!(item.content.to_str(), "[redacted] meets \\entityref{King}.");
+        assert_eq!(item.properties.additional_concerns(), vec!["[redacted]"]);
+        assert_eq!(item.properties.births(), vec!["Heir".into()]);
+        assert_eq!(item.properties.get("deaths"), None);
+    }
+}
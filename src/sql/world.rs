@@ -0,0 +1,236 @@
+use std::collections::BTreeMap;
+
+use ::diesel::prelude::*;
+use diesel::{
+    dsl::{exists, not},
+    sql_types::Bool,
+    sqlite::Sqlite,
+};
+
+use crate::{
+    errors::{sql_loading_error, LoreCoreError},
+    extractions::redact_entity_references,
+    types::*,
+};
+
+use super::{
+    lore_database::LoreDatabase,
+    schema::{entities, entity_tags, timed_entity_columns},
+    search_params::{EntityColumnSearchParams, RelationshipSearchParams},
+    sql_types::SqlTimedEntityColumn,
+    tag::nested_tag_range,
+};
+
+/// The entity columns and relationships as they stood in a given year.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WorldState {
+    pub year: Year,
+    pub columns: Vec<EntityColumn>,
+    pub relationships: Vec<EntityRelationship>,
+}
+
+impl LoreDatabase {
+    /// Stores descriptions with limited validity. Fails if a column ends before it starts,
+    /// or if it overlaps in time with another one for the same label and descriptor.
+    pub fn write_timed_entity_columns(
+        &self,
+        cols: Vec<TimedEntityColumn>,
+    ) -> Result<(), LoreCoreError> {
+        let mut existing = self.read_timed_entity_columns(EntityColumnSearchParams::empty())?;
+        for col in cols.iter() {
+            if let (Some(start), Some(end)) = (col.start, col.end) {
+                if start > end {
+                    return Err(LoreCoreError::InputError(format!(
+                        "The description \"{}\" of \"{}\" for \"{}\" ends in {} before it starts in {}.",
+                        col.description, col.descriptor, col.label, end, start
+                    )));
+                }
+            }
+            if let Some(other) = existing.iter().find(|other| col.overlaps(other)) {
+                return Err(LoreCoreError::InputError(format!(
+                    "The description \"{}\" of \"{}\" for \"{}\" overlaps in time with \"{}\".",
+                    col.description, col.descriptor, col.label, other.description
+                )));
+            }
+            existing.push(col.clone());
+        }
+        let mut connection = self.db_connection()?;
+        for col in cols.into_iter() {
+            let col = col.to_sql_timed_entity_column();
+            diesel::insert_into(timed_entity_columns::table)
+                .values(&col)
+                .execute(&mut connection)
+                .map_err(|e| {
+                    LoreCoreError::SqlError(
+                        "Writing timed column to database failed: ".to_string() + &e.to_string(),
+                    )
+                })?;
+        }
+        Ok(())
+    }
+
+    pub fn read_timed_entity_columns(
+        &self,
+        search_params: EntityColumnSearchParams,
+    ) -> Result<Vec<TimedEntityColumn>, LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        let mut cols: Vec<_> = timed_entity_columns::table
+            .filter(timed_entity_column_filter(&search_params))
+            .load::<SqlTimedEntityColumn>(&mut connection)
+            .map_err(|e| {
+                sql_loading_error(
+                    "timed entity columns",
+                    vec![
+                        ("label", &search_params.label),
+                        ("descriptor", &search_params.descriptor),
+                    ],
+                    e,
+                )
+            })?
+            .into_iter()
+            .map(|col| col.to_timed_entity_column())
+            .collect();
+        if let Some(hidden) = self.read_redactions(search_params.audience)? {
+            for col in cols.iter_mut() {
+                col.description = redact_entity_references(col.description.to_str(), |label| {
+                    hidden.contains(label)
+                })
+                .into();
+            }
+        }
+        cols.sort();
+        Ok(cols)
+    }
+
+    /// Deletes the timed column of `label` and `descriptor` starting in the year `start`.
+    pub fn delete_timed_entity_column(
+        &self,
+        (label, descriptor): (&Label, &Descriptor),
+        start: Option<Year>,
+    ) -> Result<(), LoreCoreError> {
+        let mut connection = self.db_connection()?;
+        let cols = timed_entity_columns::table
+            .filter(timed_entity_columns::label.eq(label.to_str()))
+            .filter(timed_entity_columns::descriptor.eq(descriptor.to_str()));
+        let result = match start {
+            Some(start) => {
+                diesel::delete(cols.filter(timed_entity_columns::start_year.eq(start.to_int())))
+                    .execute(&mut connection)
+            }
+            None => diesel::delete(cols.filter(timed_entity_columns::start_year.is_null()))
+                .execute(&mut connection),
+        };
+        result.map_err(|e| {
+            LoreCoreError::SqlError(
+                "Deleting timed column from database failed: ".to_string() + &e.to_string(),
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Returns the world as it stood in `year`. Untimed entity columns are always valid,
+    /// but are replaced by timed columns with the same label and descriptor that are valid
+    /// in `year`. Relationships are included if they are valid in `year`.
+    /// Only the parts visible to `audience` are included, and references to hidden entities
+    /// are redacted. Timed columns are hidden along with their entity or with the untimed column
+    /// of the same label and descriptor.
+    pub fn world_at(&self, year: Year, audience: Visibility) -> Result<WorldState, LoreCoreError> {
+        let cols =
+            self.read_entity_columns(EntityColumnSearchParams::empty().for_audience(audience))?;
+        let timed_cols = self
+            .read_timed_entity_columns(EntityColumnSearchParams::empty().for_audience(audience))?;
+        let relationships = self.read_relationships(
+            RelationshipSearchParams::empty()
+                .valid_at(year)
+                .for_audience(audience),
+        )?;
+        Ok(WorldState {
+            year,
+            columns: columns_at(&cols, &timed_cols, year),
+            relationships,
+        })
+    }
+}
+
+/// Combines untimed and timed columns into the columns valid in `year`, sorted.
+pub fn columns_at(
+    cols: &[EntityColumn],
+    timed_cols: &[TimedEntityColumn],
+    year: Year,
+) -> Vec<EntityColumn> {
+    let mut columns: BTreeMap<(Label, Descriptor), EntityColumn> = cols
+        .iter()
+        .map(|col| ((col.label.clone(), col.descriptor.clone()), col.clone()))
+        .collect();
+    for col in timed_cols.iter().filter(|col| col.is_valid_at(year)) {
+        columns.insert(
+            (col.label.clone(), col.descriptor.clone()),
+            col.to_entity_column(),
+        );
+    }
+    columns.into_values().collect()
+}
+
+type TimedEntityColumnFilter =
+    Box<dyn BoxableExpression<timed_entity_columns::table, Sqlite, SqlType = Bool>>;
+
+fn timed_entity_column_filter(search_params: &EntityColumnSearchParams) -> TimedEntityColumnFilter {
+    let mut filter: TimedEntityColumnFilter = Box::new(true.into_sql::<Bool>());
+    let label = &search_params.label;
+    if label.is_some() {
+        if label.is_exact {
+            filter = Box::new(filter.and(timed_entity_columns::label.eq(label.exact_text())));
+        } else {
+            filter = Box::new(filter.and(timed_entity_columns::label.like(label.search_pattern())));
+        }
+    }
+    let descriptor = &search_params.descriptor;
+    if descriptor.is_some() {
+        if descriptor.is_exact {
+            filter =
+                Box::new(filter.and(timed_entity_columns::descriptor.eq(descriptor.exact_text())));
+        } else {
+            filter = Box::new(
+                filter.and(timed_entity_columns::descriptor.like(descriptor.search_pattern())),
+            );
+        }
+    }
+    if let Some(tag) = &search_params.tag {
+        let (from, until) = nested_tag_range(tag);
+        filter = Box::new(
+            filter.and(
+                timed_entity_columns::label.eq_any(
+                    entity_tags::table
+                        .filter(
+                            entity_tags::tag
+                                .eq(tag.to_string())
+                                .or(entity_tags::tag.ge(from).and(entity_tags::tag.lt(until))),
+                        )
+                        .select(entity_tags::label),
+                ),
+            ),
+        );
+    }
+    if search_params.audience != Visibility::AuthorOnly {
+        let audience = search_params.audience.to_int();
+        // Timed columns are hidden along with their entity, which is hidden if none of its
+        // columns are visible, and with the untimed column of the same label and descriptor.
+        filter = Box::new(
+            filter
+                .and(
+                    timed_entity_columns::label.eq_any(
+                        entities::table
+                            .filter(entities::visibility.le(audience))
+                            .select(entities::label),
+                    ),
+                )
+                .and(not(exists(
+                    entities::table
+                        .filter(entities::label.eq(timed_entity_columns::label))
+                        .filter(entities::descriptor.eq(timed_entity_columns::descriptor))
+                        .filter(entities::visibility.gt(audience)),
+                ))),
+        );
+    }
+    filter
+}
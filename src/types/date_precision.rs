@@ -0,0 +1,193 @@
+use std::fmt::Display;
+
+use crate::{calendar::ordinal, errors::LoreCoreError};
+
+use super::year::Year;
+
+/// How precisely the year of a history item is known.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DatePrecision {
+    #[default]
+    Exact,
+    /// Around the year. For sorting and searching, circa dates count as their year.
+    Circa,
+    /// At some point from the year until this many years later.
+    Range(u32),
+    /// At some point in the decade containing the year.
+    Decade,
+    /// At some point in the century containing the year, counting centuries from year 0.
+    Century,
+}
+
+/// The markup used for rendering dates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Markup {
+    Plain,
+    Markdown,
+    Latex,
+}
+
+impl DatePrecision {
+    pub fn earliest_year(&self, year: Year) -> Year {
+        match self {
+            DatePrecision::Exact | DatePrecision::Circa | DatePrecision::Range(_) => year,
+            DatePrecision::Decade => year.to_int().div_euclid(10).saturating_mul(10).into(),
+            DatePrecision::Century => year.to_int().div_euclid(100).saturating_mul(100).into(),
+        }
+    }
+
+    pub fn latest_year(&self, year: Year) -> Year {
+        let earliest = self.earliest_year(year).to_int();
+        let span = match self {
+            DatePrecision::Exact | DatePrecision::Circa => 0,
+            DatePrecision::Range(years) => (*years).min(i32::MAX as u32) as i32,
+            DatePrecision::Decade => 9,
+            DatePrecision::Century => 99,
+        };
+        earliest.saturating_add(span).into()
+    }
+
+    /// Whether a history item with this precision may have happened between `from` and `to`.
+    pub fn overlaps(&self, year: Year, from: Year, to: Year) -> bool {
+        self.earliest_year(year) <= to && from <= self.latest_year(year)
+    }
+
+    /// Renders the date like "300", "c. 300", "120–140", "310s", "4th century" or
+    /// "2nd century before year 0".
+    pub fn format(&self, year: Year, markup: Markup) -> String {
+        let earliest = self.earliest_year(year).to_int() as i64;
+        match self {
+            DatePrecision::Decade => format!("{}s", earliest),
+            DatePrecision::Century if earliest >= 0 => {
+                format!("{} century", ordinal((earliest / 100 + 1) as u32))
+            }
+            DatePrecision::Century => {
+                format!(
+                    "{} century before year 0",
+                    ordinal((-earliest / 100) as u32)
+                )
+            }
+            _ => self.format_with(year, markup, |year| year.to_string()),
+        }
+    }
+
+    /// Renders the date with years formatted by `format_year`. Decades and centuries are
+    /// rendered as the range of their years.
+    pub fn format_with<F>(&self, year: Year, markup: Markup, format_year: F) -> String
+    where
+        F: Fn(Year) -> String,
+    {
+        let (circa, dash) = match markup {
+            Markup::Plain => ("c. ", "\u{2013}"),
+            Markup::Markdown => ("*c.* ", "\u{2013}"),
+            Markup::Latex => ("c.~", "--"),
+        };
+        match self {
+            DatePrecision::Exact => format_year(year),
+            DatePrecision::Circa => format!("{}{}", circa, format_year(year)),
+            DatePrecision::Range(_) | DatePrecision::Decade | DatePrecision::Century => format!(
+                "{}{}{}",
+                format_year(self.earliest_year(year)),
+                dash,
+                format_year(self.latest_year(year))
+            ),
+        }
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            DatePrecision::Exact => "exact",
+            DatePrecision::Circa => "circa",
+            DatePrecision::Range(_) => "range",
+            DatePrecision::Decade => "decade",
+            DatePrecision::Century => "century",
+        }
+    }
+
+    pub(crate) fn range_years(&self) -> u32 {
+        match self {
+            DatePrecision::Range(years) => *years,
+            _ => 0,
+        }
+    }
+
+    pub(crate) fn from_name(name: &str, range_years: u32) -> Result<Self, LoreCoreError> {
+        match name {
+            "exact" => Ok(DatePrecision::Exact),
+            "circa" => Ok(DatePrecision::Circa),
+            "range" => Ok(DatePrecision::Range(range_years)),
+            "decade" => Ok(DatePrecision::Decade),
+            "century" => Ok(DatePrecision::Century),
+            _ => Err(LoreCoreError::InputError(format!(
+                "Unknown date precision \"{}\".",
+                name
+            ))),
+        }
+    }
+}
+
+impl Display for DatePrecision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DatePrecision::Range(years) => write!(f, "range of {} years", years),
+            _ => write!(f, "{}", self.name()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounds() {
+        assert_eq!(DatePrecision::Decade.earliest_year(315.into()), 310.into());
+        assert_eq!(DatePrecision::Decade.latest_year(315.into()), 319.into());
+        assert_eq!(
+            DatePrecision::Decade.earliest_year((-5).into()),
+            (-10).into()
+        );
+        assert_eq!(DatePrecision::Century.latest_year(315.into()), 399.into());
+        assert_eq!(DatePrecision::Range(20).latest_year(120.into()), 140.into());
+        assert!(DatePrecision::Decade.overlaps(315.into(), 300.into(), 311.into()));
+        assert!(!DatePrecision::Circa.overlaps(300.into(), 301.into(), 310.into()));
+    }
+
+    #[test]
+    fn test_format() {
+        let format =
+            |precision: DatePrecision, year: i32, markup| precision.format(year.into(), markup);
+        assert_eq!(format(DatePrecision::Exact, 300, Markup::Plain), "300");
+        assert_eq!(format(DatePrecision::Circa, 300, Markup::Plain), "c. 300");
+        assert_eq!(
+            format(DatePrecision::Circa, 300, Markup::Markdown),
+            "*c.* 300"
+        );
+        assert_eq!(format(DatePrecision::Circa, 300, Markup::Latex), "c.~300");
+        assert_eq!(
+            format(DatePrecision::Range(20), 120, Markup::Markdown),
+            "120\u{2013}140"
+        );
+        assert_eq!(
+            format(DatePrecision::Range(20), 120, Markup::Latex),
+            "120--140"
+        );
+        assert_eq!(format(DatePrecision::Decade, 315, Markup::Plain), "310s");
+        assert_eq!(
+            format(DatePrecision::Century, 315, Markup::Plain),
+            "4th century"
+        );
+        assert_eq!(
+            format(DatePrecision::Century, 1015, Markup::Plain),
+            "11th century"
+        );
+        assert_eq!(
+            format(DatePrecision::Century, -15, Markup::Plain),
+            "1st century before year 0"
+        );
+        assert_eq!(
+            format(DatePrecision::Century, -101, Markup::Plain),
+            "2nd century before year 0"
+        );
+    }
+}
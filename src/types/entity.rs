@@ -1,4 +1,14 @@
-use super::{description::Description, descriptor::Descriptor, label::Label};
+use std::collections::BTreeMap;
+
+use crate::{
+    extractions::extract_descriptors,
+    sql::relationship::{extract_children, extract_parents},
+};
+
+use super::{
+    child::Child, description::Description, descriptor::Descriptor, history::HistoryItem,
+    label::Label, parent::Parent, relationship::EntityRelationship, role::Role,
+};
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct EntityColumn {
@@ -6,3 +16,111 @@ pub struct EntityColumn {
     pub descriptor: Descriptor,
     pub description: Description,
 }
+
+impl EntityColumn {
+    pub fn new(label: Label, descriptor: Descriptor, description: Description) -> Self {
+        Self {
+            label,
+            descriptor,
+            description,
+        }
+    }
+}
+
+/// All information stored about a single entity, gathered from every table.
+///
+/// Outgoing relationships are those in which the entity is the parent,
+/// incoming relationships are those in which it is the child.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entity {
+    pub label: Label,
+    pub columns: Vec<EntityColumn>,
+    pub outgoing_relationships: BTreeMap<Role, Vec<EntityRelationship>>,
+    pub incoming_relationships: BTreeMap<Role, Vec<EntityRelationship>>,
+    pub history_items: Vec<HistoryItem>,
+}
+
+impl Entity {
+    /// Assembles the entity with the given label from already loaded data.
+    /// Columns, relationships and history items not concerning the entity are ignored.
+    pub fn new(
+        label: Label,
+        cols: &[EntityColumn],
+        rels: &[EntityRelationship],
+        items: &[HistoryItem],
+    ) -> Self {
+        let mut columns: Vec<_> = cols.iter().filter(|c| c.label == label).cloned().collect();
+        columns.sort();
+
+        let mut outgoing_relationships: BTreeMap<Role, Vec<EntityRelationship>> = BTreeMap::new();
+        let mut incoming_relationships: BTreeMap<Role, Vec<EntityRelationship>> = BTreeMap::new();
+        for rel in rels.iter() {
+            if rel.parent.to_str() == label.to_str() {
+                outgoing_relationships
+                    .entry(rel.role.clone())
+                    .or_default()
+                    .push(rel.clone());
+            }
+            if rel.child.to_str() == label.to_str() {
+                incoming_relationships
+                    .entry(rel.role.clone())
+                    .or_default()
+                    .push(rel.clone());
+            }
+        }
+        for rels in outgoing_relationships.values_mut() {
+            rels.sort();
+        }
+        for rels in incoming_relationships.values_mut() {
+            rels.sort();
+        }
+
+        let mut history_items: Vec<_> = items
+            .iter()
+            .filter(|item| item.mentions(&label))
+            .cloned()
+            .collect();
+        history_items.sort();
+
+        Self {
+            label,
+            columns,
+            outgoing_relationships,
+            incoming_relationships,
+            history_items,
+        }
+    }
+
+    pub fn descriptors(&self) -> Vec<Descriptor> {
+        extract_descriptors(&self.columns)
+    }
+
+    pub fn description(&self, descriptor: &Descriptor) -> Option<&Description> {
+        self.columns
+            .iter()
+            .find(|c| &c.descriptor == descriptor)
+            .map(|c| &c.description)
+    }
+
+    /// The parents of all incoming relationships.
+    pub fn parents(&self) -> Vec<Parent> {
+        let rels: Vec<_> = self
+            .incoming_relationships
+            .values()
+            .flatten()
+            .cloned()
+            .collect();
+        extract_parents(&rels)
+    }
+
+    /// The children of all outgoing relationships.
+    pub fn children(&self) -> Vec<Child> {
+        let rels: Vec<_> = self
+            .outgoing_relationships
+            .values()
+            .flatten()
+            .cloned()
+            .collect();
+        extract_children(&rels)
+    }
+}
@@ -1,8 +1,10 @@
 use std::fmt::Display;
 
+use crate::extractions::extract_entity_references;
+
 use super::{
-    day::Day, history_item_content::HistoryItemContent,
-    history_item_properties::HistoryItemProperties, timestamp::Timestamp, year::Year,
+    date_precision::DatePrecision, day::Day, history_item_content::HistoryItemContent,
+    history_item_properties::HistoryItemProperties, label::Label, timestamp::Timestamp, year::Year,
 };
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -12,6 +14,66 @@ pub struct HistoryItem {
     pub day: Day,
     pub content: HistoryItemContent,
     pub properties: HistoryItemProperties,
+    pub precision: DatePrecision,
+    /// Orders history items on the same day, lower keys first.
+    pub sort_key: i64,
+    /// Whether the item is a repetition of a recurring item, see
+    /// [`Recurrence::occurrences`](super::recurrence::Recurrence::occurrences).
+    /// Repetitions are never stored and share the timestamp of the stored item, so
+    /// [`HistoryItem::occurrence_key`] has to be used to tell them apart.
+    pub is_occurrence: bool,
+}
+
+impl HistoryItem {
+    /// An exactly dated item without properties.
+    pub fn new(timestamp: Timestamp, year: Year, day: Day, content: HistoryItemContent) -> Self {
+        Self {
+            timestamp,
+            year,
+            day,
+            content,
+            properties: HistoryItemProperties::none(),
+            precision: DatePrecision::Exact,
+            sort_key: 0,
+            is_occurrence: false,
+        }
+    }
+
+    pub fn with_properties(self, properties: HistoryItemProperties) -> Self {
+        Self { properties, ..self }
+    }
+
+    pub fn with_precision(self, precision: DatePrecision) -> Self {
+        Self { precision, ..self }
+    }
+
+    /// The labels referenced via `\entityref{label}` in the content or the additional concerns.
+    pub fn entity_references(&self) -> Vec<Label> {
+        let mut labels = extract_entity_references(self.content.to_str());
+        for concern in self.properties.additional_concerns() {
+            labels.extend(extract_entity_references(&concern));
+        }
+        labels.sort();
+        labels.dedup();
+        labels
+    }
+
+    /// Identifies the item among the stored items and the repetitions of recurring items.
+    pub fn occurrence_key(&self) -> (Timestamp, Year) {
+        (self.timestamp, self.year)
+    }
+
+    pub fn earliest_year(&self) -> Year {
+        self.precision.earliest_year(self.year)
+    }
+
+    pub fn latest_year(&self) -> Year {
+        self.precision.latest_year(self.year)
+    }
+
+    pub fn mentions(&self, label: &Label) -> bool {
+        self.entity_references().contains(label)
+    }
 }
 
 impl PartialOrd for HistoryItem {
@@ -20,15 +82,16 @@ impl PartialOrd for HistoryItem {
     }
 }
 
+/// History items are ordered by the earliest year they may have happened in, then by day,
+/// then by their sort key. Among otherwise equal items, more precisely dated ones come first.
 impl Ord for HistoryItem {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        match self.year.cmp(&other.year) {
-            std::cmp::Ordering::Equal => match self.day.cmp(&other.day) {
-                std::cmp::Ordering::Equal => self.timestamp.cmp(&other.timestamp),
-                other => other,
-            },
-            other => other,
-        }
+        self.earliest_year()
+            .cmp(&other.earliest_year())
+            .then_with(|| self.day.cmp(&other.day))
+            .then_with(|| self.sort_key.cmp(&other.sort_key))
+            .then_with(|| self.latest_year().cmp(&other.latest_year()))
+            .then_with(|| self.timestamp.cmp(&other.timestamp))
     }
 }
 
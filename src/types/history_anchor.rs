@@ -0,0 +1,110 @@
+use crate::{calendar::Calendar, errors::LoreCoreError};
+
+use super::{day::Day, timestamp::Timestamp, year::Year};
+
+/// Dates a history item relative to another one, like "three days after the coronation".
+/// Day offsets count days of the year and do not carry over into neighbouring years.
+/// Without a calendar the length of the year is unknown, so only offsets before its first day
+/// are rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct HistoryAnchor {
+    /// The timestamp of the history item this one is dated relative to.
+    pub anchor: Timestamp,
+    pub years: i32,
+    pub days: i32,
+}
+
+impl HistoryAnchor {
+    pub fn new(anchor: Timestamp, years: i32, days: i32) -> Self {
+        Self {
+            anchor,
+            years,
+            days,
+        }
+    }
+
+    /// The date `years` and `days` after the date of the anchor.
+    /// Anchors dated only by year cannot be offset by days. If `calendar` is given, the
+    /// resulting day has to exist in it.
+    pub fn apply(
+        &self,
+        year: Year,
+        day: Day,
+        calendar: Option<&Calendar>,
+    ) -> Result<(Year, Day), LoreCoreError> {
+        let year = year + self.years;
+        if self.days == 0 {
+            return Ok((year, day));
+        }
+        let Some(anchor_day) = day.to_optional_int() else {
+            return Err(LoreCoreError::InputError(format!(
+                "History item {} has no day to offset by {} days.",
+                self.anchor, self.days
+            )));
+        };
+        let day = anchor_day as i64 + self.days as i64;
+        if day < 1 || day > u32::MAX as i64 {
+            return Err(LoreCoreError::InputError(format!(
+                "Offsetting day {} of history item {} by {} days leaves the year.",
+                anchor_day, self.anchor, self.days
+            )));
+        }
+        let day = (day as u32).into();
+        if let Some(calendar) = calendar {
+            calendar.validate(year, day)?;
+        }
+        Ok((year, day))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::Month;
+
+    #[test]
+    fn test_apply() {
+        let anchor = HistoryAnchor::new(1.into(), 0, 3);
+        assert_eq!(
+            anchor.apply(100.into(), 5.into(), None).unwrap(),
+            (100.into(), 8.into())
+        );
+        assert!(anchor.apply(100.into(), Day::NONE, None).is_err());
+
+        let anchor = HistoryAnchor::new(1.into(), -2, 0);
+        assert_eq!(
+            anchor.apply(100.into(), Day::NONE, None).unwrap(),
+            (98.into(), Day::NONE)
+        );
+
+        let anchor = HistoryAnchor::new(1.into(), 0, -5);
+        assert!(anchor.apply(100.into(), 5.into(), None).is_err());
+    }
+
+    #[test]
+    fn test_apply_checks_calendar() {
+        let calendar = Calendar {
+            name: "Short".to_string(),
+            months: vec![Month {
+                name: "Only".to_string(),
+                days: 10,
+                leap_days: 0,
+            }],
+            weekdays: Vec::new(),
+            leap_rule: None,
+            intercalary_days: Vec::new(),
+            epoch: 0,
+            first_weekday: 0,
+        };
+        let anchor = HistoryAnchor::new(1.into(), 0, 3);
+        assert_eq!(
+            anchor.apply(100.into(), 7.into(), Some(&calendar)).unwrap(),
+            (100.into(), 10.into())
+        );
+        assert!(anchor.apply(100.into(), 8.into(), Some(&calendar)).is_err());
+        assert_eq!(
+            anchor.apply(100.into(), 8.into(), None).unwrap(),
+            (100.into(), 11.into())
+        );
+    }
+}
@@ -2,6 +2,10 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::{collections::HashMap, fmt::Display};
 
+use crate::errors::LoreCoreError;
+
+use super::{label::Label, visibility::Visibility};
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct HistoryItemProperties(pub(crate) HashMap<String, Value>);
 
@@ -13,6 +17,94 @@ impl HistoryItemProperties {
     pub fn to_map(&self) -> &HashMap<String, Value> {
         &self.0
     }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.0.get(key)
+    }
+
+    /// Sets the value of `key`. Values are checked against the property schema when written.
+    pub fn set(&mut self, key: &str, value: Value) {
+        self.0.insert(key.to_string(), value);
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        self.0.remove(key)
+    }
+
+    /// Whether the history item is hidden from readers, stored under the key `is_secret`.
+    pub fn is_secret(&self) -> bool {
+        matches!(self.0.get("is_secret"), Some(Value::Bool(true)))
+    }
+
+    pub fn set_secret(&mut self, is_secret: bool) {
+        self.set("is_secret", Value::Bool(is_secret));
+    }
+
+    /// Who may see the history item, stored under the key `visibility`.
+    /// Without it, secret items are visible to game masters only and all others are public.
+    /// Unknown levels hide the item from everyone but the authors.
+    pub fn visibility(&self) -> Visibility {
+        match self.0.get("visibility") {
+            Some(Value::String(visibility)) => {
+                Visibility::try_from(visibility.as_str()).unwrap_or(Visibility::AuthorOnly)
+            }
+            _ if self.is_secret() => Visibility::GmOnly,
+            _ => Visibility::Public,
+        }
+    }
+
+    pub fn set_visibility(&mut self, visibility: Visibility) {
+        self.set("visibility", Value::String(visibility.to_string()));
+    }
+
+    /// Further texts the history item concerns, stored under the key `additional_concerns`.
+    pub fn additional_concerns(&self) -> Vec<String> {
+        match self.0.get("additional_concerns") {
+            Some(Value::Array(values)) => values
+                .iter()
+                .filter_map(|value| value.as_str())
+                .map(|concern| concern.to_string())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn set_additional_concerns(&mut self, concerns: Vec<String>) {
+        let concerns = concerns.into_iter().map(Value::String).collect();
+        self.set("additional_concerns", Value::Array(concerns));
+    }
+
+    /// The labels of the entities born in this history item, listed under the key `births`.
+    pub fn births(&self) -> Vec<Label> {
+        self.labels("births")
+    }
+
+    /// The labels of the entities that died in this history item, listed under the key `deaths`.
+    pub fn deaths(&self) -> Vec<Label> {
+        self.labels("deaths")
+    }
+
+    /// The labels of the entities founded in this history item, listed under the key `foundings`.
+    pub fn foundings(&self) -> Vec<Label> {
+        self.labels("foundings")
+    }
+
+    /// The labels of the entities dissolved in this history item,
+    /// listed under the key `dissolutions`.
+    pub fn dissolutions(&self) -> Vec<Label> {
+        self.labels("dissolutions")
+    }
+
+    pub(crate) fn labels(&self, key: &str) -> Vec<Label> {
+        match self.0.get(key) {
+            Some(Value::Array(values)) => values
+                .iter()
+                .filter_map(|value| value.as_str())
+                .map(|label| label.into())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
 }
 
 impl From<HashMap<String, Value>> for HistoryItemProperties {
@@ -21,18 +113,28 @@ impl From<HashMap<String, Value>> for HistoryItemProperties {
     }
 }
 
-impl From<&String> for HistoryItemProperties {
-    fn from(value: &String) -> Self {
-        Self::from(value.as_str())
+impl TryFrom<&String> for HistoryItemProperties {
+    type Error = LoreCoreError;
+
+    fn try_from(value: &String) -> Result<Self, Self::Error> {
+        Self::try_from(value.as_str())
     }
 }
 
-impl From<&str> for HistoryItemProperties {
-    fn from(value: &str) -> Self {
-        match serde_json::from_str(value) {
-            Ok(value) => Self(value),
-            Err(_) => Self::none(),
+/// Parses properties from a JSON object. The empty string stands for no properties.
+impl TryFrom<&str> for HistoryItemProperties {
+    type Error = LoreCoreError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        if value.is_empty() {
+            return Ok(Self::none());
         }
+        serde_json::from_str(value).map(Self).map_err(|e| {
+            LoreCoreError::InputError(format!(
+                "Unable to parse \"{}\" as history item properties: {}",
+                value, e
+            ))
+        })
     }
 }
 
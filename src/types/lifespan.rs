@@ -0,0 +1,130 @@
+use super::{day::Day, timestamp::Timestamp, year::Year};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LifeEventKind {
+    Birth,
+    Founding,
+    Death,
+    Dissolution,
+}
+
+impl LifeEventKind {
+    /// The key under which entities with this event are listed in `HistoryItemProperties`.
+    pub fn property_key(&self) -> &'static str {
+        match self {
+            LifeEventKind::Birth => "births",
+            LifeEventKind::Founding => "foundings",
+            LifeEventKind::Death => "deaths",
+            LifeEventKind::Dissolution => "dissolutions",
+        }
+    }
+
+    pub fn is_start(&self) -> bool {
+        matches!(self, LifeEventKind::Birth | LifeEventKind::Founding)
+    }
+}
+
+/// The beginning or end of an entity's existence, as recorded by a history item.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LifeEvent {
+    pub kind: LifeEventKind,
+    pub year: Year,
+    pub day: Day,
+    /// The timestamp of the history item recording the event.
+    pub timestamp: Timestamp,
+}
+
+/// When an entity came into and went out of existence, as far as it is known.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Lifespan {
+    pub start: Option<LifeEvent>,
+    pub end: Option<LifeEvent>,
+}
+
+impl Lifespan {
+    /// Whether the entity exists in `year`. Entities without a known start never exist,
+    /// entities without a known end exist forever. The years of both events are included.
+    pub fn exists_at(&self, year: Year) -> bool {
+        match self.start {
+            Some(start) => start.year <= year && self.end.is_none_or(|end| year <= end.year),
+            None => false,
+        }
+    }
+
+    /// The number of full years the entity has existed for on `day` of `year`, or `None` if it
+    /// does not exist then. If either day is unknown, only the years are compared.
+    pub fn age_at(&self, year: Year, day: Day) -> Option<u32> {
+        let start = self.start?;
+        if !self.exists_at(year) {
+            return None;
+        }
+        if let Some(end) = self.end {
+            if end.year == year && end.day.is_some() && day.is_some() && end.day < day {
+                return None;
+            }
+        }
+        let years = (year.to_int() as i64 - start.year.to_int() as i64) as u32;
+        if start.day.is_some() && day.is_some() && day < start.day {
+            if years == 0 {
+                return None;
+            }
+            return Some(years - 1);
+        }
+        Some(years)
+    }
+
+    /// The age at the end of the entity's existence, if both its start and end are known.
+    pub fn final_age(&self) -> Option<u32> {
+        let end = self.end?;
+        self.age_at(end.year, end.day)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(kind: LifeEventKind, year: i32, day: u32) -> Option<LifeEvent> {
+        Some(LifeEvent {
+            kind,
+            year: year.into(),
+            day: day.into(),
+            timestamp: 0.into(),
+        })
+    }
+
+    #[test]
+    fn test_age() {
+        let lifespan = Lifespan {
+            start: event(LifeEventKind::Birth, 900, 100),
+            end: event(LifeEventKind::Death, 950, 50),
+        };
+        assert_eq!(lifespan.age_at(899.into(), Day::NONE), None);
+        assert_eq!(lifespan.age_at(900.into(), 99.into()), None);
+        assert_eq!(lifespan.age_at(900.into(), 100.into()), Some(0));
+        assert_eq!(lifespan.age_at(910.into(), 99.into()), Some(9));
+        assert_eq!(lifespan.age_at(910.into(), 100.into()), Some(10));
+        assert_eq!(lifespan.age_at(910.into(), Day::NONE), Some(10));
+        assert_eq!(lifespan.age_at(950.into(), 51.into()), None);
+        assert_eq!(lifespan.final_age(), Some(49));
+    }
+
+    #[test]
+    fn test_existence() {
+        let founded = Lifespan {
+            start: event(LifeEventKind::Founding, 10, 0),
+            end: None,
+        };
+        assert!(!founded.exists_at(9.into()));
+        assert!(founded.exists_at(10.into()));
+        assert!(founded.exists_at(10000.into()));
+        assert_eq!(founded.final_age(), None);
+
+        let unknown = Lifespan {
+            start: None,
+            end: event(LifeEventKind::Dissolution, 10, 0),
+        };
+        assert!(!unknown.exists_at(5.into()));
+        assert_eq!(unknown.age_at(5.into(), Day::NONE), None);
+    }
+}
@@ -1,29 +1,51 @@
 pub mod child;
+pub mod date_precision;
 pub mod day;
 pub mod description;
 pub mod descriptor;
 pub mod entity;
 pub mod history;
+pub mod history_anchor;
 pub mod history_item_content;
 pub mod history_item_properties;
 pub mod label;
+pub mod lifespan;
 pub mod parent;
+pub mod property_schema;
+pub mod recurrence;
 pub mod relationship;
 pub mod role;
+pub mod role_constraint;
+pub mod role_declaration;
+pub mod role_strictness;
+pub mod tag;
+pub mod timed_entity;
 pub mod timestamp;
+pub mod visibility;
 pub mod year;
 
 pub use child::Child;
+pub use date_precision::{DatePrecision, Markup};
 pub use day::Day;
 pub use description::Description;
 pub use descriptor::Descriptor;
-pub use entity::EntityColumn;
+pub use entity::{Entity, EntityColumn};
 pub use history::HistoryItem;
+pub use history_anchor::HistoryAnchor;
 pub use history_item_content::HistoryItemContent;
 pub use history_item_properties::HistoryItemProperties;
 pub use label::Label;
+pub use lifespan::{LifeEvent, LifeEventKind, Lifespan};
 pub use parent::Parent;
+pub use property_schema::{PropertyDeclaration, PropertyType};
+pub use recurrence::Recurrence;
 pub use relationship::EntityRelationship;
 pub use role::Role;
+pub use role_constraint::{ConstraintViolation, RoleConstraint, RoleRule};
+pub use role_declaration::RoleDeclaration;
+pub use role_strictness::RoleStrictness;
+pub use tag::Tag;
+pub use timed_entity::TimedEntityColumn;
 pub use timestamp::Timestamp;
+pub use visibility::Visibility;
 pub use year::Year;
@@ -0,0 +1,183 @@
+use serde_json::Value;
+use std::fmt::Display;
+
+use crate::errors::LoreCoreError;
+
+use super::visibility::Visibility;
+
+use super::history_item_properties::HistoryItemProperties;
+
+/// The type of values a key of `HistoryItemProperties` may have.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PropertyType {
+    Boolean,
+    Integer,
+    Number,
+    Text,
+    TextList,
+}
+
+impl PropertyType {
+    pub fn matches(&self, value: &Value) -> bool {
+        match self {
+            PropertyType::Boolean => value.is_boolean(),
+            PropertyType::Integer => value.is_i64() || value.is_u64(),
+            PropertyType::Number => value.is_number(),
+            PropertyType::Text => value.is_string(),
+            PropertyType::TextList => value
+                .as_array()
+                .is_some_and(|values| values.iter().all(|value| value.is_string())),
+        }
+    }
+}
+
+impl TryFrom<&str> for PropertyType {
+    type Error = LoreCoreError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "boolean" => Ok(Self::Boolean),
+            "integer" => Ok(Self::Integer),
+            "number" => Ok(Self::Number),
+            "text" => Ok(Self::Text),
+            "text_list" => Ok(Self::TextList),
+            _ => Err(LoreCoreError::InputError(format!(
+                "Unable to parse \"{}\" as property type",
+                value
+            ))),
+        }
+    }
+}
+
+impl Display for PropertyType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Boolean => "boolean".fmt(f),
+            Self::Integer => "integer".fmt(f),
+            Self::Number => "number".fmt(f),
+            Self::Text => "text".fmt(f),
+            Self::TextList => "text_list".fmt(f),
+        }
+    }
+}
+
+/// Declares a user-defined key of `HistoryItemProperties` together with the type of its values.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PropertyDeclaration {
+    pub key: String,
+    pub value_type: PropertyType,
+}
+
+impl PropertyDeclaration {
+    pub fn new(key: &str, value_type: PropertyType) -> Self {
+        Self {
+            key: key.to_string(),
+            value_type,
+        }
+    }
+}
+
+/// The keys known to lorecore, which need no declaration.
+pub const BUILT_IN_PROPERTIES: [(&str, PropertyType); 7] = [
+    ("is_secret", PropertyType::Boolean),
+    ("visibility", PropertyType::Text),
+    ("additional_concerns", PropertyType::TextList),
+    ("births", PropertyType::TextList),
+    ("deaths", PropertyType::TextList),
+    ("foundings", PropertyType::TextList),
+    ("dissolutions", PropertyType::TextList),
+];
+
+/// The type of `key`, if it is built in or declared.
+pub fn property_type(key: &str, declarations: &[PropertyDeclaration]) -> Option<PropertyType> {
+    BUILT_IN_PROPERTIES
+        .iter()
+        .find(|(built_in, _)| *built_in == key)
+        .map(|(_, value_type)| *value_type)
+        .or_else(|| {
+            declarations
+                .iter()
+                .find(|declaration| declaration.key == key)
+                .map(|declaration| declaration.value_type)
+        })
+}
+
+/// Checks that every key of `properties` is built in or declared, with a value of its type.
+pub fn validate_properties(
+    properties: &HistoryItemProperties,
+    declarations: &[PropertyDeclaration],
+) -> Result<(), LoreCoreError> {
+    let mut keys: Vec<_> = properties.to_map().keys().collect();
+    keys.sort();
+    for key in keys {
+        let value = &properties.to_map()[key];
+        match property_type(key, declarations) {
+            Some(_) if key == "visibility" => {
+                Visibility::try_from(value.as_str().unwrap_or_default())?;
+            }
+            Some(value_type) if value_type.matches(value) => {}
+            Some(value_type) => {
+                return Err(LoreCoreError::InputError(format!(
+                    "The property \"{}\" has to be of type {}, but is {}.",
+                    key, value_type, value
+                )))
+            }
+            None => {
+                return Err(LoreCoreError::InputError(format!(
+                    "The property \"{}\" is not declared.",
+                    key
+                )))
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn properties(json: &str) -> HistoryItemProperties {
+        HistoryItemProperties::try_from(json).unwrap()
+    }
+
+    #[test]
+    fn test_validate_properties() {
+        let declarations = vec![PropertyDeclaration::new(
+            "casualties",
+            PropertyType::Integer,
+        )];
+        assert!(validate_properties(&properties("{}"), &declarations).is_ok());
+        assert!(validate_properties(
+            &properties("{\"is_secret\":true,\"casualties\":300}"),
+            &declarations
+        )
+        .is_ok());
+        assert!(
+            validate_properties(&properties("{\"is_secret\":\"yes\"}"), &declarations).is_err()
+        );
+        assert!(validate_properties(&properties("{\"casualties\":1.5}"), &declarations).is_err());
+        assert!(validate_properties(&properties("{\"births\":[1]}"), &declarations).is_err());
+        assert!(
+            validate_properties(&properties("{\"visibility\":\"gm_only\"}"), &declarations).is_ok()
+        );
+        assert!(
+            validate_properties(&properties("{\"visibility\":\"hidden\"}"), &declarations).is_err()
+        );
+        assert!(validate_properties(&properties("{\"weather\":\"rain\"}"), &declarations).is_err());
+    }
+
+    #[test]
+    fn test_property_types_roundtrip() {
+        for value_type in [
+            PropertyType::Boolean,
+            PropertyType::Integer,
+            PropertyType::Number,
+            PropertyType::Text,
+            PropertyType::TextList,
+        ] {
+            let text = value_type.to_string();
+            assert_eq!(PropertyType::try_from(text.as_str()).unwrap(), value_type);
+        }
+    }
+}
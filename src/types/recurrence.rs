@@ -0,0 +1,127 @@
+use std::fmt::Display;
+
+use super::{history::HistoryItem, year::Year};
+
+/// Repeats a history item every `every_years` years after its own year, up to and including
+/// the year `until` if given. A recurrence every year repeats the item on the same day each year.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Recurrence {
+    pub every_years: u32,
+    pub until: Option<Year>,
+}
+
+impl Recurrence {
+    pub fn new(every_years: u32, until: Option<Year>) -> Self {
+        Self { every_years, until }
+    }
+
+    /// The repetitions of `item` between the years `from` and `to`, both included.
+    /// The item itself is not repeated. The repetitions are marked with `is_occurrence`.
+    pub fn occurrences(&self, item: &HistoryItem, from: Year, to: Year) -> Vec<HistoryItem> {
+        let (first_step, last_step) = self.steps(item, from, to);
+        let every = self.every_years.max(1) as i64;
+        let base = item.year.to_int() as i64;
+        (first_step..=last_step)
+            .map(|step| {
+                let mut occurrence = item.clone();
+                occurrence.year = ((base + step * every) as i32).into();
+                occurrence.is_occurrence = true;
+                occurrence
+            })
+            .collect()
+    }
+
+    /// The number of [`Recurrence::occurrences`], without creating them.
+    pub fn count_occurrences(&self, item: &HistoryItem, from: Year, to: Year) -> u64 {
+        let (first_step, last_step) = self.steps(item, from, to);
+        (last_step - first_step + 1).max(0) as u64
+    }
+
+    /// The first and last multiple of `every_years` after the year of `item` that lie within
+    /// the period.
+    fn steps(&self, item: &HistoryItem, from: Year, to: Year) -> (i64, i64) {
+        let every = self.every_years.max(1) as i64;
+        let base = item.year.to_int() as i64;
+        let last = match self.until {
+            Some(until) => until.min(to).to_int() as i64,
+            None => to.to_int() as i64,
+        };
+        let first_step = ((from.to_int() as i64 - base + every - 1).div_euclid(every)).max(1);
+        let last_step = (last - base).div_euclid(every);
+        (first_step, last_step)
+    }
+}
+
+impl Display for Recurrence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.every_years {
+            0 | 1 => write!(f, "every year")?,
+            n => write!(f, "every {} years", n)?,
+        }
+        if let Some(until) = self.until {
+            write!(f, " until {}", until)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn years(recurrence: Recurrence, from: i32, to: i32) -> Vec<i32> {
+        let item = HistoryItem::new(1.into(), 300.into(), 12.into(), "Festival".into());
+        recurrence
+            .occurrences(&item, from.into(), to.into())
+            .iter()
+            .map(|occurrence| occurrence.year.to_int())
+            .collect()
+    }
+
+    #[test]
+    fn test_occurrences() {
+        assert_eq!(
+            years(Recurrence::new(7, None), 300, 330),
+            vec![307, 314, 321, 328]
+        );
+        assert_eq!(years(Recurrence::new(7, None), 310, 321), vec![314, 321]);
+        assert_eq!(
+            years(Recurrence::new(1, Some(303.into())), 0, 1000),
+            vec![301, 302, 303]
+        );
+        assert_eq!(years(Recurrence::new(5, None), 0, 299), Vec::<i32>::new());
+        assert_eq!(years(Recurrence::new(0, None), 299, 301), vec![301]);
+        assert_eq!(years(Recurrence::new(7, None), 330, 300), Vec::<i32>::new());
+
+        let item = HistoryItem::new(1.into(), 300.into(), 12.into(), "Festival".into());
+        assert!(Recurrence::new(1, None)
+            .occurrences(&item, 0.into(), 302.into())
+            .iter()
+            .all(|occurrence| occurrence.is_occurrence));
+    }
+
+    #[test]
+    fn test_count_occurrences() {
+        let item = HistoryItem::new(1.into(), 300.into(), 12.into(), "Festival".into());
+        let count = |recurrence: Recurrence, from: i32, to: i32| {
+            recurrence.count_occurrences(&item, from.into(), to.into())
+        };
+        assert_eq!(count(Recurrence::new(7, None), 300, 330), 4);
+        assert_eq!(count(Recurrence::new(1, Some(303.into())), 0, 1000), 3);
+        assert_eq!(count(Recurrence::new(5, None), 0, 299), 0);
+        assert_eq!(count(Recurrence::new(7, None), 330, 300), 0);
+        assert_eq!(
+            count(Recurrence::new(1, None), i32::MIN, i32::MAX),
+            (i32::MAX - 300) as u64
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Recurrence::new(1, None).to_string(), "every year");
+        assert_eq!(
+            Recurrence::new(7, Some(400.into())).to_string(),
+            "every 7 years until 400"
+        );
+    }
+}
@@ -1,8 +1,112 @@
-use super::{child::Child, parent::Parent, role::Role};
+use super::{child::Child, day::Day, parent::Parent, role::Role, year::Year};
 
+/// A directed relationship from `parent` to `child`.
+///
+/// `start` and `end` optionally bound the time during which the relationship holds.
+/// Both bounds are inclusive, and a missing bound means the relationship has always held
+/// or still holds, respectively.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct EntityRelationship {
     pub parent: Parent,
     pub child: Child,
     pub role: Role,
+    pub start: Option<(Year, Day)>,
+    pub end: Option<(Year, Day)>,
+}
+
+impl EntityRelationship {
+    /// A relationship that holds at all times.
+    pub fn new(parent: Parent, child: Child, role: Role) -> Self {
+        Self {
+            parent,
+            child,
+            role,
+            start: None,
+            end: None,
+        }
+    }
+
+    pub fn valid_between(self, start: Option<(Year, Day)>, end: Option<(Year, Day)>) -> Self {
+        Self { start, end, ..self }
+    }
+
+    pub fn is_valid_at(&self, year: Year) -> bool {
+        let started = self.start.is_none_or(|(start, _)| start <= year);
+        let not_ended = self.end.is_none_or(|(end, _)| year <= end);
+        started && not_ended
+    }
+
+    /// Whether the relationship ends before it starts. Days are only compared if both are
+    /// known.
+    pub fn ends_before_it_starts(&self) -> bool {
+        match (self.start, self.end) {
+            (Some((start_year, start_day)), Some((end_year, end_day))) => {
+                end_year < start_year
+                    || (end_year == start_year
+                        && start_day != Day::NONE
+                        && end_day != Day::NONE
+                        && end_day < start_day)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether there is a year in which both relationships are valid.
+    pub fn overlaps(&self, other: &EntityRelationship) -> bool {
+        let starts_before_other_ends = match (self.start, other.end) {
+            (Some((start, _)), Some((end, _))) => start <= end,
+            _ => true,
+        };
+        let ends_after_other_starts = match (self.end, other.start) {
+            (Some((end, _)), Some((start, _))) => start <= end,
+            _ => true,
+        };
+        starts_before_other_ends && ends_after_other_starts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rel(start: Option<i32>, end: Option<i32>) -> EntityRelationship {
+        EntityRelationship::new("a".into(), "b".into(), Role::NONE).valid_between(
+            start.map(|y| (y.into(), Day::NONE)),
+            end.map(|y| (y.into(), Day::NONE)),
+        )
+    }
+
+    #[test]
+    fn test_is_valid_at() {
+        assert!(rel(None, None).is_valid_at((-1000).into()));
+        assert!(rel(Some(10), Some(20)).is_valid_at(10.into()));
+        assert!(rel(Some(10), Some(20)).is_valid_at(20.into()));
+        assert!(!rel(Some(10), Some(20)).is_valid_at(21.into()));
+        assert!(!rel(Some(10), None).is_valid_at(9.into()));
+        assert!(rel(None, Some(20)).is_valid_at(9.into()));
+    }
+
+    #[test]
+    fn test_ends_before_it_starts() {
+        let dated = |start: (i32, u32), end: (i32, Day)| {
+            EntityRelationship::new("a".into(), "b".into(), Role::NONE).valid_between(
+                Some((start.0.into(), start.1.into())),
+                Some((end.0.into(), end.1)),
+            )
+        };
+        assert!(rel(Some(20), Some(10)).ends_before_it_starts());
+        assert!(!rel(Some(10), Some(10)).ends_before_it_starts());
+        assert!(!rel(Some(20), None).ends_before_it_starts());
+        assert!(dated((10, 5), (10, 4.into())).ends_before_it_starts());
+        assert!(!dated((10, 5), (10, 5.into())).ends_before_it_starts());
+        assert!(!dated((10, 5), (10, Day::NONE)).ends_before_it_starts());
+    }
+
+    #[test]
+    fn test_overlaps() {
+        assert!(rel(Some(10), Some(20)).overlaps(&rel(Some(20), None)));
+        assert!(!rel(Some(10), Some(20)).overlaps(&rel(Some(21), None)));
+        assert!(!rel(None, Some(9)).overlaps(&rel(Some(10), Some(20))));
+        assert!(rel(None, None).overlaps(&rel(Some(10), Some(20))));
+    }
 }
@@ -0,0 +1,165 @@
+use std::fmt::Display;
+
+use super::{
+    child::Child, description::Description, descriptor::Descriptor, parent::Parent,
+    relationship::EntityRelationship, role::Role,
+};
+
+/// A rule that all relationships with a given role have to obey.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RoleRule {
+    /// A parent may have at most this many relationships with the role.
+    MaxPerParent(u32),
+    /// A child may have at most this many relationships with the role.
+    MaxPerChild(u32),
+    /// The parent needs a column with this descriptor and description.
+    /// If several of these rules exist for the same role, the parent has to satisfy one of them.
+    ParentKind(Descriptor, Description),
+    /// The child needs a column with this descriptor and description.
+    /// If several of these rules exist for the same role, the child has to satisfy one of them.
+    ChildKind(Descriptor, Description),
+}
+
+/// Constrains the relationships with a role. For example, "a person has at most 2 parents" is
+/// `RoleConstraint::max_per_child("parent".into(), 2)`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RoleConstraint {
+    pub role: Role,
+    pub rule: RoleRule,
+}
+
+impl RoleConstraint {
+    pub fn max_per_parent(role: Role, max: u32) -> Self {
+        Self {
+            role,
+            rule: RoleRule::MaxPerParent(max),
+        }
+    }
+
+    pub fn max_per_child(role: Role, max: u32) -> Self {
+        Self {
+            role,
+            rule: RoleRule::MaxPerChild(max),
+        }
+    }
+
+    /// Every parent and every child has at most one relationship with the role.
+    pub fn one_to_one(role: Role) -> Vec<Self> {
+        vec![
+            Self::max_per_parent(role.clone(), 1),
+            Self::max_per_child(role, 1),
+        ]
+    }
+
+    pub fn parent_kind(role: Role, descriptor: Descriptor, description: Description) -> Self {
+        Self {
+            role,
+            rule: RoleRule::ParentKind(descriptor, description),
+        }
+    }
+
+    pub fn child_kind(role: Role, descriptor: Descriptor, description: Description) -> Self {
+        Self {
+            role,
+            rule: RoleRule::ChildKind(descriptor, description),
+        }
+    }
+}
+
+/// A relationship, or a group of relationships, breaking a role constraint.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConstraintViolation {
+    TooManyPerParent {
+        parent: Parent,
+        role: Role,
+        count: usize,
+        max: u32,
+    },
+    TooManyPerChild {
+        child: Child,
+        role: Role,
+        count: usize,
+        max: u32,
+    },
+    WrongParentKind {
+        relationship: EntityRelationship,
+        allowed: Vec<(Descriptor, Description)>,
+    },
+    WrongChildKind {
+        relationship: EntityRelationship,
+        allowed: Vec<(Descriptor, Description)>,
+    },
+}
+
+impl ConstraintViolation {
+    /// Whether `rel` is one of the relationships breaking the constraint.
+    pub fn concerns(&self, rel: &EntityRelationship) -> bool {
+        match self {
+            ConstraintViolation::TooManyPerParent { parent, role, .. } => {
+                &rel.parent == parent && &rel.role == role
+            }
+            ConstraintViolation::TooManyPerChild { child, role, .. } => {
+                &rel.child == child && &rel.role == role
+            }
+            ConstraintViolation::WrongParentKind { relationship, .. }
+            | ConstraintViolation::WrongChildKind { relationship, .. } => relationship == rel,
+        }
+    }
+}
+
+fn kinds_to_string(kinds: &[(Descriptor, Description)]) -> String {
+    let kinds: Vec<_> = kinds
+        .iter()
+        .map(|(descriptor, description)| format!("\"{}\" \"{}\"", descriptor, description))
+        .collect();
+    kinds.join(" or ")
+}
+
+impl Display for ConstraintViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConstraintViolation::TooManyPerParent {
+                parent,
+                role,
+                count,
+                max,
+            } => write!(
+                f,
+                "\"{}\" is the parent of {} relationships with role \"{}\" at the same time, but at most {} are allowed.",
+                parent, count, role, max
+            ),
+            ConstraintViolation::TooManyPerChild {
+                child,
+                role,
+                count,
+                max,
+            } => write!(
+                f,
+                "\"{}\" is the child of {} relationships with role \"{}\" at the same time, but at most {} are allowed.",
+                child, count, role, max
+            ),
+            ConstraintViolation::WrongParentKind {
+                relationship,
+                allowed,
+            } => write!(
+                f,
+                "The relationship with role \"{}\" from \"{}\" to \"{}\" requires the parent to have {}.",
+                relationship.role,
+                relationship.parent,
+                relationship.child,
+                kinds_to_string(allowed)
+            ),
+            ConstraintViolation::WrongChildKind {
+                relationship,
+                allowed,
+            } => write!(
+                f,
+                "The relationship with role \"{}\" from \"{}\" to \"{}\" requires the child to have {}.",
+                relationship.role,
+                relationship.parent,
+                relationship.child,
+                kinds_to_string(allowed)
+            ),
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use super::role::Role;
+
+/// Declares a role in the role registry.
+///
+/// A role may have an inverse, which is the role of the same relationship read from the child's
+/// point of view. For example, the inverse of "liege" is "vassal". A symmetric role such as
+/// "sibling" is its own inverse.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RoleDeclaration {
+    pub role: Role,
+    pub inverse: Option<Role>,
+}
+
+impl RoleDeclaration {
+    pub fn plain(role: Role) -> Self {
+        Self {
+            role,
+            inverse: None,
+        }
+    }
+
+    pub fn symmetric(role: Role) -> Self {
+        Self {
+            inverse: Some(role.clone()),
+            role,
+        }
+    }
+
+    pub fn with_inverse(role: Role, inverse: Role) -> Self {
+        Self {
+            role,
+            inverse: Some(inverse),
+        }
+    }
+
+    pub fn is_symmetric(&self) -> bool {
+        self.inverse.as_ref() == Some(&self.role)
+    }
+}
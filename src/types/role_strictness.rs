@@ -0,0 +1,42 @@
+use std::fmt::Display;
+
+use crate::errors::LoreCoreError;
+
+/// Determines how writing relationships with roles missing from the role registry is handled.
+/// Relationships without a role are always accepted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum RoleStrictness {
+    /// Undeclared roles are accepted silently.
+    #[default]
+    Lenient,
+    /// Undeclared roles are accepted, but reported by `LoreDatabase::find_undeclared_roles`.
+    Warn,
+    /// Undeclared roles are rejected with an error.
+    Strict,
+}
+
+impl TryFrom<&str> for RoleStrictness {
+    type Error = LoreCoreError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "lenient" => Ok(Self::Lenient),
+            "warn" => Ok(Self::Warn),
+            "strict" => Ok(Self::Strict),
+            _ => Err(LoreCoreError::InputError(format!(
+                "Unable to parse \"{}\" as role strictness",
+                value
+            ))),
+        }
+    }
+}
+
+impl Display for RoleStrictness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Lenient => "lenient".fmt(f),
+            Self::Warn => "warn".fmt(f),
+            Self::Strict => "strict".fmt(f),
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use std::fmt::Display;
+
+use crate::errors::LoreCoreError;
+
+/// A free-form tag, optionally nested below other tags with slashes, like "war/naval".
+/// Segments are trimmed and may not be empty.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Tag(String);
+
+const SEPARATOR: char = '/';
+
+impl Tag {
+    pub fn to_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The tag this one is nested below, such as "war" for "war/naval".
+    pub fn parent(&self) -> Option<Tag> {
+        self.0
+            .rsplit_once(SEPARATOR)
+            .map(|(parent, _)| Tag(parent.to_string()))
+    }
+
+    /// Whether this tag is `other` or nested below it at any depth.
+    pub fn is_within(&self, other: &Tag) -> bool {
+        self == other
+            || self
+                .0
+                .strip_prefix(&other.0)
+                .is_some_and(|rest| rest.starts_with(SEPARATOR))
+    }
+
+    /// Replaces `old` by `new` if this tag is within `old`, keeping the nested part.
+    pub fn moved(&self, old: &Tag, new: &Tag) -> Option<Tag> {
+        self.is_within(old)
+            .then(|| Tag(new.0.clone() + &self.0[old.0.len()..]))
+    }
+}
+
+impl TryFrom<&str> for Tag {
+    type Error = LoreCoreError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let segments: Vec<_> = value.split(SEPARATOR).map(|s| s.trim()).collect();
+        if segments.iter().any(|segment| segment.is_empty()) {
+            return Err(LoreCoreError::InputError(format!(
+                "Unable to parse \"{}\" as tag",
+                value
+            )));
+        }
+        Ok(Self(segments.join("/")))
+    }
+}
+
+impl TryFrom<String> for Tag {
+    type Error = LoreCoreError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::try_from(value.as_str())
+    }
+}
+
+impl Display for Tag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(text: &str) -> Tag {
+        Tag::try_from(text).unwrap()
+    }
+
+    #[test]
+    fn test_parsing_tags() {
+        assert_eq!(tag(" war / naval ").to_str(), "war/naval");
+        assert!(Tag::try_from("").is_err());
+        assert!(Tag::try_from("war/").is_err());
+        assert!(Tag::try_from("war//naval").is_err());
+    }
+
+    #[test]
+    fn test_tag_hierarchy() {
+        assert_eq!(tag("war/naval/boarding").parent(), Some(tag("war/naval")));
+        assert_eq!(tag("war").parent(), None);
+        assert!(tag("war/naval").is_within(&tag("war")));
+        assert!(tag("war").is_within(&tag("war")));
+        assert!(!tag("warfare").is_within(&tag("war")));
+        assert!(!tag("war").is_within(&tag("war/naval")));
+        assert_eq!(
+            tag("war/naval").moved(&tag("war"), &tag("conflict")),
+            Some(tag("conflict/naval"))
+        );
+        assert_eq!(tag("warfare").moved(&tag("war"), &tag("conflict")), None);
+    }
+}
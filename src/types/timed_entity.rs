@@ -0,0 +1,77 @@
+use super::{
+    description::Description, descriptor::Descriptor, entity::EntityColumn, label::Label,
+    year::Year,
+};
+
+/// A description that only holds from the year `start` until the year `end`, both included.
+/// Missing bounds mean that the description holds since or until forever.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TimedEntityColumn {
+    pub label: Label,
+    pub descriptor: Descriptor,
+    pub description: Description,
+    pub start: Option<Year>,
+    pub end: Option<Year>,
+}
+
+impl TimedEntityColumn {
+    pub fn is_valid_at(&self, year: Year) -> bool {
+        self.start.is_none_or(|start| start <= year) && self.end.is_none_or(|end| year <= end)
+    }
+
+    /// Whether both columns describe the same descriptor of the same entity in a common year.
+    pub fn overlaps(&self, other: &TimedEntityColumn) -> bool {
+        let starts_before_other_ends = match (self.start, other.end) {
+            (Some(start), Some(end)) => start <= end,
+            _ => true,
+        };
+        let ends_after_other_starts = match (self.end, other.start) {
+            (Some(end), Some(start)) => start <= end,
+            _ => true,
+        };
+        self.label == other.label
+            && self.descriptor == other.descriptor
+            && starts_before_other_ends
+            && ends_after_other_starts
+    }
+
+    pub fn to_entity_column(&self) -> EntityColumn {
+        EntityColumn {
+            label: self.label.clone(),
+            descriptor: self.descriptor.clone(),
+            description: self.description.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn col(descriptor: &str, start: Option<i32>, end: Option<i32>) -> TimedEntityColumn {
+        TimedEntityColumn {
+            label: "minas_tirith".into(),
+            descriptor: descriptor.into(),
+            description: "someone".into(),
+            start: start.map(|year| year.into()),
+            end: end.map(|year| year.into()),
+        }
+    }
+
+    #[test]
+    fn test_overlaps() {
+        assert!(col("ruler", Some(10), Some(20)).overlaps(&col("ruler", Some(20), None)));
+        assert!(!col("ruler", Some(10), Some(20)).overlaps(&col("ruler", Some(21), None)));
+        assert!(!col("ruler", None, Some(9)).overlaps(&col("ruler", Some(10), Some(20))));
+        assert!(col("ruler", None, None).overlaps(&col("ruler", Some(10), Some(20))));
+        assert!(!col("ruler", None, None).overlaps(&col("steward", None, None)));
+    }
+
+    #[test]
+    fn test_validity() {
+        assert!(col("ruler", Some(10), Some(20)).is_valid_at(10.into()));
+        assert!(col("ruler", Some(10), Some(20)).is_valid_at(20.into()));
+        assert!(!col("ruler", Some(10), Some(20)).is_valid_at(21.into()));
+        assert!(col("ruler", None, None).is_valid_at((-5000).into()));
+    }
+}
@@ -0,0 +1,109 @@
+use std::fmt::Display;
+
+use crate::errors::LoreCoreError;
+
+/// Who may see a piece of the world. Every level includes the ones before it, so an audience
+/// given as a visibility sees everything up to and including that level.
+///
+/// Every read of entities, relationships and history items, and everything derived from them,
+/// takes an audience, either directly or via `for_audience` on its search parameters.
+/// Calendars, eras, declarations of roles and properties, anchors, recurrences and the checks
+/// of references, roles, constraints and calendar days are tools for authors and see everything.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Visibility {
+    #[default]
+    Public,
+    /// Known to the players, but not to the public.
+    PlayerKnown,
+    /// Known to the game masters only.
+    GmOnly,
+    /// Notes of the authors, hidden from everyone else.
+    AuthorOnly,
+}
+
+impl Visibility {
+    pub fn is_visible_to(self, audience: Visibility) -> bool {
+        self <= audience
+    }
+
+    pub(crate) fn to_int(self) -> i32 {
+        match self {
+            Self::Public => 0,
+            Self::PlayerKnown => 1,
+            Self::GmOnly => 2,
+            Self::AuthorOnly => 3,
+        }
+    }
+
+    pub(crate) fn from_int(value: i32) -> Result<Self, LoreCoreError> {
+        match value {
+            0 => Ok(Self::Public),
+            1 => Ok(Self::PlayerKnown),
+            2 => Ok(Self::GmOnly),
+            3 => Ok(Self::AuthorOnly),
+            _ => Err(LoreCoreError::InputError(format!(
+                "Unable to parse {} as visibility",
+                value
+            ))),
+        }
+    }
+}
+
+impl TryFrom<&str> for Visibility {
+    type Error = LoreCoreError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "public" => Ok(Self::Public),
+            "player_known" => Ok(Self::PlayerKnown),
+            "gm_only" => Ok(Self::GmOnly),
+            "author_only" => Ok(Self::AuthorOnly),
+            _ => Err(LoreCoreError::InputError(format!(
+                "Unable to parse \"{}\" as visibility",
+                value
+            ))),
+        }
+    }
+}
+
+impl Display for Visibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Public => "public".fmt(f),
+            Self::PlayerKnown => "player_known".fmt(f),
+            Self::GmOnly => "gm_only".fmt(f),
+            Self::AuthorOnly => "author_only".fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visibility_roundtrips() {
+        for visibility in [
+            Visibility::Public,
+            Visibility::PlayerKnown,
+            Visibility::GmOnly,
+            Visibility::AuthorOnly,
+        ] {
+            let text = visibility.to_string();
+            assert_eq!(Visibility::try_from(text.as_str()).unwrap(), visibility);
+            assert_eq!(
+                Visibility::from_int(visibility.to_int()).unwrap(),
+                visibility
+            );
+        }
+        assert!(Visibility::try_from("secret").is_err());
+    }
+
+    #[test]
+    fn test_audiences_see_lower_levels() {
+        assert!(Visibility::Public.is_visible_to(Visibility::PlayerKnown));
+        assert!(Visibility::GmOnly.is_visible_to(Visibility::GmOnly));
+        assert!(!Visibility::GmOnly.is_visible_to(Visibility::PlayerKnown));
+        assert!(!Visibility::AuthorOnly.is_visible_to(Visibility::GmOnly));
+    }
+}
@@ -0,0 +1,78 @@
+use lorecore::{sql::lore_database::LoreDatabase, types::*};
+use std::path::PathBuf;
+use tempfile::NamedTempFile;
+
+mod common;
+use common::*;
+
+fn labels(labels: &[&str]) -> Vec<Label> {
+    labels.iter().map(|label| (*label).into()).collect()
+}
+
+fn create_example() -> (tempfile::TempPath, LoreDatabase) {
+    let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+    let db = LoreDatabase::open(path_in.clone()).unwrap();
+
+    db.write_entity_columns(vec![
+        col("Gondor", "type", "realm"),
+        col("Minas_Tirith", "type", "city"),
+        col("Denethor", "type", "person"),
+        col("Boromir", "type", "person"),
+        col("Tom_Bombadil", "type", "person"),
+        col("Goldberry", "spouse", "\\entityref{Tom_Bombadil}"),
+        col("Shire", "type", "region"),
+        col("Bree", "type", "town"),
+    ])
+    .unwrap();
+    db.write_relationships(vec![
+        rel("Gondor", "Minas_Tirith", "capital"),
+        rel("Denethor", "Gondor", "ruler"),
+        rel("Denethor", "Boromir", "parent"),
+        rel("Shire", "Bree", "neighbour"),
+    ])
+    .unwrap();
+    db.write_history_items(vec![HistoryItem::new(
+        1.into(),
+        1418.into(),
+        Day::NONE,
+        "The hobbits reach \\entityref{Shire}.".into(),
+    )])
+    .unwrap();
+    (temp_path, db)
+}
+
+#[test]
+fn graph_report() {
+    let (temp_path, db) = create_example();
+
+    let report = db.read_graph_report(2, Visibility::AuthorOnly).unwrap();
+    assert_eq!(
+        report.components,
+        vec![
+            labels(&["Boromir", "Denethor", "Gondor", "Minas_Tirith"]),
+            labels(&["Bree", "Shire"]),
+            labels(&["Goldberry"]),
+            labels(&["Tom_Bombadil"]),
+        ]
+    );
+    assert_eq!(
+        report.isolated_entities,
+        labels(&["Goldberry", "Tom_Bombadil"])
+    );
+    let hubs: Vec<_> = report
+        .hubs
+        .iter()
+        .map(|hub| (hub.label.clone(), hub.degree, hub.betweenness))
+        .collect();
+    assert_eq!(
+        hubs,
+        vec![("Denethor".into(), 2, 2.0), ("Gondor".into(), 2, 2.0),]
+    );
+    assert_eq!(
+        report.unreferenced_entities,
+        labels(&["Denethor", "Goldberry"])
+    );
+
+    temp_path.close().unwrap();
+}
@@ -0,0 +1,188 @@
+use lorecore::{
+    calendar::{Calendar, Month},
+    sql::{
+        anchor::AnchorProblem, lore_database::LoreDatabase, search_params::HistoryItemSearchParams,
+    },
+    types::*,
+};
+use std::path::PathBuf;
+use tempfile::NamedTempFile;
+
+mod common;
+use common::*;
+
+fn create_example() -> (tempfile::TempPath, LoreDatabase) {
+    let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+    let db = LoreDatabase::open(path_in.clone()).unwrap();
+
+    db.write_history_items(vec![
+        item(1, 1032, 10, "Something happened."),
+        item(2, 0, 0, "Something happened."),
+        item(3, 0, 0, "Something happened."),
+        item(4, 900, 0, "Something happened."),
+    ])
+    .unwrap();
+    db.write_history_anchor(2.into(), HistoryAnchor::new(1.into(), 0, 3))
+        .unwrap();
+    db.write_history_anchor(3.into(), HistoryAnchor::new(2.into(), 1, 0))
+        .unwrap();
+    (temp_path, db)
+}
+
+fn date(db: &LoreDatabase, timestamp: i64) -> (Year, Day) {
+    let item = db
+        .read_history_items(HistoryItemSearchParams::new(
+            None,
+            None,
+            Some(timestamp.into()),
+            None,
+        ))
+        .unwrap()
+        .pop()
+        .unwrap();
+    (item.year, item.day)
+}
+
+#[test]
+fn anchored_items_get_derived_dates() {
+    let (temp_path, db) = create_example();
+
+    assert_eq!(date(&db, 2), (1032.into(), 13.into()));
+    assert_eq!(date(&db, 3), (1033.into(), 13.into()));
+    assert_eq!(
+        db.read_history_anchor(3.into()).unwrap(),
+        Some(HistoryAnchor::new(2.into(), 1, 0))
+    );
+    assert_eq!(db.read_history_anchors().unwrap().len(), 2);
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn redating_the_anchor_moves_dependents() {
+    let (temp_path, db) = create_example();
+
+    db.redate_history_item(1.into(), 1040.into(), 20.into())
+        .unwrap();
+    assert_eq!(date(&db, 2), (1040.into(), 23.into()));
+    assert_eq!(date(&db, 3), (1041.into(), 23.into()));
+
+    db.redate_history_item(2.into(), 500.into(), 1.into())
+        .unwrap();
+    assert_eq!(db.read_history_anchor(2.into()).unwrap(), None);
+    assert_eq!(date(&db, 3), (501.into(), 1.into()));
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn redating_to_a_date_without_day_fails_for_day_offsets() {
+    let (temp_path, db) = create_example();
+
+    assert!(db
+        .redate_history_item(1.into(), 1040.into(), Day::NONE)
+        .is_err());
+    assert_eq!(date(&db, 1), (1032.into(), 10.into()));
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn cycles_and_dangling_anchors_are_rejected() {
+    let (temp_path, db) = create_example();
+
+    assert!(db
+        .write_history_anchor(1.into(), HistoryAnchor::new(3.into(), 0, 0))
+        .is_err());
+    assert!(db
+        .write_history_anchor(4.into(), HistoryAnchor::new(4.into(), 0, 0))
+        .is_err());
+    assert!(db
+        .write_history_anchor(4.into(), HistoryAnchor::new(99.into(), 0, 0))
+        .is_err());
+    assert!(db.find_anchor_problems().unwrap().is_empty());
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn deleting_an_anchor_leaves_a_dangling_anchor() {
+    let (temp_path, db) = create_example();
+
+    db.delete_history_item(2.into()).unwrap();
+    assert_eq!(
+        db.find_anchor_problems().unwrap(),
+        vec![AnchorProblem::DanglingAnchor {
+            timestamp: 3.into(),
+            anchor: 2.into()
+        }]
+    );
+    assert_eq!(date(&db, 3), (1033.into(), 13.into()));
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn anchors_leaving_the_year_of_the_default_calendar_are_rejected() {
+    let (temp_path, db) = create_example();
+    db.write_calendar(Calendar {
+        name: "Short".to_string(),
+        months: vec![Month {
+            name: "Only".to_string(),
+            days: 20,
+            leap_days: 0,
+        }],
+        weekdays: Vec::new(),
+        leap_rule: None,
+        intercalary_days: Vec::new(),
+        epoch: 0,
+        first_weekday: 0,
+    })
+    .unwrap();
+    db.set_default_calendar(Some("Short")).unwrap();
+
+    assert!(db
+        .write_history_anchor(4.into(), HistoryAnchor::new(1.into(), 0, 11))
+        .is_err());
+    db.write_history_anchor(4.into(), HistoryAnchor::new(1.into(), 0, 10))
+        .unwrap();
+    assert_eq!(date(&db, 4), (1032.into(), 20.into()));
+    assert!(db
+        .redate_history_item(1.into(), 1032.into(), 15.into())
+        .is_err());
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn failed_redating_changes_no_anchors() {
+    let (temp_path, db) = create_example();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+
+    let connection = rusqlite::Connection::open(&path_in).unwrap();
+    connection
+        .execute_batch(
+            "CREATE TRIGGER no_redating BEFORE UPDATE OF year ON history_items
+             BEGIN SELECT RAISE(ABORT, 'redating is disabled'); END;",
+        )
+        .unwrap();
+
+    assert!(db
+        .write_history_anchor(4.into(), HistoryAnchor::new(1.into(), 1, 0))
+        .is_err());
+    assert_eq!(db.read_history_anchor(4.into()).unwrap(), None);
+    assert!(db
+        .redate_history_item(2.into(), 1000.into(), 1.into())
+        .is_err());
+    assert_eq!(
+        db.read_history_anchor(2.into()).unwrap(),
+        Some(HistoryAnchor::new(1.into(), 0, 3))
+    );
+
+    connection
+        .execute_batch("DROP TRIGGER no_redating;")
+        .unwrap();
+    drop(connection);
+    temp_path.close().unwrap();
+}
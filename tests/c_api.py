@@ -37,24 +37,35 @@ class CHistoryItem(ctypes.Structure):
                 ("year", ctypes.c_int),
                 ("day", ctypes.c_int),
                 ("content", ctypes.c_char_p),
-                ("properties", ctypes.c_char_p)]
+                ("properties", ctypes.c_char_p),
+                ("precision", ctypes.c_uint),
+                ("range_years", ctypes.c_uint),
+                ("sort_key", ctypes.c_longlong)]
 
 class CEntityRelationship(ctypes.Structure):
     _fields_ = [("parent", ctypes.c_char_p),
                 ("child", ctypes.c_char_p),
-                ("role", ctypes.c_char_p)]
+                ("role", ctypes.c_char_p),
+                ("has_start", ctypes.c_bool),
+                ("start_year", ctypes.c_int),
+                ("start_day", ctypes.c_uint),
+                ("has_end", ctypes.c_bool),
+                ("end_year", ctypes.c_int),
+                ("end_day", ctypes.c_uint)]
 
 print("Define the Rust functions")
+AUTHOR_ONLY = 3
+
 write_entity_columns = rust_lib.write_entity_columns
 write_entity_columns.argtypes = [ctypes.c_char_p, ctypes.POINTER(CEntityColumn), ctypes.c_int]
 write_entity_columns.restype = ctypes.c_char_p
 
 get_number_of_entity_columns = rust_lib.get_number_of_entity_columns
-get_number_of_entity_columns.argtypes = [ctypes.c_char_p, ctypes.POINTER(ctypes.c_int)]
+get_number_of_entity_columns.argtypes = [ctypes.c_char_p, ctypes.c_uint, ctypes.POINTER(ctypes.c_int)]
 get_number_of_entity_columns.restype = ctypes.c_char_p
 
 read_entity_columns = rust_lib.read_entity_columns
-read_entity_columns.argtypes = [ctypes.c_char_p, ctypes.POINTER(CEntityColumn)]
+read_entity_columns.argtypes = [ctypes.c_char_p, ctypes.c_uint, ctypes.POINTER(CEntityColumn)]
 read_entity_columns.restype = ctypes.c_char_p
 
 write_history_items = rust_lib.write_history_items
@@ -62,25 +73,77 @@ write_history_items.argtypes = [ctypes.c_char_p, ctypes.POINTER(CHistoryItem), c
 write_history_items.restype = ctypes.c_char_p
 
 get_number_of_history_items = rust_lib.get_number_of_history_items
-get_number_of_history_items.argtypes = [ctypes.c_char_p, ctypes.POINTER(ctypes.c_int)]
+get_number_of_history_items.argtypes = [ctypes.c_char_p, ctypes.c_uint, ctypes.POINTER(ctypes.c_int)]
 get_number_of_history_items.restype = ctypes.c_char_p
 
 read_history_items = rust_lib.read_history_items
-read_history_items.argtypes = [ctypes.c_char_p, ctypes.POINTER(CHistoryItem)]
+read_history_items.argtypes = [ctypes.c_char_p, ctypes.c_uint, ctypes.POINTER(CHistoryItem)]
 read_history_items.restype = ctypes.c_char_p
 
+move_history_item = rust_lib.move_history_item
+move_history_item.argtypes = [ctypes.c_char_p, ctypes.c_longlong, ctypes.c_longlong, ctypes.c_bool]
+move_history_item.restype = ctypes.c_char_p
+
+change_history_item_sort_key = rust_lib.change_history_item_sort_key
+change_history_item_sort_key.argtypes = [ctypes.c_char_p, ctypes.c_longlong, ctypes.c_longlong]
+change_history_item_sort_key.restype = ctypes.c_char_p
+
 write_relationships = rust_lib.write_relationships
 write_relationships.argtypes = [ctypes.c_char_p, ctypes.POINTER(CEntityRelationship), ctypes.c_int]
 write_relationships.restype = ctypes.c_char_p
 
 get_number_of_relationships = rust_lib.get_number_of_relationships
-get_number_of_relationships.argtypes = [ctypes.c_char_p, ctypes.POINTER(ctypes.c_int)]
+get_number_of_relationships.argtypes = [ctypes.c_char_p, ctypes.c_uint, ctypes.POINTER(ctypes.c_int)]
 get_number_of_relationships.restype = ctypes.c_char_p
 
 read_relationships = rust_lib.read_relationships
-read_relationships.argtypes = [ctypes.c_char_p, ctypes.POINTER(CEntityRelationship)]
+read_relationships.argtypes = [ctypes.c_char_p, ctypes.c_uint, ctypes.POINTER(CEntityRelationship)]
 read_relationships.restype = ctypes.c_char_p
 
+class CLabelCount(ctypes.Structure):
+    _fields_ = [("label", ctypes.c_char_p),
+                ("count", ctypes.c_ssize_t)]
+
+class CYearCount(ctypes.Structure):
+    _fields_ = [("year", ctypes.c_int),
+                ("count", ctypes.c_ssize_t)]
+
+class CRoleCount(ctypes.Structure):
+    _fields_ = [("role", ctypes.c_char_p),
+                ("count", ctypes.c_ssize_t)]
+
+get_number_of_labels = rust_lib.get_number_of_labels
+get_number_of_labels.argtypes = [ctypes.c_char_p, ctypes.c_uint, ctypes.POINTER(ctypes.c_ssize_t)]
+get_number_of_labels.restype = ctypes.c_char_p
+
+read_descriptor_counts_per_label = rust_lib.read_descriptor_counts_per_label
+read_descriptor_counts_per_label.argtypes = [ctypes.c_char_p, ctypes.c_uint, ctypes.POINTER(CLabelCount)]
+read_descriptor_counts_per_label.restype = ctypes.c_char_p
+
+get_number_of_years = rust_lib.get_number_of_years
+get_number_of_years.argtypes = [ctypes.c_char_p, ctypes.c_uint, ctypes.POINTER(ctypes.c_ssize_t)]
+get_number_of_years.restype = ctypes.c_char_p
+
+read_history_item_counts_per_year = rust_lib.read_history_item_counts_per_year
+read_history_item_counts_per_year.argtypes = [ctypes.c_char_p, ctypes.c_uint, ctypes.POINTER(CYearCount)]
+read_history_item_counts_per_year.restype = ctypes.c_char_p
+
+get_number_of_roles = rust_lib.get_number_of_roles
+get_number_of_roles.argtypes = [ctypes.c_char_p, ctypes.c_uint, ctypes.POINTER(ctypes.c_ssize_t)]
+get_number_of_roles.restype = ctypes.c_char_p
+
+read_relationship_counts_per_role = rust_lib.read_relationship_counts_per_role
+read_relationship_counts_per_role.argtypes = [ctypes.c_char_p, ctypes.c_uint, ctypes.POINTER(CRoleCount)]
+read_relationship_counts_per_role.restype = ctypes.c_char_p
+
+get_number_of_label_suggestions = rust_lib.get_number_of_label_suggestions
+get_number_of_label_suggestions.argtypes = [ctypes.c_char_p, ctypes.c_char_p, ctypes.c_int, ctypes.c_uint, ctypes.POINTER(ctypes.c_int)]
+get_number_of_label_suggestions.restype = ctypes.c_char_p
+
+read_label_suggestions = rust_lib.read_label_suggestions
+read_label_suggestions.argtypes = [ctypes.c_char_p, ctypes.c_char_p, ctypes.c_int, ctypes.c_uint, ctypes.POINTER(ctypes.c_char_p)]
+read_label_suggestions.restype = ctypes.c_char_p
+
 get_current_timestamp = rust_lib.get_current_timestamp
 get_current_timestamp.argtypes = []
 get_current_timestamp.restype = ctypes.c_longlong
@@ -102,13 +165,13 @@ def test_write_entity_column():
 
     print("Getting the number of entity columns in the database")
     size = ctypes.c_int(0)
-    result = get_number_of_entity_columns(db_path, ctypes.byref(size))
+    result = get_number_of_entity_columns(db_path, AUTHOR_ONLY, ctypes.byref(size))
     assert result.decode('utf-8') == ""
     assert size.value == len(columns)
 
     print("Reading the entity columns from the database")
     read_columns = (CEntityColumn * size.value)()
-    result = read_entity_columns(db_path, read_columns)
+    result = read_entity_columns(db_path, AUTHOR_ONLY, read_columns)
     assert result.decode('utf-8') == ""
 
     temp_path.close()
@@ -121,8 +184,8 @@ def test_write_history_items():
     print("Created a temporary file at: " + temp_path.name)
 
     db_path = temp_path.name.encode('utf-8')
-    item1 = CHistoryItem(get_current_timestamp(), 2021, 29, b"testcontent1", b"testproperties1")
-    item2 = CHistoryItem(get_current_timestamp(), 2021, 30, b"testcontent2", b"testproperties2")
+    item1 = CHistoryItem(get_current_timestamp(), 2021, 29, b"testcontent1", b"{\"is_secret\":true}")
+    item2 = CHistoryItem(get_current_timestamp(), 2021, 30, b"testcontent2", b"{}")
     items = (CHistoryItem * 2)(item1, item2)
 
     print("Writing the history items to the database")
@@ -131,18 +194,51 @@ def test_write_history_items():
 
     print("Getting the number of history items in the database")
     size = ctypes.c_int(0)
-    result = get_number_of_history_items(db_path, ctypes.byref(size))
+    result = get_number_of_history_items(db_path, AUTHOR_ONLY, ctypes.byref(size))
     assert result.decode('utf-8') == ""
     assert size.value == len(items)
 
     print("Reading the history items from the database")
     read_items = (CHistoryItem * size.value)()
-    result = read_history_items(db_path, read_items)
+    result = read_history_items(db_path, AUTHOR_ONLY, read_items)
     assert result.decode('utf-8') == ""
 
     temp_path.close()
 test_write_history_items()
 
+def test_reorder_history_items():
+    print("Running the reorder_history_items test")
+
+    temp_path = tempfile.NamedTemporaryFile(delete=False)
+    print("Created a temporary file at: " + temp_path.name)
+
+    db_path = temp_path.name.encode('utf-8')
+    item1 = CHistoryItem(1, 2021, 29, b"first", b"{}")
+    item2 = CHistoryItem(2, 2021, 29, b"second", b"{}")
+    item3 = CHistoryItem(3, 2021, 29, b"third", b"{}")
+    items = (CHistoryItem * 3)(item1, item2, item3)
+    result = write_history_items(db_path, items, len(items))
+    assert result.decode('utf-8') == ""
+
+    print("Moving the last history item to the front")
+    result = move_history_item(db_path, 3, 1, False)
+    assert result.decode('utf-8') == ""
+    read_items = (CHistoryItem * 3)()
+    result = read_history_items(db_path, AUTHOR_ONLY, read_items)
+    assert result.decode('utf-8') == ""
+    assert [item.timestamp for item in read_items] == [3, 1, 2]
+    assert [item.sort_key for item in read_items] == [0, 1, 2]
+
+    print("Changing a sort key")
+    result = change_history_item_sort_key(db_path, 3, 5)
+    assert result.decode('utf-8') == ""
+    result = read_history_items(db_path, AUTHOR_ONLY, read_items)
+    assert result.decode('utf-8') == ""
+    assert [item.timestamp for item in read_items] == [1, 2, 3]
+
+    temp_path.close()
+test_reorder_history_items()
+
 def test_write_relationships():
     print("Running the write_relationships test")
 
@@ -151,7 +247,7 @@ def test_write_relationships():
 
     db_path = temp_path.name.encode('utf-8')
     relationship1 = CEntityRelationship(b"testparent1", b"testchild1", b"testrole1")
-    relationship2 = CEntityRelationship(b"testparent2", b"testchild2", b"testrole2")
+    relationship2 = CEntityRelationship(b"testparent2", b"testchild2", b"testrole2", True, 1032, 7, False, 0, 0)
     relationships = (CEntityRelationship * 2)(relationship1, relationship2)
 
     print("Writing the relationships to the database")
@@ -160,18 +256,108 @@ def test_write_relationships():
 
     print("Getting the number of relationships in the database")
     size = ctypes.c_int(0)
-    result = get_number_of_relationships(db_path, ctypes.byref(size))
+    result = get_number_of_relationships(db_path, AUTHOR_ONLY, ctypes.byref(size))
     assert result.decode('utf-8') == ""
     assert size.value == len(relationships)
 
     print("Reading the relationships from the database")
     read_in_relationships = (CEntityRelationship * size.value)()
-    result = read_relationships(db_path, read_in_relationships)
+    result = read_relationships(db_path, AUTHOR_ONLY, read_in_relationships)
     assert result.decode('utf-8') == ""
+    assert not read_in_relationships[0].has_start
+    assert read_in_relationships[1].has_start
+    assert (read_in_relationships[1].start_year, read_in_relationships[1].start_day) == (1032, 7)
+    assert not read_in_relationships[1].has_end
 
     temp_path.close()
 test_write_relationships()
 
+def test_aggregations():
+    print("Running the aggregations test")
+
+    temp_path = tempfile.NamedTemporaryFile(delete=False)
+    print("Created a temporary file at: " + temp_path.name)
+
+    db_path = temp_path.name.encode('utf-8')
+    column1 = CEntityColumn(b"testlabel1", b"testdescriptor1", b"testdescription1")
+    column2 = CEntityColumn(b"testlabel1", b"testdescriptor2", b"testdescription2")
+    column3 = CEntityColumn(b"testlabel2", b"testdescriptor1", b"testdescription3")
+    columns = (CEntityColumn * 3)(column1, column2, column3)
+    result = write_entity_columns(db_path, columns, len(columns))
+    assert result.decode('utf-8') == ""
+
+    item1 = CHistoryItem(get_current_timestamp(), 2021, 29, b"testcontent1", b"{}")
+    item2 = CHistoryItem(get_current_timestamp(), 2021, 30, b"testcontent2", b"{}")
+    item3 = CHistoryItem(get_current_timestamp(), 2022, 30, b"testcontent3", b"{}")
+    items = (CHistoryItem * 3)(item1, item2, item3)
+    result = write_history_items(db_path, items, len(items))
+    assert result.decode('utf-8') == ""
+
+    relationship1 = CEntityRelationship(b"testlabel1", b"testlabel2", b"testrole")
+    relationship2 = CEntityRelationship(b"testlabel2", b"testlabel1", b"testrole")
+    relationships = (CEntityRelationship * 2)(relationship1, relationship2)
+    result = write_relationships(db_path, relationships, len(relationships))
+    assert result.decode('utf-8') == ""
+
+    print("Reading the number of descriptors per label")
+    size = ctypes.c_ssize_t(0)
+    result = get_number_of_labels(db_path, AUTHOR_ONLY, ctypes.byref(size))
+    assert result.decode('utf-8') == ""
+    assert size.value == 2
+    label_counts = (CLabelCount * size.value)()
+    result = read_descriptor_counts_per_label(db_path, AUTHOR_ONLY, label_counts)
+    assert result.decode('utf-8') == ""
+    assert [(c.label, c.count) for c in label_counts] == [(b"testlabel1", 2), (b"testlabel2", 1)]
+
+    print("Reading the number of history items per year")
+    result = get_number_of_years(db_path, AUTHOR_ONLY, ctypes.byref(size))
+    assert result.decode('utf-8') == ""
+    assert size.value == 2
+    year_counts = (CYearCount * size.value)()
+    result = read_history_item_counts_per_year(db_path, AUTHOR_ONLY, year_counts)
+    assert result.decode('utf-8') == ""
+    assert [(c.year, c.count) for c in year_counts] == [(2021, 2), (2022, 1)]
+
+    print("Reading the number of relationships per role")
+    result = get_number_of_roles(db_path, AUTHOR_ONLY, ctypes.byref(size))
+    assert result.decode('utf-8') == ""
+    assert size.value == 1
+    role_counts = (CRoleCount * size.value)()
+    result = read_relationship_counts_per_role(db_path, AUTHOR_ONLY, role_counts)
+    assert result.decode('utf-8') == ""
+    assert [(c.role, c.count) for c in role_counts] == [(b"testrole", 2)]
+
+    temp_path.close()
+test_aggregations()
+
+def test_label_suggestions():
+    print("Running the label_suggestions test")
+
+    temp_path = tempfile.NamedTemporaryFile(delete=False)
+    print("Created a temporary file at: " + temp_path.name)
+
+    db_path = temp_path.name.encode('utf-8')
+    column1 = CEntityColumn(b"Gandalf_the_Grey", b"testdescriptor", b"testdescription")
+    column2 = CEntityColumn(b"Saruman", b"testdescriptor", b"testdescription")
+    columns = (CEntityColumn * 2)(column1, column2)
+    result = write_entity_columns(db_path, columns, len(columns))
+    assert result.decode('utf-8') == ""
+
+    print("Getting the number of label suggestions")
+    size = ctypes.c_int(0)
+    result = get_number_of_label_suggestions(db_path, b"Gandalf_the_Gray", 5, AUTHOR_ONLY, ctypes.byref(size))
+    assert result.decode('utf-8') == ""
+    assert size.value == 1
+
+    print("Reading the label suggestions")
+    suggestions = (ctypes.c_char_p * size.value)()
+    result = read_label_suggestions(db_path, b"Gandalf_the_Gray", 5, AUTHOR_ONLY, suggestions)
+    assert result.decode('utf-8') == ""
+    assert suggestions[0].decode('utf-8') == "Gandalf_the_Grey"
+
+    temp_path.close()
+test_label_suggestions()
+
 def test_get_current_timestamp():
     print("Running the get_current_timestamp test")
     timestamp = get_current_timestamp()
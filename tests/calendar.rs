@@ -0,0 +1,136 @@
+use lorecore::{
+    calendar::{Calendar, IntercalaryDay, LeapRule, Month},
+    sql::lore_database::LoreDatabase,
+    types::*,
+};
+use std::path::PathBuf;
+use tempfile::NamedTempFile;
+
+fn month(name: &str, days: u32) -> Month {
+    Month {
+        name: name.to_string(),
+        days,
+        leap_days: 0,
+    }
+}
+
+fn reckoning() -> Calendar {
+    Calendar {
+        name: "Reckoning".to_string(),
+        months: vec![
+            month("Frostmoon", 30),
+            month("Thawmoon", 30),
+            month("Harvestmoon", 30),
+        ],
+        weekdays: vec!["Sunday".to_string(), "Moonday".to_string()],
+        leap_rule: Some(LeapRule {
+            every: 4,
+            except_every: None,
+            unless_every: None,
+        }),
+        intercalary_days: vec![IntercalaryDay {
+            name: "Overlithe".to_string(),
+            after_month: 2,
+            leap_only: true,
+        }],
+        epoch: 0,
+        first_weekday: 0,
+    }
+}
+
+fn elvish() -> Calendar {
+    Calendar {
+        name: "Elvish".to_string(),
+        months: vec![month("Spring", 45), month("Autumn", 45)],
+        weekdays: Vec::new(),
+        leap_rule: None,
+        intercalary_days: Vec::new(),
+        epoch: -90,
+        first_weekday: 0,
+    }
+}
+
+fn create_example() -> (tempfile::TempPath, LoreDatabase) {
+    let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+    let db = LoreDatabase::open(path_in.clone()).unwrap();
+
+    db.write_calendar(reckoning()).unwrap();
+    db.write_calendar(elvish()).unwrap();
+    (temp_path, db)
+}
+
+#[test]
+fn calendars_roundtrip() {
+    let (temp_path, db) = create_example();
+
+    assert_eq!(db.read_calendar("Reckoning").unwrap(), reckoning());
+    assert_eq!(db.read_calendars().unwrap(), vec![elvish(), reckoning()]);
+    assert!(db.write_calendar(reckoning()).is_err());
+    assert!(db.read_calendar("Shire").is_err());
+
+    db.delete_calendar("Elvish").unwrap();
+    assert_eq!(db.read_calendars().unwrap().len(), 1);
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn inconsistent_calendars_are_rejected() {
+    let (temp_path, db) = create_example();
+
+    let mut calendar = reckoning();
+    calendar.name = "Broken".to_string();
+    calendar.months[1].days = 0;
+    assert!(db.write_calendar(calendar).is_err());
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn format_parse_and_convert_stored_calendar() {
+    let (temp_path, db) = create_example();
+
+    let calendar = db.read_calendar("Reckoning").unwrap();
+    assert_eq!(
+        calendar.format(1032.into(), 63.into()).unwrap(),
+        "2nd of Harvestmoon, 1032"
+    );
+    assert_eq!(
+        calendar.parse("Overlithe, 1032").unwrap(),
+        (1032.into(), 61.into())
+    );
+
+    let (year, day) = db
+        .convert_date(1.into(), 1.into(), "Reckoning", "Elvish")
+        .unwrap();
+    assert_eq!((year, day), (2.into(), 2.into()));
+    assert_eq!(
+        db.convert_date(year, day, "Elvish", "Reckoning").unwrap(),
+        (1.into(), 1.into())
+    );
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn history_items_beyond_year_length_are_found() {
+    let (temp_path, db) = create_example();
+
+    let item = |timestamp: i64, year: i32, day: u32| {
+        HistoryItem::new(
+            timestamp.into(),
+            year.into(),
+            day.into(),
+            "Something happened.".into(),
+        )
+    };
+    db.write_history_items(vec![item(1, 1032, 91), item(2, 1033, 91), item(3, 1033, 0)])
+        .unwrap();
+    let invalid = db
+        .find_history_items_with_invalid_days("Reckoning")
+        .unwrap();
+    assert_eq!(invalid, vec![item(2, 1033, 91)]);
+
+    temp_path.close().unwrap();
+}
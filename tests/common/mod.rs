@@ -0,0 +1,25 @@
+#![allow(dead_code)]
+
+use lorecore::types::*;
+
+pub fn item(timestamp: i64, year: i32, day: u32, content: &str) -> HistoryItem {
+    HistoryItem::new(timestamp.into(), year.into(), day.into(), content.into())
+}
+
+pub fn item_with_properties(
+    timestamp: i64,
+    year: i32,
+    day: u32,
+    content: &str,
+    properties: &str,
+) -> HistoryItem {
+    item(timestamp, year, day, content).with_properties(properties.try_into().unwrap())
+}
+
+pub fn rel(parent: &str, child: &str, role: &str) -> EntityRelationship {
+    EntityRelationship::new(parent.into(), child.into(), role.into())
+}
+
+pub fn col(label: &str, descriptor: &str, description: &str) -> EntityColumn {
+    EntityColumn::new(label.into(), descriptor.into(), description.into())
+}
@@ -0,0 +1,141 @@
+use lorecore::{
+    calendar::{Calendar, Month},
+    sql::{
+        consistency::TimelineIssue, lore_database::LoreDatabase,
+        search_params::RelationshipTraversalParams,
+    },
+    types::*,
+};
+use std::path::PathBuf;
+use tempfile::NamedTempFile;
+
+mod common;
+use common::*;
+
+fn create_example() -> (tempfile::TempPath, LoreDatabase) {
+    let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+    let db = LoreDatabase::open(path_in.clone()).unwrap();
+
+    db.write_history_items(vec![
+        item_with_properties(1, 900, 10, "", "{\"births\":[\"father\"]}"),
+        item_with_properties(2, 890, 0, "", "{\"births\":[\"son\"]}"),
+        item_with_properties(3, 950, 0, "", "{\"deaths\":[\"father\"]}"),
+        item_with_properties(4, 960, 0, "\\entityref{father} gives a speech.", "{}"),
+        item_with_properties(5, 895, 0, "\\entityref{father} is mentioned.", "{}"),
+        item_with_properties(6, 920, 400, "\\entityref{son} goes to sea.", "{}"),
+    ])
+    .unwrap();
+    db.write_relationships(vec![
+        EntityRelationship::new("father".into(), "son".into(), "parent".into()),
+        EntityRelationship::new("father".into(), "city".into(), "ruler".into())
+            .valid_between(Some((940.into(), Day::NONE)), Some((955.into(), Day::NONE))),
+    ])
+    .unwrap();
+    (temp_path, db)
+}
+
+#[test]
+fn timeline_issues_are_found() {
+    let (temp_path, db) = create_example();
+
+    let params = RelationshipTraversalParams::new(vec!["parent".into()], None);
+    let issues = db.check_timeline(params, Visibility::AuthorOnly).unwrap();
+    let summaries: Vec<_> = issues
+        .iter()
+        .map(|issue| (issue.timestamps(), issue.labels()))
+        .collect();
+    assert_eq!(
+        summaries,
+        vec![
+            (vec![5.into(), 1.into()], vec!["father".into()]),
+            (vec![4.into(), 3.into()], vec!["father".into()]),
+            (
+                vec![1.into(), 2.into()],
+                vec!["father".into(), "son".into()]
+            ),
+            (vec![3.into()], vec!["father".into(), "city".into()]),
+        ]
+    );
+    assert!(matches!(
+        issues[3],
+        TimelineIssue::RelationshipOutsideLifespan { ref label, .. } if label == &"father".into()
+    ));
+
+    let issues = db
+        .check_timeline(
+            RelationshipTraversalParams::new(vec!["none".into()], None),
+            Visibility::AuthorOnly,
+        )
+        .unwrap();
+    assert_eq!(issues.len(), 3);
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn days_are_checked_against_the_default_calendar() {
+    let (temp_path, db) = create_example();
+
+    db.write_calendar(Calendar {
+        name: "Simple".to_string(),
+        months: vec![Month {
+            name: "Frostmoon".to_string(),
+            days: 365,
+            leap_days: 0,
+        }],
+        weekdays: Vec::new(),
+        leap_rule: None,
+        intercalary_days: Vec::new(),
+        epoch: 0,
+        first_weekday: 0,
+    })
+    .unwrap();
+    db.set_default_calendar(Some("Simple")).unwrap();
+    let params = || RelationshipTraversalParams::new(vec!["parent".into()], None);
+    let issues = db.check_timeline(params(), Visibility::AuthorOnly).unwrap();
+    assert!(issues.contains(&TimelineIssue::DayBeyondYear {
+        timestamp: 6.into(),
+        year: 920.into(),
+        day: 400,
+        year_length: 365
+    }));
+
+    db.delete_calendar("Simple").unwrap();
+    assert_eq!(db.read_default_calendar().unwrap(), None);
+    assert_eq!(
+        db.check_timeline(params(), Visibility::AuthorOnly)
+            .unwrap()
+            .len(),
+        4
+    );
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn uncertain_life_events_are_only_flagged_when_certain() {
+    let (temp_path, db) = create_example();
+
+    db.write_history_items(vec![
+        item_with_properties(7, 905, 3, "", "{\"births\":[\"daughter\"]}")
+            .with_precision(DatePrecision::Decade),
+        item(8, 902, 1, "\\entityref{daughter} learns to walk."),
+        item(9, 899, 0, "\\entityref{daughter} is expected."),
+    ])
+    .unwrap();
+    let issues = db
+        .check_timeline(
+            RelationshipTraversalParams::new(vec![], None),
+            Visibility::AuthorOnly,
+        )
+        .unwrap();
+    let daughter_issues: Vec<_> = issues
+        .iter()
+        .filter(|issue| issue.labels().contains(&"daughter".into()))
+        .map(|issue| issue.timestamps())
+        .collect();
+    assert_eq!(daughter_issues, vec![vec![9.into(), 7.into()]]);
+
+    temp_path.close().unwrap();
+}
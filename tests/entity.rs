@@ -661,3 +661,198 @@ fn test_change_entity_description_to_none() {
 
     temp_path.close().unwrap();
 }
+
+fn create_entity_example() -> (tempfile::TempPath, LoreDatabase) {
+    let (temp_path, db, _) = create_example();
+    db.write_relationships(vec![
+        EntityRelationship {
+            parent: "testlabel1".into(),
+            child: "testlabel2and_stuff".into(),
+            role: "liege".into(),
+            start: None,
+            end: None,
+        },
+        EntityRelationship {
+            parent: "testlabel1".into(),
+            child: "someone_else".into(),
+            role: "liege".into(),
+            start: None,
+            end: None,
+        },
+        EntityRelationship {
+            parent: "testlabel2and_stuff".into(),
+            child: "testlabel1".into(),
+            role: Role::NONE,
+            start: None,
+            end: None,
+        },
+        EntityRelationship {
+            parent: "someone_else".into(),
+            child: "another_one".into(),
+            role: "liege".into(),
+            start: None,
+            end: None,
+        },
+    ])
+    .unwrap();
+    db.write_history_items(vec![
+        HistoryItem::new(
+            1.into(),
+            12.into(),
+            Day::NONE,
+            "\\entityref{testlabel1} was born.".into(),
+        ),
+        HistoryItem::new(2.into(), 13.into(), 2.into(), "Something happened.".into())
+            .with_properties(
+                "{\"additional_concerns\":[\"\\\\entityref{testlabel1}\"]}"
+                    .try_into()
+                    .unwrap(),
+            ),
+        HistoryItem::new(
+            3.into(),
+            14.into(),
+            Day::NONE,
+            "\\entityref{testlabel2and_stuff} was born.".into(),
+        ),
+    ])
+    .unwrap();
+    (temp_path, db)
+}
+
+#[test]
+fn read_entity_assembles_all_tables() {
+    let (temp_path, db) = create_entity_example();
+
+    let entity = db
+        .read_entity(&"testlabel1".into(), Visibility::AuthorOnly)
+        .unwrap();
+
+    assert_eq!(entity.label, "testlabel1".into());
+    assert_eq!(
+        entity.descriptors(),
+        vec!["testdescriptor1".into(), "testdescriptor2and_stuff".into()]
+    );
+    assert_eq!(
+        entity.description(&"testdescriptor1".into()),
+        Some(&"testlabel1testdescriptor1".into())
+    );
+    assert_eq!(entity.outgoing_relationships.len(), 1);
+    assert_eq!(entity.outgoing_relationships[&"liege".into()].len(), 2);
+    assert_eq!(entity.incoming_relationships.len(), 1);
+    assert_eq!(entity.incoming_relationships[&Role::NONE].len(), 1);
+    assert_eq!(
+        entity.children(),
+        vec!["someone_else".into(), "testlabel2and_stuff".into()]
+    );
+    assert_eq!(entity.parents(), vec!["testlabel2and_stuff".into()]);
+    let timestamps: Vec<_> = entity
+        .history_items
+        .iter()
+        .map(|item| item.timestamp)
+        .collect();
+    assert_eq!(timestamps, vec![1.into(), 2.into()]);
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn read_entities_loads_all_requested_labels() {
+    let (temp_path, db) = create_entity_example();
+    let labels: Vec<Label> = vec!["testlabel2and_stuff".into(), "testlabel1".into()];
+
+    let entities = db.read_entities(&labels, Visibility::AuthorOnly).unwrap();
+
+    assert_eq!(entities.len(), 2);
+    for (entity, label) in entities.iter().zip(labels.iter()) {
+        assert_eq!(&entity.label, label);
+        assert_eq!(
+            entity,
+            &db.read_entity(label, Visibility::AuthorOnly).unwrap()
+        );
+    }
+    let timestamps: Vec<_> = entities[0]
+        .history_items
+        .iter()
+        .map(|item| item.timestamp)
+        .collect();
+    assert_eq!(timestamps, vec![3.into()]);
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn read_non_existing_entity_fails() {
+    let (temp_path, db) = create_entity_example();
+
+    assert!(db
+        .read_entity(&"someone_else".into(), Visibility::AuthorOnly)
+        .is_err());
+    assert!(db
+        .read_entities(
+            &["testlabel1".into(), "fununu".into()],
+            Visibility::AuthorOnly,
+        )
+        .is_err());
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn count_entity_columns_honours_search_params() {
+    let (temp_path, db, entities) = create_example();
+
+    let count = db
+        .count_entity_columns(EntityColumnSearchParams::empty())
+        .unwrap();
+    assert_eq!(count, entities.len());
+
+    let count = db
+        .count_entity_columns(EntityColumnSearchParams::new(
+            Some(SqlSearchText::partial("bel1")),
+            None,
+        ))
+        .unwrap();
+    assert_eq!(count, 2);
+
+    let count = db
+        .count_entity_columns(EntityColumnSearchParams::new(
+            Some(SqlSearchText::exact("testlabel1")),
+            Some(SqlSearchText::exact("testdescriptor1")),
+        ))
+        .unwrap();
+    assert_eq!(count, 1);
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn count_descriptors_per_label() {
+    let (temp_path, db, _) = create_example();
+    db.write_entity_columns(vec![EntityColumn {
+        label: "testlabel1".into(),
+        descriptor: "testdescriptor3".into(),
+        description: Description::NONE,
+    }])
+    .unwrap();
+
+    let counts = db
+        .count_descriptors_per_label(EntityColumnSearchParams::empty())
+        .unwrap();
+    assert_eq!(
+        counts,
+        vec![("testlabel1".into(), 3), ("testlabel2and_stuff".into(), 2)]
+    );
+
+    let counts = db
+        .count_descriptors_per_label(EntityColumnSearchParams::new(
+            None,
+            Some(SqlSearchText::partial("riptor1")),
+        ))
+        .unwrap();
+    assert_eq!(
+        counts,
+        vec![("testlabel1".into(), 1), ("testlabel2and_stuff".into(), 1)]
+    );
+
+    temp_path.close().unwrap();
+}
@@ -0,0 +1,148 @@
+use lorecore::{
+    calendar::{Calendar, Month},
+    era::Era,
+    sql::{lore_database::LoreDatabase, search_params::HistoryItemSearchParams},
+    types::*,
+};
+use std::path::PathBuf;
+use tempfile::NamedTempFile;
+
+mod common;
+use common::*;
+
+fn before_sundering() -> Era {
+    Era {
+        name: "Before the Sundering".to_string(),
+        abbreviation: "BS".to_string(),
+        epoch: (-1).into(),
+        counts_backwards: true,
+        has_year_zero: false,
+        start: None,
+        end: Some((-1).into()),
+    }
+}
+
+fn age_of_ash() -> Era {
+    Era {
+        name: "Age of Ash".to_string(),
+        abbreviation: "AA".to_string(),
+        epoch: 0.into(),
+        counts_backwards: false,
+        has_year_zero: true,
+        start: Some(0.into()),
+        end: None,
+    }
+}
+
+fn create_example() -> (tempfile::TempPath, LoreDatabase) {
+    let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+    let db = LoreDatabase::open(path_in.clone()).unwrap();
+
+    db.write_eras(vec![age_of_ash(), before_sundering()])
+        .unwrap();
+    (temp_path, db)
+}
+
+#[test]
+fn eras_roundtrip() {
+    let (temp_path, db) = create_example();
+
+    assert_eq!(
+        db.read_eras().unwrap(),
+        vec![before_sundering(), age_of_ash()]
+    );
+    assert_eq!(db.read_era("Age of Ash").unwrap(), age_of_ash());
+    assert!(db.read_era("Age of Stars").is_err());
+
+    db.delete_era("Age of Ash").unwrap();
+    assert_eq!(db.read_eras().unwrap(), vec![before_sundering()]);
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn format_and_parse_years() {
+    let (temp_path, db) = create_example();
+
+    assert_eq!(db.format_year((-412).into(), None).unwrap(), "412 BS");
+    assert_eq!(db.format_year(1032.into(), None).unwrap(), "1032 AA");
+    assert_eq!(
+        db.format_year(1032.into(), Some("Before the Sundering"))
+            .unwrap(),
+        "-1033 BS"
+    );
+    assert_eq!(db.parse_year("412 BS").unwrap(), (-412).into());
+    assert_eq!(db.parse_year("1032 AA").unwrap(), 1032.into());
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn history_items_with_dates_in_eras() {
+    let (temp_path, db) = create_example();
+
+    db.write_calendar(Calendar {
+        name: "Simple".to_string(),
+        months: vec![Month {
+            name: "Frostmoon".to_string(),
+            days: 365,
+            leap_days: 0,
+        }],
+        weekdays: Vec::new(),
+        leap_rule: None,
+        intercalary_days: Vec::new(),
+        epoch: 0,
+        first_weekday: 0,
+    })
+    .unwrap();
+    db.write_history_items(vec![
+        item(1, -412, 3, "Something happened."),
+        item(2, 1032, 0, "Something happened."),
+    ])
+    .unwrap();
+
+    let dates = |era: Option<&str>, calendar: Option<&str>| -> Vec<String> {
+        db.read_dated_history_items(
+            HistoryItemSearchParams::empty(),
+            era,
+            calendar,
+            Markup::Plain,
+        )
+        .unwrap()
+        .into_iter()
+        .map(|dated| dated.date)
+        .collect()
+    };
+    assert_eq!(dates(None, None), vec!["day 3 of 412 BS", "1032 AA"]);
+    assert_eq!(
+        dates(Some("Age of Ash"), Some("Simple")),
+        vec!["3rd of Frostmoon, -412 AA", "1032 AA"]
+    );
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn uncertain_dates_are_rendered_with_markup() {
+    let (temp_path, db) = create_example();
+
+    let circa = item(1, 300, 0, "Something happened.").with_precision(DatePrecision::Circa);
+    let range = item(2, 120, 0, "Something happened.").with_precision(DatePrecision::Range(20));
+    db.write_history_items(vec![circa, range]).unwrap();
+
+    let dates = |markup| -> Vec<String> {
+        db.read_dated_history_items(HistoryItemSearchParams::empty(), None, None, markup)
+            .unwrap()
+            .into_iter()
+            .map(|dated| dated.date)
+            .collect()
+    };
+    assert_eq!(
+        dates(Markup::Markdown),
+        vec!["120 AA\u{2013}140 AA", "*c.* 300 AA"]
+    );
+    assert_eq!(dates(Markup::Latex), vec!["120 AA--140 AA", "c.~300 AA"]);
+
+    temp_path.close().unwrap();
+}
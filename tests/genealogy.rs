@@ -0,0 +1,218 @@
+use lorecore::{
+    sql::{
+        genealogy::CommonAncestor, lore_database::LoreDatabase,
+        search_params::RelationshipTraversalParams,
+    },
+    timestamp::current_timestamp,
+    types::*,
+};
+use std::path::PathBuf;
+use tempfile::NamedTempFile;
+
+fn family() -> RelationshipTraversalParams {
+    RelationshipTraversalParams::new(vec!["parent".into()], None)
+}
+
+fn create_example() -> (tempfile::TempPath, LoreDatabase) {
+    let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+    let db = LoreDatabase::open(path_in.clone()).unwrap();
+
+    let rels = [
+        ("queen", "prince_a", "parent"),
+        ("queen", "princess_b", "parent"),
+        ("queen", "prince_c", "parent"),
+        ("king", "prince_a", "parent"),
+        ("king", "princess_b", "parent"),
+        ("king", "prince_c", "parent"),
+        ("princess_b", "grandchild_b1", "parent"),
+        ("prince_a", "grandchild_a1", "parent"),
+        ("prince_a", "grandchild_a2", "parent"),
+        ("grandchild_a1", "great_grandchild_a1x", "parent"),
+        ("queen", "kingdom", "rules"),
+    ];
+    db.write_relationships(
+        rels.iter()
+            .map(|(parent, child, role)| EntityRelationship {
+                parent: (*parent).into(),
+                child: (*child).into(),
+                role: (*role).into(),
+                start: None,
+                end: None,
+            })
+            .collect(),
+    )
+    .unwrap();
+
+    let births = [
+        (900, "queen"),
+        (930, "prince_a"),
+        (925, "princess_b"),
+        (950, "grandchild_b1"),
+        (955, "grandchild_a1"),
+        (952, "grandchild_a2"),
+    ];
+    db.write_history_items(
+        births
+            .iter()
+            .map(|(year, label)| {
+                HistoryItem::new(
+                    current_timestamp(),
+                    (*year).into(),
+                    Day::NONE,
+                    format!("\\entityref{{{}}} is born.", label).into(),
+                )
+                .with_properties(
+                    format!("{{\"births\":[\"{}\"]}}", label)
+                        .as_str()
+                        .try_into()
+                        .unwrap(),
+                )
+            })
+            .collect(),
+    )
+    .unwrap();
+    (temp_path, db)
+}
+
+#[test]
+fn generations_count_from_oldest_ancestor() {
+    let (temp_path, db) = create_example();
+
+    let generations = db
+        .read_generations(family(), Visibility::AuthorOnly)
+        .unwrap();
+    assert_eq!(generations[&"queen".into()], 0);
+    assert_eq!(generations[&"king".into()], 0);
+    assert_eq!(generations[&"prince_c".into()], 1);
+    assert_eq!(generations[&"grandchild_b1".into()], 2);
+    assert_eq!(generations[&"great_grandchild_a1x".into()], 3);
+    assert!(!generations.contains_key(&"kingdom".into()));
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn lowest_common_ancestors_of_siblings_are_both_parents() {
+    let (temp_path, db) = create_example();
+
+    let ancestors = db
+        .read_lowest_common_ancestors(
+            &"prince_a".into(),
+            &"princess_b".into(),
+            family(),
+            Visibility::AuthorOnly,
+        )
+        .unwrap();
+    assert_eq!(
+        ancestors,
+        vec![
+            CommonAncestor {
+                label: "king".into(),
+                depth_from_first: 1,
+                depth_from_second: 1,
+            },
+            CommonAncestor {
+                label: "queen".into(),
+                depth_from_first: 1,
+                depth_from_second: 1,
+            },
+        ]
+    );
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn lowest_common_ancestor_of_descendant_is_ancestor_itself() {
+    let (temp_path, db) = create_example();
+
+    let ancestors = db
+        .read_lowest_common_ancestors(
+            &"great_grandchild_a1x".into(),
+            &"prince_a".into(),
+            family(),
+            Visibility::AuthorOnly,
+        )
+        .unwrap();
+    assert_eq!(
+        ancestors,
+        vec![CommonAncestor {
+            label: "prince_a".into(),
+            depth_from_first: 2,
+            depth_from_second: 0,
+        }]
+    );
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn kinship_is_named() {
+    let (temp_path, db) = create_example();
+
+    let kinship = |first: &str, second: &str| {
+        db.read_kinship(
+            &first.into(),
+            &second.into(),
+            family(),
+            Visibility::AuthorOnly,
+        )
+        .unwrap()
+    };
+    assert_eq!(kinship("prince_a", "princess_b").unwrap(), "sibling");
+    assert_eq!(
+        kinship("grandchild_a1", "grandchild_b1").unwrap(),
+        "first cousin"
+    );
+    assert_eq!(
+        kinship("great_grandchild_a1x", "grandchild_b1").unwrap(),
+        "first cousin once removed"
+    );
+    assert_eq!(
+        kinship("great_grandchild_a1x", "prince_a").unwrap(),
+        "grandparent"
+    );
+    assert_eq!(
+        kinship("grandchild_b1", "prince_c").unwrap(),
+        "aunt or uncle"
+    );
+    assert_eq!(kinship("queen", "kingdom"), None);
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn line_of_succession_is_ordered_by_birth() {
+    let (temp_path, db) = create_example();
+
+    let line = db
+        .read_line_of_succession(&"queen".into(), family(), Visibility::AuthorOnly)
+        .unwrap();
+    assert_eq!(
+        line,
+        vec![
+            "princess_b".into(),
+            "grandchild_b1".into(),
+            "prince_a".into(),
+            "grandchild_a2".into(),
+            "grandchild_a1".into(),
+            "great_grandchild_a1x".into(),
+            "prince_c".into(),
+        ]
+    );
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn birth_dates_are_read_from_history_items() {
+    let (temp_path, db) = create_example();
+
+    let birth_dates = db.read_birth_dates(Visibility::AuthorOnly).unwrap();
+    assert_eq!(birth_dates.len(), 6);
+    assert_eq!(birth_dates[&"princess_b".into()], (925.into(), Day::NONE));
+    assert!(!birth_dates.contains_key(&"prince_c".into()));
+
+    temp_path.close().unwrap();
+}
@@ -0,0 +1,273 @@
+use lorecore::{
+    sql::{
+        graph::ReachedEntity, lore_database::LoreDatabase,
+        search_params::RelationshipTraversalParams,
+    },
+    types::*,
+};
+use std::path::PathBuf;
+use tempfile::NamedTempFile;
+
+fn reached(label: &str, depth: u32) -> ReachedEntity {
+    ReachedEntity {
+        label: label.into(),
+        depth,
+    }
+}
+
+fn create_example() -> (tempfile::TempPath, LoreDatabase) {
+    let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+    let db = LoreDatabase::open(path_in.clone()).unwrap();
+
+    let rels = [
+        ("continent", "kingdom", "contains"),
+        ("kingdom", "duchy", "contains"),
+        ("duchy", "city", "contains"),
+        ("kingdom", "city", "capital"),
+        ("city", "guild", "hosts"),
+        ("king", "kingdom", "rules"),
+    ];
+    db.write_relationships(
+        rels.iter()
+            .map(|(parent, child, role)| EntityRelationship {
+                parent: (*parent).into(),
+                child: (*child).into(),
+                role: (*role).into(),
+                start: None,
+                end: None,
+            })
+            .collect(),
+    )
+    .unwrap();
+    (temp_path, db)
+}
+
+#[test]
+fn ancestors_are_listed_at_smallest_depth() {
+    let (temp_path, db) = create_example();
+
+    let ancestors = db
+        .read_ancestors(
+            &"city".into(),
+            RelationshipTraversalParams::empty(),
+            Visibility::AuthorOnly,
+        )
+        .unwrap();
+    assert_eq!(
+        ancestors,
+        vec![
+            reached("duchy", 1),
+            reached("kingdom", 1),
+            reached("continent", 2),
+            reached("king", 2),
+        ]
+    );
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn descendants_are_listed_at_smallest_depth() {
+    let (temp_path, db) = create_example();
+
+    let descendants = db
+        .read_descendants(
+            &"kingdom".into(),
+            RelationshipTraversalParams::empty(),
+            Visibility::AuthorOnly,
+        )
+        .unwrap();
+    assert_eq!(
+        descendants,
+        vec![reached("city", 1), reached("duchy", 1), reached("guild", 2)]
+    );
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn traversal_honours_role_filter() {
+    let (temp_path, db) = create_example();
+
+    let params = RelationshipTraversalParams::new(vec!["contains".into()], None);
+    let ancestors = db
+        .read_ancestors(&"city".into(), params, Visibility::AuthorOnly)
+        .unwrap();
+    assert_eq!(
+        ancestors,
+        vec![
+            reached("duchy", 1),
+            reached("kingdom", 2),
+            reached("continent", 3),
+        ]
+    );
+
+    let params = RelationshipTraversalParams::new(vec!["capital".into(), "hosts".into()], None);
+    let descendants = db
+        .read_descendants(&"kingdom".into(), params, Visibility::AuthorOnly)
+        .unwrap();
+    assert_eq!(descendants, vec![reached("city", 1), reached("guild", 2)]);
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn traversal_honours_depth_limit() {
+    let (temp_path, db) = create_example();
+
+    let params = RelationshipTraversalParams::new(vec![], Some(1));
+    let descendants = db
+        .read_descendants(&"continent".into(), params, Visibility::AuthorOnly)
+        .unwrap();
+    assert_eq!(descendants, vec![reached("kingdom", 1)]);
+
+    let params = RelationshipTraversalParams::new(vec![], Some(0));
+    let descendants = db
+        .read_descendants(&"continent".into(), params, Visibility::AuthorOnly)
+        .unwrap();
+    assert!(descendants.is_empty());
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn traversal_of_unknown_label_is_empty() {
+    let (temp_path, db) = create_example();
+
+    let ancestors = db
+        .read_ancestors(
+            &"fununu".into(),
+            RelationshipTraversalParams::empty(),
+            Visibility::AuthorOnly,
+        )
+        .unwrap();
+    assert!(ancestors.is_empty());
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn traversal_terminates_on_cycles() {
+    let (temp_path, db) = create_example();
+    db.write_relationships(vec![EntityRelationship {
+        parent: "guild".into(),
+        child: "king".into(),
+        role: "employs".into(),
+        start: None,
+        end: None,
+    }])
+    .unwrap();
+
+    let descendants = db
+        .read_descendants(
+            &"king".into(),
+            RelationshipTraversalParams::empty(),
+            Visibility::AuthorOnly,
+        )
+        .unwrap();
+    assert_eq!(
+        descendants,
+        vec![
+            reached("kingdom", 1),
+            reached("city", 2),
+            reached("duchy", 2),
+            reached("guild", 3),
+            reached("king", 4),
+        ]
+    );
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn cycles_are_reported_as_label_chains() {
+    let (temp_path, db) = create_example();
+    assert!(db
+        .find_cycles(RelationshipTraversalParams::empty(), Visibility::AuthorOnly)
+        .unwrap()
+        .is_empty());
+
+    db.write_relationships(vec![EntityRelationship {
+        parent: "guild".into(),
+        child: "king".into(),
+        role: "employs".into(),
+        start: None,
+        end: None,
+    }])
+    .unwrap();
+
+    let cycles = db
+        .find_cycles(RelationshipTraversalParams::empty(), Visibility::AuthorOnly)
+        .unwrap();
+    assert_eq!(
+        cycles,
+        vec![
+            vec![
+                "city".into(),
+                "guild".into(),
+                "king".into(),
+                "kingdom".into(),
+                "city".into()
+            ],
+            vec![
+                "city".into(),
+                "guild".into(),
+                "king".into(),
+                "kingdom".into(),
+                "duchy".into(),
+                "city".into()
+            ]
+        ]
+    );
+
+    let params = RelationshipTraversalParams::new(vec!["contains".into(), "employs".into()], None);
+    assert!(db
+        .find_cycles(params, Visibility::AuthorOnly)
+        .unwrap()
+        .is_empty());
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn traversal_of_dense_cycles_is_bounded() {
+    let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+    let db = LoreDatabase::open(path_in.clone()).unwrap();
+
+    let labels: Vec<_> = (0..40).map(|i| format!("n{:02}", i)).collect();
+    let mut rels = Vec::new();
+    for parent in labels.iter() {
+        for child in labels.iter() {
+            rels.push(EntityRelationship::new(
+                parent.as_str().into(),
+                child.as_str().into(),
+                Role::NONE,
+            ));
+        }
+    }
+    db.write_relationships(rels).unwrap();
+
+    for max_depth in [None, Some(u32::MAX)] {
+        let descendants = db
+            .read_descendants(
+                &"n00".into(),
+                RelationshipTraversalParams::new(Vec::new(), max_depth),
+                Visibility::AuthorOnly,
+            )
+            .unwrap();
+        assert_eq!(descendants.len(), 40);
+        assert!(descendants.iter().all(|reached| reached.depth == 1));
+    }
+    let descendants = db
+        .read_descendants(
+            &"n00".into(),
+            RelationshipTraversalParams::new(Vec::new(), Some(0)),
+            Visibility::AuthorOnly,
+        )
+        .unwrap();
+    assert!(descendants.is_empty());
+
+    temp_path.close().unwrap();
+}
@@ -16,6 +16,9 @@ fn write_single_history_item() {
         timestamp: current_timestamp(),
         content: "testcontent".into(),
         properties: HistoryItemProperties::none(),
+        precision: DatePrecision::Exact,
+        sort_key: 0,
+        is_occurrence: false,
     };
     db.write_history_items(vec![item.clone()]).unwrap();
     let item_out = db
@@ -45,7 +48,10 @@ fn create_example() -> (tempfile::TempPath, LoreDatabase, Vec<HistoryItem>) {
                         day: day.clone(),
                         timestamp: current_timestamp(),
                         content: content.as_str().into(),
-                        properties: (&property.clone().unwrap_or_default()).into(),
+                        properties: (&property.clone().unwrap_or_default()).try_into().unwrap(),
+                        precision: DatePrecision::Exact,
+                        sort_key: 0,
+                        is_occurrence: false,
                     });
                 }
             }
@@ -291,6 +297,9 @@ fn test_write_read_history_after_db_deletion() {
         timestamp: current_timestamp(),
         content: "testcontent".into(),
         properties: HistoryItemProperties::none(),
+        precision: DatePrecision::Exact,
+        sort_key: 0,
+        is_occurrence: false,
     }]);
     assert!(
         write_result.is_err(),
@@ -343,6 +352,9 @@ fn test_setting_day_to_some() {
         timestamp: current_timestamp(),
         content: "testcontent".into(),
         properties: HistoryItemProperties::none(),
+        precision: DatePrecision::Exact,
+        sort_key: 0,
+        is_occurrence: false,
     };
 
     db.write_history_items(vec![item.clone()].clone()).unwrap();
@@ -382,6 +394,9 @@ fn test_setting_day_to_none() {
         timestamp: current_timestamp(),
         content: "testcontent".into(),
         properties: HistoryItemProperties::none(),
+        precision: DatePrecision::Exact,
+        sort_key: 0,
+        is_occurrence: false,
     };
 
     db.write_history_items(vec![item.clone()].clone()).unwrap();
@@ -454,7 +469,7 @@ fn test_change_history_item_content() {
 fn test_changing_history_item_properties_to_some() {
     let (temp_path, db, mut items) = create_example();
     let item = items.pop().unwrap();
-    let new_properties = "{\"is_secret\": false}".into();
+    let new_properties = "{\"is_secret\": false}".try_into().unwrap();
 
     db.change_history_item_properties(item.timestamp, &new_properties)
         .unwrap();
@@ -499,3 +514,227 @@ fn test_changing_history_item_properties_to_none() {
 
     temp_path.close().unwrap();
 }
+
+#[test]
+fn count_history_items_honours_search_params() {
+    let (temp_path, db, items) = create_example();
+
+    let count = db
+        .count_history_items(HistoryItemSearchParams::empty())
+        .unwrap();
+    assert_eq!(count, items.len());
+
+    let year = items[0].year;
+    let expected = items.iter().filter(|item| item.year == year).count();
+    let count = db
+        .count_history_items(HistoryItemSearchParams::new(Some(year), None, None, None))
+        .unwrap();
+    assert_eq!(count, expected);
+
+    let count = db
+        .count_history_items(HistoryItemSearchParams::new(
+            None,
+            None,
+            None,
+            Some(SqlSearchText::partial("content1")),
+        ))
+        .unwrap();
+    assert_eq!(count, items.len() / 2);
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn count_history_items_per_year() {
+    let (temp_path, db, _) = create_example();
+
+    let counts = db
+        .count_history_items_per_year(HistoryItemSearchParams::empty())
+        .unwrap();
+    assert_eq!(
+        counts,
+        vec![((-13).into(), 8), (0.into(), 8), (2021.into(), 8)]
+    );
+
+    let counts = db
+        .count_history_items_per_year(HistoryItemSearchParams::new(
+            None,
+            Some(Day::NONE),
+            None,
+            None,
+        ))
+        .unwrap();
+    assert_eq!(
+        counts,
+        vec![((-13).into(), 4), (0.into(), 4), (2021.into(), 4)]
+    );
+
+    temp_path.close().unwrap();
+}
+
+fn create_uncertain_example() -> (tempfile::TempPath, LoreDatabase) {
+    let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+    let db = LoreDatabase::open(path_in.clone()).unwrap();
+
+    let precisions = [
+        (1, 300, DatePrecision::Circa),
+        (2, 120, DatePrecision::Range(20)),
+        (3, 315, DatePrecision::Decade),
+        (4, 250, DatePrecision::Century),
+        (5, 200, DatePrecision::Exact),
+    ];
+    let items = precisions
+        .into_iter()
+        .map(|(timestamp, year, precision)| HistoryItem {
+            timestamp: timestamp.into(),
+            year: year.into(),
+            day: Day::NONE,
+            content: "Something happened.".into(),
+            properties: HistoryItemProperties::none(),
+            precision,
+            sort_key: 0,
+            is_occurrence: false,
+        })
+        .collect();
+    db.write_history_items(items).unwrap();
+    (temp_path, db)
+}
+
+fn timestamps(items: Vec<HistoryItem>) -> Vec<i64> {
+    items.iter().map(|item| item.timestamp.to_int()).collect()
+}
+
+#[test]
+fn uncertain_items_are_sorted_by_earliest_year() {
+    let (temp_path, db) = create_uncertain_example();
+
+    let items = db
+        .read_history_items(HistoryItemSearchParams::empty())
+        .unwrap();
+    assert_eq!(timestamps(items), vec![2, 5, 4, 1, 3]);
+    let counts = db
+        .count_history_items_per_year(HistoryItemSearchParams::empty())
+        .unwrap();
+    assert_eq!(
+        counts,
+        vec![
+            (120.into(), 1),
+            (200.into(), 2),
+            (300.into(), 1),
+            (310.into(), 1)
+        ]
+    );
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn uncertain_items_match_periods_they_overlap() {
+    let (temp_path, db) = create_uncertain_example();
+
+    let params = || HistoryItemSearchParams::empty().in_period(130.into(), 210.into());
+    let items = db.read_history_items(params()).unwrap();
+    assert_eq!(timestamps(items), vec![2, 5, 4]);
+    assert_eq!(db.count_history_items(params()).unwrap(), 3);
+
+    let items = db
+        .read_history_items(HistoryItemSearchParams::empty().in_period(319.into(), 400.into()))
+        .unwrap();
+    assert_eq!(timestamps(items), vec![3]);
+    assert_eq!(
+        db.count_history_items_per_year(params()).unwrap(),
+        vec![(120.into(), 1), (200.into(), 2)]
+    );
+
+    let long_range = HistoryItem::new(6.into(), 0.into(), Day::NONE, "An age passes.".into())
+        .with_precision(DatePrecision::Range(1000));
+    db.write_history_items(vec![long_range]).unwrap();
+    let items = db
+        .read_history_items(HistoryItemSearchParams::empty().in_period(900.into(), 950.into()))
+        .unwrap();
+    assert_eq!(timestamps(items), vec![6]);
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn change_history_item_precision() {
+    let (temp_path, db) = create_uncertain_example();
+
+    db.change_history_item_precision(5.into(), DatePrecision::Range(5))
+        .unwrap();
+    let item = db
+        .read_history_items(HistoryItemSearchParams::new(
+            None,
+            None,
+            Some(5.into()),
+            None,
+        ))
+        .unwrap()
+        .pop()
+        .unwrap();
+    assert_eq!(item.precision, DatePrecision::Range(5));
+    assert_eq!(item.latest_year(), 205.into());
+
+    let too_long = DatePrecision::Range(i32::MAX as u32 + 1);
+    assert!(db
+        .change_history_item_precision(5.into(), too_long)
+        .is_err());
+    let item = HistoryItem::new(6.into(), 0.into(), Day::NONE, "An age passes.".into())
+        .with_precision(too_long);
+    assert!(db.write_history_items(vec![item]).is_err());
+    db.change_history_item_precision(5.into(), DatePrecision::Range(i32::MAX as u32))
+        .unwrap();
+    let items = db
+        .read_history_items(HistoryItemSearchParams::empty().in_period(1000.into(), 1001.into()))
+        .unwrap();
+    assert_eq!(timestamps(items), vec![5]);
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn reorder_history_items_within_a_day() {
+    let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+    let db = LoreDatabase::open(path_in.clone()).unwrap();
+    let items = [(1, 29), (2, 29), (3, 29), (4, 30)]
+        .into_iter()
+        .map(|(timestamp, day)| HistoryItem {
+            timestamp: timestamp.into(),
+            year: 2021.into(),
+            day: day.into(),
+            content: "testcontent".into(),
+            properties: HistoryItemProperties::none(),
+            precision: DatePrecision::Exact,
+            sort_key: 0,
+            is_occurrence: false,
+        })
+        .collect();
+    db.write_history_items(items).unwrap();
+    let order = || {
+        timestamps(
+            db.read_history_items(HistoryItemSearchParams::empty())
+                .unwrap(),
+        )
+    };
+    assert_eq!(order(), vec![1, 2, 3, 4]);
+
+    db.move_history_item_before(3.into(), 1.into()).unwrap();
+    assert_eq!(order(), vec![3, 1, 2, 4]);
+    db.move_history_item_after(3.into(), 2.into()).unwrap();
+    assert_eq!(order(), vec![1, 2, 3, 4]);
+    db.move_history_item_after(1.into(), 2.into()).unwrap();
+    assert_eq!(order(), vec![2, 1, 3, 4]);
+
+    assert!(db.move_history_item_before(4.into(), 1.into()).is_err());
+    assert!(db.move_history_item_before(5.into(), 1.into()).is_err());
+    assert!(db.move_history_item_after(1.into(), 1.into()).is_err());
+    assert_eq!(order(), vec![2, 1, 3, 4]);
+
+    db.change_history_item_sort_key(2.into(), 10).unwrap();
+    assert_eq!(order(), vec![1, 3, 2, 4]);
+
+    temp_path.close().unwrap();
+}
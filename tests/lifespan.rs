@@ -0,0 +1,126 @@
+use lorecore::{sql::lore_database::LoreDatabase, types::*};
+use std::path::PathBuf;
+use tempfile::NamedTempFile;
+
+mod common;
+use common::*;
+
+fn create_example() -> (tempfile::TempPath, LoreDatabase) {
+    let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+    let db = LoreDatabase::open(path_in.clone()).unwrap();
+
+    db.write_history_items(vec![
+        item_with_properties(
+            1,
+            900,
+            100,
+            "Something happened.",
+            "{\"births\":[\"aragorn\"]}",
+        ),
+        item_with_properties(
+            2,
+            905,
+            0,
+            "Something happened.",
+            "{\"births\":[\"arwen\"],\"foundings\":[\"guild\"]}",
+        ),
+        item_with_properties(
+            3,
+            950,
+            50,
+            "Something happened.",
+            "{\"deaths\":[\"aragorn\"]}",
+        ),
+        item_with_properties(
+            4,
+            960,
+            0,
+            "Something happened.",
+            "{\"dissolutions\":[\"guild\"],\"deaths\":[\"nobody\"]}",
+        ),
+        item_with_properties(
+            5,
+            970,
+            0,
+            "Something happened.",
+            "{\"births\":[\"aragorn\"]}",
+        ),
+    ])
+    .unwrap();
+    (temp_path, db)
+}
+
+#[test]
+fn lifespans_are_derived_from_history_items() {
+    let (temp_path, db) = create_example();
+
+    let lifespans = db.read_lifespans(Visibility::AuthorOnly).unwrap();
+    assert_eq!(lifespans.len(), 4);
+    let aragorn = lifespans[&"aragorn".into()];
+    assert_eq!(
+        aragorn.start,
+        Some(LifeEvent {
+            kind: LifeEventKind::Birth,
+            year: 900.into(),
+            day: 100.into(),
+            timestamp: 1.into()
+        })
+    );
+    assert_eq!(aragorn.end.unwrap().kind, LifeEventKind::Death);
+    assert_eq!(aragorn.final_age(), Some(49));
+    assert_eq!(
+        lifespans[&"guild".into()].end.unwrap().kind,
+        LifeEventKind::Dissolution
+    );
+    assert_eq!(lifespans[&"nobody".into()].start, None);
+    assert_eq!(
+        db.read_lifespan(&"unknown".into(), Visibility::AuthorOnly)
+            .unwrap(),
+        Lifespan::default()
+    );
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn ages_are_computed() {
+    let (temp_path, db) = create_example();
+
+    let age = |label: &str, year: i32, day: u32| {
+        db.read_age(
+            &label.into(),
+            year.into(),
+            day.into(),
+            Visibility::AuthorOnly,
+        )
+        .unwrap()
+    };
+    assert_eq!(age("aragorn", 930, 99), Some(29));
+    assert_eq!(age("aragorn", 930, 100), Some(30));
+    assert_eq!(age("aragorn", 960, 0), None);
+    assert_eq!(age("guild", 930, 0), Some(25));
+    assert_eq!(age("nobody", 930, 0), None);
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn entities_existing_in_a_year() {
+    let (temp_path, db) = create_example();
+
+    let existing = |year: i32| -> Vec<String> {
+        db.read_entities_existing_at(year.into(), Visibility::AuthorOnly)
+            .unwrap()
+            .iter()
+            .map(|label| label.to_string())
+            .collect()
+    };
+    assert_eq!(existing(899), Vec::<String>::new());
+    assert_eq!(existing(903), vec!["aragorn"]);
+    assert_eq!(existing(950), vec!["aragorn", "arwen", "guild"]);
+    assert_eq!(existing(955), vec!["arwen", "guild"]);
+    assert_eq!(existing(961), vec!["arwen"]);
+
+    temp_path.close().unwrap();
+}
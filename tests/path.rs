@@ -0,0 +1,91 @@
+use lorecore::{
+    sql::{lore_database::LoreDatabase, search_params::PathSearchParams},
+    types::*,
+};
+use std::path::PathBuf;
+use tempfile::NamedTempFile;
+
+mod common;
+use common::*;
+
+fn create_example() -> (tempfile::TempPath, LoreDatabase) {
+    let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+    let db = LoreDatabase::open(path_in.clone()).unwrap();
+
+    db.write_relationships(vec![
+        rel("Denethor", "Boromir", "parent"),
+        rel("Denethor", "Faramir", "parent"),
+        rel("Denethor", "Gondor", "ruler"),
+        rel("Gondor", "Minas_Tirith", "capital"),
+        rel("Faramir", "Minas_Tirith", "defender"),
+    ])
+    .unwrap();
+    (temp_path, db)
+}
+
+#[test]
+fn directed_shortest_path() {
+    let (temp_path, db) = create_example();
+
+    let paths = db
+        .read_shortest_paths(
+            &"Denethor".into(),
+            &"Minas_Tirith".into(),
+            PathSearchParams::empty(),
+            Visibility::AuthorOnly,
+        )
+        .unwrap();
+    let explanations: Vec<_> = paths.iter().map(|path| path.explanation()).collect();
+    assert_eq!(
+        explanations,
+        vec![
+            "Denethor -[parent]-> Faramir -[defender]-> Minas_Tirith",
+            "Denethor -[ruler]-> Gondor -[capital]-> Minas_Tirith",
+        ]
+    );
+
+    let paths = db
+        .read_shortest_paths(
+            &"Boromir".into(),
+            &"Gondor".into(),
+            PathSearchParams::empty(),
+            Visibility::AuthorOnly,
+        )
+        .unwrap();
+    assert!(paths.is_empty());
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn undirected_shortest_path_with_excluded_role() {
+    let (temp_path, db) = create_example();
+
+    let params = PathSearchParams::new(true).excluding_role("ruler".into());
+    let paths = db
+        .read_shortest_paths(
+            &"Boromir".into(),
+            &"Gondor".into(),
+            params,
+            Visibility::AuthorOnly,
+        )
+        .unwrap();
+    assert_eq!(paths.len(), 1);
+    assert_eq!(paths[0].cost, 4);
+    assert_eq!(
+        paths[0].relationships,
+        vec![
+            rel("Denethor", "Boromir", "parent"),
+            rel("Denethor", "Faramir", "parent"),
+            rel("Faramir", "Minas_Tirith", "defender"),
+            rel("Gondor", "Minas_Tirith", "capital"),
+        ]
+    );
+    assert_eq!(
+        paths[0].explanation(),
+        "Boromir <-[parent]- Denethor -[parent]-> Faramir -[defender]-> Minas_Tirith <-[capital]- Gondor"
+    );
+
+    temp_path.close().unwrap();
+}
@@ -0,0 +1,164 @@
+use lorecore::{
+    sql::{lore_database::LoreDatabase, search_params::HistoryItemSearchParams},
+    types::*,
+};
+use std::path::PathBuf;
+use tempfile::NamedTempFile;
+
+mod common;
+use common::*;
+
+fn create_example() -> (tempfile::TempPath, LoreDatabase) {
+    let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+    let db = LoreDatabase::open(path_in.clone()).unwrap();
+
+    db.write_property_declarations(vec![
+        PropertyDeclaration::new("casualties", PropertyType::Integer),
+        PropertyDeclaration::new("weather", PropertyType::Text),
+    ])
+    .unwrap();
+    (temp_path, db)
+}
+
+#[test]
+fn declared_properties_can_be_written() {
+    let (temp_path, db) = create_example();
+    db.write_history_items(vec![item_with_properties(
+        1,
+        300,
+        1,
+        "The battle of the fords",
+        "{\"is_secret\":true,\"casualties\":300,\"weather\":\"rain\"}",
+    )])
+    .unwrap();
+    let items = db
+        .read_history_items(HistoryItemSearchParams::empty())
+        .unwrap();
+    assert!(items[0].properties.is_secret());
+    assert_eq!(
+        items[0].properties.get("casualties"),
+        Some(&serde_json::json!(300))
+    );
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn undeclared_or_mistyped_properties_are_rejected() {
+    let (temp_path, db) = create_example();
+    assert!(db
+        .write_history_items(vec![item_with_properties(
+            1,
+            300,
+            1,
+            "The battle of the fords",
+            "{\"mood\":\"grim\"}"
+        )])
+        .is_err());
+    assert!(db
+        .write_history_items(vec![item_with_properties(
+            1,
+            300,
+            1,
+            "The battle of the fords",
+            "{\"casualties\":\"many\"}"
+        )])
+        .is_err());
+    assert!(db
+        .write_history_items(vec![item_with_properties(
+            1,
+            300,
+            1,
+            "The battle of the fords",
+            "{\"is_secret\":1}"
+        )])
+        .is_err());
+    assert!(db
+        .read_history_items(HistoryItemSearchParams::empty())
+        .unwrap()
+        .is_empty());
+
+    db.write_history_items(vec![item_with_properties(
+        1,
+        300,
+        1,
+        "The battle of the fords",
+        "",
+    )])
+    .unwrap();
+    let mut properties = HistoryItemProperties::none();
+    properties.set("casualties", serde_json::json!(1.5));
+    assert!(db
+        .change_history_item_properties(1.into(), &properties)
+        .is_err());
+    properties.set("casualties", serde_json::json!(2));
+    properties.set_secret(true);
+    db.change_history_item_properties(1.into(), &properties)
+        .unwrap();
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn malformed_properties_cannot_be_parsed() {
+    assert!(HistoryItemProperties::try_from("testproperties").is_err());
+    assert!(HistoryItemProperties::try_from("[1, 2]").is_err());
+    assert_eq!(
+        HistoryItemProperties::try_from("").unwrap(),
+        HistoryItemProperties::none()
+    );
+}
+
+#[test]
+fn property_declarations_are_managed() {
+    let (temp_path, db) = create_example();
+    assert!(db
+        .write_property_declarations(vec![PropertyDeclaration::new(
+            "is_secret",
+            PropertyType::Text
+        )])
+        .is_err());
+    db.write_property_declarations(vec![PropertyDeclaration::new(
+        "weather",
+        PropertyType::TextList,
+    )])
+    .unwrap();
+    assert_eq!(
+        db.read_property_declarations().unwrap(),
+        vec![
+            PropertyDeclaration::new("casualties", PropertyType::Integer),
+            PropertyDeclaration::new("weather", PropertyType::TextList),
+        ]
+    );
+
+    db.write_history_items(vec![item_with_properties(
+        1,
+        300,
+        1,
+        "The battle of the fords",
+        "{\"casualties\":3}",
+    )])
+    .unwrap();
+    assert!(db.delete_property_declaration("casualties").is_err());
+    db.delete_property_declaration("weather").unwrap();
+    assert_eq!(db.read_property_declarations().unwrap().len(), 1);
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn typed_accessors() {
+    let mut properties = HistoryItemProperties::none();
+    assert!(!properties.is_secret());
+    assert!(properties.additional_concerns().is_empty());
+    properties.set_secret(true);
+    properties.set_additional_concerns(vec!["\\entityref{Gondor}".to_string()]);
+    assert!(properties.is_secret());
+    assert_eq!(
+        properties.additional_concerns(),
+        vec!["\\entityref{Gondor}".to_string()]
+    );
+    assert_eq!(
+        properties.remove("is_secret"),
+        Some(serde_json::Value::Bool(true))
+    );
+    assert!(!properties.is_secret());
+}
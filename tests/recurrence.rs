@@ -0,0 +1,131 @@
+use lorecore::{
+    sql::{
+        era::render_timeline, lore_database::LoreDatabase, search_params::HistoryItemSearchParams,
+    },
+    types::*,
+};
+use std::path::PathBuf;
+use tempfile::NamedTempFile;
+
+mod common;
+use common::*;
+
+fn create_example() -> (tempfile::TempPath, LoreDatabase) {
+    let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+    let db = LoreDatabase::open(path_in.clone()).unwrap();
+
+    db.write_history_items(vec![
+        item(1, 300, 12, "Midsummer festival"),
+        item(2, 301, 0, "Eclipse"),
+        item(3, 305, 0, "A war begins"),
+    ])
+    .unwrap();
+    db.write_recurrence(1.into(), Recurrence::new(1, Some(303.into())))
+        .unwrap();
+    db.write_recurrence(2.into(), Recurrence::new(7, None))
+        .unwrap();
+    (temp_path, db)
+}
+
+fn years_and_timestamps(items: &[HistoryItem]) -> Vec<(i32, i64)> {
+    items
+        .iter()
+        .map(|item| (item.year.to_int(), item.timestamp.to_int()))
+        .collect()
+}
+
+#[test]
+fn recurring_items_are_expanded_within_a_period() {
+    let (temp_path, db) = create_example();
+
+    let items = db
+        .read_history_items(HistoryItemSearchParams::empty())
+        .unwrap();
+    assert_eq!(items.len(), 3);
+
+    let params = || HistoryItemSearchParams::empty().in_period(302.into(), 315.into());
+    let items = db.read_history_items(params()).unwrap();
+    assert_eq!(
+        years_and_timestamps(&items),
+        vec![(302, 1), (303, 1), (305, 3), (308, 2), (315, 2)]
+    );
+    assert!(items[..2].iter().all(|item| item.day == 12.into()));
+    assert_eq!(db.count_history_items(params()).unwrap(), 5);
+
+    let occurrences: Vec<_> = items.iter().filter(|item| item.is_occurrence).collect();
+    assert_eq!(occurrences.len(), 4);
+    assert_ne!(items[0].occurrence_key(), items[1].occurrence_key());
+    assert!(db
+        .write_history_items(vec![occurrences[0].clone()])
+        .is_err());
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn reading_too_many_repetitions_fails() {
+    let (temp_path, db) = create_example();
+
+    let params =
+        |from: i32, to: i32| HistoryItemSearchParams::empty().in_period(from.into(), to.into());
+    assert!(db.read_history_items(params(i32::MIN, i32::MAX)).is_err());
+    assert!(db.count_history_items(params(i32::MIN, i32::MAX)).is_err());
+    let items = db.read_history_items(params(0, 300_000)).unwrap();
+    assert_eq!(items.len(), 3 + 3 + (300_000 - 301) / 7);
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn recurrences_can_be_changed_and_deleted() {
+    let (temp_path, db) = create_example();
+
+    assert!(db
+        .write_recurrence(3.into(), Recurrence::new(0, None))
+        .is_err());
+    assert!(db
+        .write_recurrence(9.into(), Recurrence::new(1, None))
+        .is_err());
+    assert!(db
+        .write_recurrence(2.into(), Recurrence::new(10, Some(300.into())))
+        .is_err());
+    db.write_recurrence(2.into(), Recurrence::new(10, Some(320.into())))
+        .unwrap();
+    assert_eq!(db.read_recurrences().unwrap().len(), 2);
+
+    db.delete_history_item(1.into()).unwrap();
+    db.delete_recurrence(2.into()).unwrap();
+    assert!(db.read_recurrences().unwrap().is_empty());
+    let items = db
+        .read_history_items(HistoryItemSearchParams::empty().in_period(0.into(), 1000.into()))
+        .unwrap();
+    assert_eq!(years_and_timestamps(&items), vec![(301, 2), (305, 3)]);
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn recurring_items_are_rendered_in_timelines() {
+    let (temp_path, db) = create_example();
+
+    let params = || HistoryItemSearchParams::empty().in_period(301.into(), 302.into());
+    let dated = db
+        .read_dated_history_items(params(), None, None, Markup::Markdown)
+        .unwrap();
+    assert_eq!(
+        render_timeline(&dated, Markup::Markdown),
+        "- **301**: Eclipse *(every 7 years)*\n\
+         - **day 12 of 301**: Midsummer festival *(every year until 303)*\n\
+         - **day 12 of 302**: Midsummer festival *(every year until 303)*"
+    );
+    let dated = db
+        .read_dated_history_items(params(), None, None, Markup::Latex)
+        .unwrap();
+    assert_eq!(
+        render_timeline(&dated[..1], Markup::Latex),
+        "\\begin{description}\n  \\item[301] Eclipse \\emph{(every 7 years)}\n\\end{description}"
+    );
+
+    temp_path.close().unwrap();
+}
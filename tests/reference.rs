@@ -0,0 +1,240 @@
+use lorecore::{
+    sql::{lore_database::LoreDatabase, validation::ReferenceSource},
+    types::*,
+};
+use std::path::PathBuf;
+use tempfile::NamedTempFile;
+
+mod common;
+use common::*;
+
+fn create_example() -> (tempfile::TempPath, LoreDatabase) {
+    let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+    let db = LoreDatabase::open(path_in.clone()).unwrap();
+
+    db.write_entity_columns(vec![
+        col("Aragorn", "father", "\\entityref{Arathorn}"),
+        col(
+            "Aragorn",
+            "home",
+            "\\entityref{Rivendell} and \\entityref{Gondor}",
+        ),
+        col("Arathorn", "home", "\\entityref{Rivendell}"),
+    ])
+    .unwrap();
+    let mut crowning = item(2, 300, 0, "\\entityref{Aragorn} is crowned.");
+    crowning
+        .properties
+        .set_additional_concerns(vec!["\\entityref{Gondor}".to_string()]);
+    db.write_history_items(vec![
+        item(1, 300, 0, "\\entityref{Arathorn} dies."),
+        crowning,
+    ])
+    .unwrap();
+    (temp_path, db)
+}
+
+fn timestamps(items: &[HistoryItem]) -> Vec<i64> {
+    items.iter().map(|item| item.timestamp.to_int()).collect()
+}
+
+#[test]
+fn references_are_indexed_on_write() {
+    let (temp_path, db) = create_example();
+    assert_eq!(
+        db.read_references_from_entity(&"Aragorn".into(), Visibility::AuthorOnly)
+            .unwrap(),
+        vec!["Arathorn".into(), "Gondor".into(), "Rivendell".into()]
+    );
+    assert_eq!(
+        db.read_references_to(&"Gondor".into(), Visibility::AuthorOnly)
+            .unwrap(),
+        vec![
+            ReferenceSource::EntityColumn("Aragorn".into(), "home".into()),
+            ReferenceSource::HistoryItem(2.into()),
+        ]
+    );
+    assert_eq!(
+        timestamps(
+            &db.read_history_items_referencing(&"Arathorn".into(), Visibility::AuthorOnly)
+                .unwrap()
+        ),
+        vec![1]
+    );
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn entity_changes_keep_the_index_in_sync() {
+    let (temp_path, db) = create_example();
+    db.change_entity_description(
+        (&"Aragorn".into(), &"home".into()),
+        &"\\entityref{Arnor}".into(),
+    )
+    .unwrap();
+    assert_eq!(
+        db.read_references_to(&"Rivendell".into(), Visibility::AuthorOnly)
+            .unwrap(),
+        vec![ReferenceSource::EntityColumn(
+            "Arathorn".into(),
+            "home".into()
+        )]
+    );
+
+    db.change_entity_descriptor((&"Aragorn".into(), "home".into()), &"realm".into())
+        .unwrap();
+    db.relabel_entity(&"Aragorn".into(), &"Elessar".into())
+        .unwrap();
+    assert_eq!(
+        db.read_references_to(&"Arnor".into(), Visibility::AuthorOnly)
+            .unwrap(),
+        vec![ReferenceSource::EntityColumn(
+            "Elessar".into(),
+            "realm".into()
+        )]
+    );
+    assert!(db
+        .read_references_from_entity(&"Aragorn".into(), Visibility::AuthorOnly)
+        .unwrap()
+        .is_empty());
+
+    db.delete_entity_column(("Elessar".into(), "realm".into()))
+        .unwrap();
+    assert!(db
+        .read_references_to(&"Arnor".into(), Visibility::AuthorOnly)
+        .unwrap()
+        .is_empty());
+    db.delete_entity("Elessar".into()).unwrap();
+    assert_eq!(
+        db.read_references_to(&"Arathorn".into(), Visibility::AuthorOnly)
+            .unwrap()
+            .len(),
+        1
+    );
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn history_changes_keep_the_index_in_sync() {
+    let (temp_path, db) = create_example();
+    db.change_history_item_content(1.into(), &"\\entityref{Gondor} mourns.".into())
+        .unwrap();
+    assert!(db
+        .read_history_items_referencing(&"Arathorn".into(), Visibility::AuthorOnly)
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        timestamps(
+            &db.read_history_items_referencing(&"Gondor".into(), Visibility::AuthorOnly)
+                .unwrap()
+        ),
+        vec![1, 2]
+    );
+
+    db.change_history_item_properties(2.into(), &HistoryItemProperties::none())
+        .unwrap();
+    db.delete_history_item(1.into()).unwrap();
+    assert!(db
+        .read_history_items_referencing(&"Gondor".into(), Visibility::AuthorOnly)
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        timestamps(
+            &db.read_history_items_referencing(&"Aragorn".into(), Visibility::AuthorOnly)
+                .unwrap()
+        ),
+        vec![2]
+    );
+
+    db.rebuild_entity_references().unwrap();
+    assert_eq!(
+        db.read_references_to(&"Aragorn".into(), Visibility::AuthorOnly)
+            .unwrap()
+            .len(),
+        1
+    );
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn index_is_rebuilt_despite_malformed_properties() {
+    let (temp_path, _) = create_example();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+
+    let connection = rusqlite::Connection::open(&path_in).unwrap();
+    connection
+        .execute_batch(
+            "UPDATE history_items SET properties = 'testproperties1' WHERE timestamp = 2;
+             DROP TABLE description_references;
+             DROP TABLE history_item_references;
+             DELETE FROM __diesel_schema_migrations WHERE version = '20261019230000';",
+        )
+        .unwrap();
+    drop(connection);
+
+    let db = LoreDatabase::open(path_in).unwrap();
+    assert_eq!(
+        db.read_references_to(&"Aragorn".into(), Visibility::AuthorOnly)
+            .unwrap(),
+        vec![ReferenceSource::HistoryItem(2.into())]
+    );
+    assert_eq!(
+        db.read_references_to(&"Gondor".into(), Visibility::AuthorOnly)
+            .unwrap(),
+        vec![ReferenceSource::EntityColumn(
+            "Aragorn".into(),
+            "home".into()
+        )]
+    );
+    drop(db);
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn failed_writes_leave_the_index_untouched() {
+    let (temp_path, db) = create_example();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+
+    db.change_entity_description(
+        (&"Boromir".into(), &"home".into()),
+        &"\\entityref{Gondor}".into(),
+    )
+    .unwrap();
+    assert!(db
+        .write_entity_columns(vec![
+            col("Faramir", "home", "\\entityref{Gondor}"),
+            col("Aragorn", "home", "\\entityref{Gondor}"),
+        ])
+        .is_err());
+    assert!(db
+        .write_history_items(vec![
+            item(3, 301, 0, "\\entityref{Gondor} mourns."),
+            item(1, 301, 0, "\\entityref{Gondor} rejoices."),
+        ])
+        .is_err());
+
+    assert_eq!(
+        db.read_references_to(&"Gondor".into(), Visibility::AuthorOnly)
+            .unwrap(),
+        vec![
+            ReferenceSource::EntityColumn("Aragorn".into(), "home".into()),
+            ReferenceSource::HistoryItem(2.into()),
+        ]
+    );
+    let connection = rusqlite::Connection::open(&path_in).unwrap();
+    let count = |table: &str| -> i64 {
+        connection
+            .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+                row.get(0)
+            })
+            .unwrap()
+    };
+    assert_eq!(count("entities"), 3);
+    assert_eq!(count("description_references"), 4);
+    assert_eq!(count("history_item_references"), 3);
+    drop(connection);
+
+    temp_path.close().unwrap();
+}
@@ -8,6 +8,9 @@ use lorecore::{
 use std::path::PathBuf;
 use tempfile::NamedTempFile;
 
+mod common;
+use common::*;
+
 #[test]
 fn write_single_relationship() {
     let temp_path = NamedTempFile::new().unwrap().into_temp_path();
@@ -17,6 +20,8 @@ fn write_single_relationship() {
         parent: "testparent".into(),
         child: "testchild".into(),
         role: "testrole".into(),
+        start: None,
+        end: None,
     };
     db.write_relationships(vec![rel.clone()]).unwrap();
     let rel_out = db
@@ -57,6 +62,8 @@ fn create_example() -> (tempfile::TempPath, LoreDatabase, Vec<EntityRelationship
                     parent: parent.as_str().into(),
                     child: child.as_str().into(),
                     role: role.clone().into(),
+                    start: None,
+                    end: None,
                 });
             }
         }
@@ -82,6 +89,8 @@ fn writing_several_roles_to_same_relationship() {
             parent: parent.as_str().into(),
             child: child.as_str().into(),
             role: role.clone().into(),
+            start: None,
+            end: None,
         });
     }
 
@@ -445,6 +454,8 @@ fn test_write_read_relationships_after_db_deletion() {
         parent: "testparent".into(),
         child: "testchild".into(),
         role: Role::NONE,
+        start: None,
+        end: None,
     }]);
     assert!(
         write_result.is_err(),
@@ -472,6 +483,8 @@ fn test_change_relationship_role_to_some() {
         parent: rel.parent,
         child: rel.child,
         role: new_role,
+        start: None,
+        end: None,
     }));
     temp_path.close().unwrap();
 }
@@ -490,6 +503,8 @@ fn test_change_relationship_role_to_none() {
         parent: rel.parent,
         child: rel.child,
         role: new_role,
+        start: None,
+        end: None,
     }));
     temp_path.close().unwrap();
 }
@@ -522,11 +537,15 @@ fn test_delete_relationship_without_role() {
         parent: parent.clone(),
         child: child.clone(),
         role,
+        start: None,
+        end: None,
     };
     let rel_without_role = EntityRelationship {
         parent,
         child,
         role: Role::NONE,
+        start: None,
+        end: None,
     };
 
     // Write the relationships to the database
@@ -564,11 +583,15 @@ fn test_delete_relationship_with_role() {
         parent: parent.clone(),
         child: child.clone(),
         role,
+        start: None,
+        end: None,
     };
     let rel_without_role = EntityRelationship {
         parent,
         child,
         role: Role::NONE,
+        start: None,
+        end: None,
     };
 
     // Write the relationships to the database
@@ -590,3 +613,252 @@ fn test_delete_relationship_with_role() {
     // Close the temporary path
     temp_path.close().unwrap();
 }
+
+#[test]
+fn count_relationships_honours_search_params() {
+    let (temp_path, db, rels) = create_example();
+
+    let count = db
+        .count_relationships(RelationshipSearchParams::empty())
+        .unwrap();
+    assert_eq!(count, rels.len());
+
+    let count = db
+        .count_relationships(RelationshipSearchParams::new(
+            Some(SqlSearchText::exact("testparent1")),
+            Some(SqlSearchText::partial("child2")),
+        ))
+        .unwrap();
+    assert_eq!(count, 2);
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn count_relationships_per_role() {
+    let (temp_path, db, _) = create_example();
+
+    let counts = db
+        .count_relationships_per_role(RelationshipSearchParams::empty())
+        .unwrap();
+    assert_eq!(counts, vec![(Role::NONE, 4), ("testrole".into(), 4)]);
+
+    let counts = db
+        .count_relationships_per_role(RelationshipSearchParams::new(
+            Some(SqlSearchText::exact("testparent1")),
+            None,
+        ))
+        .unwrap();
+    assert_eq!(counts, vec![(Role::NONE, 2), ("testrole".into(), 2)]);
+
+    temp_path.close().unwrap();
+}
+
+fn ruler(parent: &str, start: Option<i32>, end: Option<i32>) -> EntityRelationship {
+    EntityRelationship::new(parent.into(), "Osgiliath".into(), "ruler".into()).valid_between(
+        start.map(|year| (year.into(), Day::NONE)),
+        end.map(|year| (year.into(), 1.into())),
+    )
+}
+
+#[test]
+fn time_bounds_roundtrip() {
+    let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+    let db = LoreDatabase::open(path_in.clone()).unwrap();
+
+    let rels = vec![
+        ruler("Anarion", None, Some(2)),
+        ruler("Gondor", Some(2), None),
+        ruler("Rebels", Some(1437), Some(1447)),
+    ];
+    db.write_relationships(rels.clone()).unwrap();
+    let rels_out = db
+        .read_relationships(RelationshipSearchParams::empty())
+        .unwrap();
+    assert_eq!(rels_out, rels);
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn relationships_valid_at_year() {
+    let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+    let db = LoreDatabase::open(path_in.clone()).unwrap();
+
+    db.write_relationships(vec![
+        ruler("Anarion", None, Some(2)),
+        ruler("Gondor", Some(2), None),
+        ruler("Rebels", Some(1437), Some(1447)),
+    ])
+    .unwrap();
+    let parents_at = |year: i32| -> Vec<String> {
+        db.read_relationships(RelationshipSearchParams::empty().valid_at(year.into()))
+            .unwrap()
+            .into_iter()
+            .map(|rel| rel.parent.to_string())
+            .collect()
+    };
+    assert_eq!(parents_at(1), vec!["Anarion"]);
+    assert_eq!(parents_at(2), vec!["Anarion", "Gondor"]);
+    assert_eq!(parents_at(1440), vec!["Gondor", "Rebels"]);
+    assert_eq!(parents_at(3000), vec!["Gondor"]);
+
+    let count = db
+        .count_relationships(RelationshipSearchParams::empty().valid_at(1440.into()))
+        .unwrap();
+    assert_eq!(count, 2);
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn change_relationship_validity() {
+    let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+    let db = LoreDatabase::open(path_in.clone()).unwrap();
+
+    let rel = ruler("Gondor", None, None);
+    db.write_relationships(vec![rel.clone()]).unwrap();
+    db.change_relationship_validity(rel, Some((2.into(), 3.into())), None)
+        .unwrap();
+    let rels_out = db
+        .read_relationships(RelationshipSearchParams::empty())
+        .unwrap();
+    assert_eq!(rels_out[0].start, Some((2.into(), 3.into())));
+    assert_eq!(rels_out[0].end, None);
+
+    let rel = rels_out[0].clone();
+    assert!(db
+        .change_relationship_validity(
+            rel.clone(),
+            Some((2.into(), 3.into())),
+            Some((1.into(), Day::NONE))
+        )
+        .is_err());
+    assert!(db
+        .change_relationship_validity(rel, Some((2.into(), 3.into())), Some((2.into(), 2.into())))
+        .is_err());
+    assert!(db
+        .write_relationships(vec![ruler("Arnor", Some(3), Some(2))])
+        .is_err());
+    let rels_out = db
+        .read_relationships(RelationshipSearchParams::empty())
+        .unwrap();
+    assert_eq!(rels_out.len(), 1);
+    assert_eq!(rels_out[0].end, None);
+
+    temp_path.close().unwrap();
+}
+
+fn create_rewiring_example() -> (tempfile::TempPath, LoreDatabase) {
+    let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+    let db = LoreDatabase::open(path_in.clone()).unwrap();
+    db.write_relationships(vec![
+        rel("Strider", "Arwen", "spouse"),
+        rel("Arathorn", "Strider", "parent"),
+        rel("Aragorn", "Gondor", "ruler"),
+        rel("Denethor", "Gondor", "steward"),
+    ])
+    .unwrap();
+    (temp_path, db)
+}
+
+#[test]
+fn change_relationship_parent_and_child() {
+    let (temp_path, db) = create_rewiring_example();
+
+    db.change_relationship_parent(rel("Strider", "Arwen", "spouse"), &"Aragorn".into())
+        .unwrap();
+    db.change_relationship_child(rel("Arathorn", "Strider", "parent"), &"Aragorn".into())
+        .unwrap();
+    let rels = db
+        .read_relationships(RelationshipSearchParams::empty())
+        .unwrap();
+    assert!(rels.contains(&rel("Aragorn", "Arwen", "spouse")));
+    assert!(rels.contains(&rel("Arathorn", "Aragorn", "parent")));
+    assert_eq!(rels.len(), 4);
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn change_relationship_role_reports_collision() {
+    let (temp_path, db) = create_rewiring_example();
+
+    db.write_relationships(vec![rel("Aragorn", "Gondor", "steward")])
+        .unwrap();
+    let result = db.change_relationship_role(rel("Aragorn", "Gondor", "ruler"), &"steward".into());
+    assert!(result.is_err());
+    let result =
+        db.change_relationship_parent(rel("Denethor", "Gondor", "steward"), &"Aragorn".into());
+    assert!(result.is_err());
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn move_relationships_between_labels() {
+    let (temp_path, db) = create_rewiring_example();
+
+    let moved = db
+        .move_relationships(&"Strider".into(), &"Aragorn".into())
+        .unwrap();
+    assert_eq!(moved, 2);
+    let rels = db
+        .read_relationships(RelationshipSearchParams::empty())
+        .unwrap();
+    assert_eq!(
+        rels,
+        vec![
+            rel("Aragorn", "Arwen", "spouse"),
+            rel("Aragorn", "Gondor", "ruler"),
+            rel("Arathorn", "Aragorn", "parent"),
+            rel("Denethor", "Gondor", "steward"),
+        ]
+    );
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn move_relationships_reports_collisions_and_changes_nothing() {
+    let (temp_path, db) = create_rewiring_example();
+
+    db.write_relationships(vec![rel("Aragorn", "Arwen", "spouse")])
+        .unwrap();
+    let result = db.move_relationships(&"Strider".into(), &"Aragorn".into());
+    match result {
+        Err(e) => assert!(e.to_string().contains("would duplicate")),
+        Ok(_) => panic!("Moving relationships should collide"),
+    }
+    let rels = db
+        .read_relationships(RelationshipSearchParams::empty())
+        .unwrap();
+    assert!(rels.contains(&rel("Arathorn", "Strider", "parent")));
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn rename_role_in_all_relationships() {
+    let (temp_path, db) = create_rewiring_example();
+
+    db.write_relationships(vec![rel("Elessar", "Gondor", "ruler")])
+        .unwrap();
+    db.rename_role(&"ruler".into(), &"king".into()).unwrap();
+    let count = db
+        .count_relationships_per_role(RelationshipSearchParams::empty())
+        .unwrap();
+    assert!(count.contains(&("king".into(), 2)));
+    assert!(!count.iter().any(|(role, _)| role == &"ruler".into()));
+
+    db.write_relationships(vec![rel("Elessar", "Gondor", "steward")])
+        .unwrap();
+    let result = db.rename_role(&"king".into(), &"steward".into());
+    assert!(result.is_err());
+
+    temp_path.close().unwrap();
+}
@@ -0,0 +1,180 @@
+use lorecore::{
+    errors::LoreCoreError,
+    sql::{lore_database::LoreDatabase, search_params::RelationshipSearchParams},
+    types::*,
+};
+use std::path::PathBuf;
+use tempfile::NamedTempFile;
+
+mod common;
+use common::*;
+
+fn create_example() -> (tempfile::TempPath, LoreDatabase) {
+    let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+    let db = LoreDatabase::open(path_in.clone()).unwrap();
+
+    db.write_entity_columns(vec![
+        col("Gondor", "type", "realm"),
+        col("Minas_Tirith", "type", "place"),
+        col("Osgiliath", "type", "place"),
+        col("Denethor", "type", "person"),
+        col("Boromir", "type", "person"),
+    ])
+    .unwrap();
+    let mut constraints = RoleConstraint::one_to_one("capital".into());
+    constraints.push(RoleConstraint::max_per_child("parent".into(), 2));
+    constraints.push(RoleConstraint::child_kind(
+        "located in".into(),
+        "type".into(),
+        "realm".into(),
+    ));
+    db.write_role_constraints(constraints).unwrap();
+    (temp_path, db)
+}
+
+#[test]
+fn role_constraints_roundtrip() {
+    let (temp_path, db) = create_example();
+
+    let constraints = db.read_role_constraints().unwrap();
+    assert_eq!(constraints.len(), 4);
+    assert!(constraints.contains(&RoleConstraint::max_per_child("parent".into(), 2)));
+
+    db.write_role_constraints(vec![RoleConstraint::max_per_child("parent".into(), 3)])
+        .unwrap();
+    let constraints = db.read_role_constraints().unwrap();
+    assert_eq!(constraints.len(), 4);
+    assert!(constraints.contains(&RoleConstraint::max_per_child("parent".into(), 3)));
+
+    db.delete_role_constraint(RoleConstraint::max_per_child("parent".into(), 3))
+        .unwrap();
+    assert_eq!(db.read_role_constraints().unwrap().len(), 3);
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn violating_relationships_are_not_written() {
+    let (temp_path, db) = create_example();
+
+    db.write_relationships(vec![rel("Gondor", "Minas_Tirith", "capital")])
+        .unwrap();
+    let result = db.write_relationships(vec![
+        rel("Minas_Tirith", "Gondor", "located in"),
+        rel("Gondor", "Osgiliath", "capital"),
+    ]);
+    match &result {
+        Err(LoreCoreError::ConstraintError(violations)) => assert_eq!(
+            violations,
+            &vec![ConstraintViolation::TooManyPerParent {
+                parent: "Gondor".into(),
+                role: "capital".into(),
+                count: 2,
+                max: 1
+            }]
+        ),
+        _ => panic!("Expected a constraint error, got {:?}", result),
+    }
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Role constraints violated: \"Gondor\" is the parent of 2 relationships with role \"capital\" at the same time, but at most 1 are allowed."
+    );
+    let rels = db
+        .read_relationships(RelationshipSearchParams::empty())
+        .unwrap();
+    assert_eq!(rels, vec![rel("Gondor", "Minas_Tirith", "capital")]);
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn changed_roles_are_checked() {
+    let (temp_path, db) = create_example();
+
+    db.write_relationships(vec![rel("Boromir", "Denethor", "located in")])
+        .unwrap_err();
+    db.write_relationships(vec![rel("Denethor", "Boromir", "father")])
+        .unwrap();
+    let result =
+        db.change_relationship_role(rel("Denethor", "Boromir", "father"), &"located in".into());
+    assert!(matches!(result, Err(LoreCoreError::ConstraintError(_))));
+    db.change_relationship_role(rel("Denethor", "Boromir", "father"), &"parent".into())
+        .unwrap();
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn bulk_validation_finds_violations_of_new_constraints() {
+    let (temp_path, db) = create_example();
+
+    db.write_relationships(vec![
+        rel("Denethor", "Boromir", "parent"),
+        rel("Minas_Tirith", "Gondor", "located in"),
+        rel("Osgiliath", "Minas_Tirith", "near"),
+    ])
+    .unwrap();
+    assert!(db.find_constraint_violations().unwrap().is_empty());
+
+    db.write_role_constraints(vec![RoleConstraint::child_kind(
+        "near".into(),
+        "type".into(),
+        "realm".into(),
+    )])
+    .unwrap();
+    assert_eq!(
+        db.find_constraint_violations().unwrap(),
+        vec![ConstraintViolation::WrongChildKind {
+            relationship: rel("Osgiliath", "Minas_Tirith", "near"),
+            allowed: vec![("type".into(), "realm".into())],
+        }]
+    );
+
+    db.write_role_constraints(vec![RoleConstraint::parent_kind(
+        "parent".into(),
+        "type".into(),
+        "realm".into(),
+    )])
+    .unwrap();
+    let violations = db.find_constraint_violations().unwrap();
+    assert_eq!(violations.len(), 2);
+    assert!(violations.contains(&ConstraintViolation::WrongParentKind {
+        relationship: rel("Denethor", "Boromir", "parent"),
+        allowed: vec![("type".into(), "realm".into())],
+    }));
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn maxima_only_count_relationships_valid_at_the_same_time() {
+    let (temp_path, db) = create_example();
+
+    let capital = |child: &str, start: Option<i32>, end: Option<i32>| {
+        rel("Gondor", child, "capital").valid_between(
+            start.map(|year| (year.into(), Day::NONE)),
+            end.map(|year| (year.into(), Day::NONE)),
+        )
+    };
+    db.write_relationships(vec![
+        capital("Osgiliath", None, Some(1640)),
+        capital("Minas_Tirith", Some(1640), None),
+    ])
+    .unwrap_err();
+    db.write_relationships(vec![
+        capital("Osgiliath", None, Some(1639)),
+        capital("Minas_Tirith", Some(1640), None),
+    ])
+    .unwrap();
+    assert!(db.find_constraint_violations().unwrap().is_empty());
+
+    let result = db.change_relationship_validity(
+        capital("Osgiliath", None, Some(1639)),
+        None,
+        Some((1700.into(), Day::NONE)),
+    );
+    assert!(matches!(result, Err(LoreCoreError::ConstraintError(_))));
+
+    temp_path.close().unwrap();
+}
@@ -0,0 +1,197 @@
+use lorecore::{
+    sql::{
+        lore_database::LoreDatabase,
+        search_params::{RelationshipSearchParams, SqlSearchText},
+    },
+    types::*,
+};
+use std::path::PathBuf;
+use tempfile::NamedTempFile;
+
+mod common;
+use common::*;
+
+fn create_example() -> (tempfile::TempPath, LoreDatabase) {
+    let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+    let db = LoreDatabase::open(path_in.clone()).unwrap();
+
+    db.write_role_declarations(vec![
+        RoleDeclaration::with_inverse("liege".into(), "vassal".into()),
+        RoleDeclaration::symmetric("sibling".into()),
+        RoleDeclaration::plain("capital".into()),
+    ])
+    .unwrap();
+    (temp_path, db)
+}
+
+#[test]
+fn role_declarations_roundtrip() {
+    let (temp_path, db) = create_example();
+
+    let declarations = db.read_role_declarations().unwrap();
+    assert_eq!(
+        declarations,
+        vec![
+            RoleDeclaration::plain("capital".into()),
+            RoleDeclaration::with_inverse("liege".into(), "vassal".into()),
+            RoleDeclaration::symmetric("sibling".into()),
+        ]
+    );
+    assert!(declarations[2].is_symmetric());
+
+    db.delete_role_declaration(&"capital".into()).unwrap();
+    assert_eq!(db.read_role_declarations().unwrap().len(), 2);
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn contradicting_role_declarations_are_rejected() {
+    let (temp_path, db) = create_example();
+
+    let result = db.write_role_declarations(vec![RoleDeclaration::with_inverse(
+        "serf".into(),
+        "liege".into(),
+    )]);
+    assert!(result.is_err());
+    assert_eq!(db.read_role_declarations().unwrap().len(), 3);
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn role_strictness_defaults_to_lenient() {
+    let (temp_path, db) = create_example();
+    assert_eq!(db.read_role_strictness().unwrap(), RoleStrictness::Lenient);
+
+    db.write_relationships(vec![rel("king", "kingdom", "ruler")])
+        .unwrap();
+    assert!(db.find_undeclared_roles().unwrap().is_empty());
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn undeclared_roles_are_reported_when_warning() {
+    let (temp_path, db) = create_example();
+    db.set_role_strictness(RoleStrictness::Warn).unwrap();
+    assert_eq!(db.read_role_strictness().unwrap(), RoleStrictness::Warn);
+
+    db.write_relationships(vec![
+        rel("king", "duke", "liege"),
+        rel("king", "kingdom", "ruler"),
+        rel("king", "queen", ""),
+    ])
+    .unwrap();
+    let undeclared = db.find_undeclared_roles().unwrap();
+    assert_eq!(undeclared.len(), 1);
+    assert_eq!(undeclared[0].relationship, rel("king", "kingdom", "ruler"));
+    assert_eq!(
+        db.count_relationships(RelationshipSearchParams::empty())
+            .unwrap(),
+        3
+    );
+
+    db.change_relationship_role(rel("king", "duke", "liege"), &"lige".into())
+        .unwrap();
+    let undeclared = db.find_undeclared_roles().unwrap();
+    assert_eq!(undeclared.len(), 2);
+    assert_eq!(undeclared[0].relationship, rel("king", "duke", "lige"));
+    assert_eq!(undeclared[0].suggestions, vec!["liege".into()]);
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn undeclared_roles_are_rejected_when_strict() {
+    let (temp_path, db) = create_example();
+    db.set_role_strictness(RoleStrictness::Strict).unwrap();
+
+    let result = db.write_relationships(vec![
+        rel("king", "duke", "liege"),
+        rel("king", "kingdom", "ruler"),
+    ]);
+    assert!(result.is_err());
+    assert_eq!(
+        db.count_relationships(RelationshipSearchParams::empty())
+            .unwrap(),
+        0
+    );
+
+    db.write_relationships(vec![rel("king", "duke", "liege")])
+        .unwrap();
+    let result = db.change_relationship_role(rel("king", "duke", "liege"), &"ruler".into());
+    assert!(result.is_err());
+    assert_eq!(
+        db.read_relationships(RelationshipSearchParams::empty())
+            .unwrap(),
+        vec![rel("king", "duke", "liege")]
+    );
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn inverse_relationships_are_derived_on_request() {
+    let (temp_path, db) = create_example();
+    db.write_relationships(vec![
+        rel("king", "duke", "liege"),
+        rel("duke", "duchess", "sibling"),
+        rel("duchess", "duke", "sibling"),
+        rel("kingdom", "city", "capital"),
+    ])
+    .unwrap();
+
+    let stored = db
+        .read_relationships(RelationshipSearchParams::empty())
+        .unwrap();
+    assert_eq!(stored.len(), 4);
+
+    let all = db
+        .read_relationships(RelationshipSearchParams::empty().including_inverses())
+        .unwrap();
+    assert_eq!(
+        all,
+        vec![
+            rel("duchess", "duke", "sibling"),
+            rel("duke", "duchess", "sibling"),
+            rel("duke", "king", "vassal"),
+            rel("king", "duke", "liege"),
+            rel("kingdom", "city", "capital"),
+        ]
+    );
+
+    let duke_as_parent = db
+        .read_relationships(
+            RelationshipSearchParams::new(Some(SqlSearchText::exact("duke")), None)
+                .including_inverses(),
+        )
+        .unwrap();
+    assert_eq!(
+        duke_as_parent,
+        vec![
+            rel("duke", "duchess", "sibling"),
+            rel("duke", "king", "vassal"),
+        ]
+    );
+
+    let count = db
+        .count_relationships(RelationshipSearchParams::empty().including_inverses())
+        .unwrap();
+    assert_eq!(count, 5);
+    let counts = db
+        .count_relationships_per_role(RelationshipSearchParams::empty().including_inverses())
+        .unwrap();
+    assert_eq!(
+        counts,
+        vec![
+            ("capital".into(), 1),
+            ("liege".into(), 1),
+            ("sibling".into(), 2),
+            ("vassal".into(), 1),
+        ]
+    );
+
+    temp_path.close().unwrap();
+}
@@ -0,0 +1,285 @@
+use lorecore::{
+    sql::{
+        lore_database::LoreDatabase,
+        search_params::{EntityColumnSearchParams, HistoryItemSearchParams, SqlSearchText},
+    },
+    types::*,
+};
+use std::path::PathBuf;
+use tempfile::NamedTempFile;
+
+mod common;
+use common::*;
+
+fn tag(text: &str) -> Tag {
+    Tag::try_from(text).unwrap()
+}
+
+fn tags(texts: &[&str]) -> Vec<Tag> {
+    texts.iter().map(|text| tag(text)).collect()
+}
+
+fn create_example() -> (tempfile::TempPath, LoreDatabase) {
+    let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+    let db = LoreDatabase::open(path_in.clone()).unwrap();
+
+    db.write_entity_columns(vec![
+        col("Admiral", "rank", "Admiral of the fleet"),
+        col("Fortress", "location", "On the coast"),
+        col("Merchant", "trade", "Spices"),
+    ])
+    .unwrap();
+    db.write_history_items(vec![
+        item(1, 300, 0, "A sea battle"),
+        item(2, 301, 0, "A siege"),
+        item(3, 302, 0, "A market opens"),
+    ])
+    .unwrap();
+
+    db.write_entity_tags(&"Admiral".into(), tags(&["war/naval", "person"]))
+        .unwrap();
+    db.write_entity_tags(&"Fortress".into(), tags(&["war/land"]))
+        .unwrap();
+    db.write_entity_tags(&"Merchant".into(), tags(&["person", "warfare"]))
+        .unwrap();
+    db.write_history_item_tags(1.into(), tags(&["war/naval"]))
+        .unwrap();
+    db.write_history_item_tags(2.into(), tags(&["war/land", "war"]))
+        .unwrap();
+    db.write_history_item_tags(3.into(), tags(&["trade"]))
+        .unwrap();
+    (temp_path, db)
+}
+
+fn labels(cols: &[EntityColumn]) -> Vec<String> {
+    cols.iter().map(|col| col.label.to_string()).collect()
+}
+
+fn timestamps(items: &[HistoryItem]) -> Vec<i64> {
+    items.iter().map(|item| item.timestamp.to_int()).collect()
+}
+
+#[test]
+fn tags_can_be_read_and_deleted() {
+    let (temp_path, db) = create_example();
+    assert_eq!(
+        db.read_entity_tags(&"Admiral".into(), Visibility::AuthorOnly)
+            .unwrap(),
+        tags(&["person", "war/naval"])
+    );
+    assert_eq!(
+        db.read_history_item_tags(2.into(), Visibility::AuthorOnly)
+            .unwrap(),
+        tags(&["war", "war/land"])
+    );
+    assert_eq!(
+        db.read_tags(Visibility::AuthorOnly).unwrap(),
+        vec![
+            (tag("person"), 2),
+            (tag("trade"), 1),
+            (tag("war"), 1),
+            (tag("war/land"), 2),
+            (tag("war/naval"), 2),
+            (tag("warfare"), 1),
+        ]
+    );
+
+    db.write_entity_tags(&"Admiral".into(), tags(&["person"]))
+        .unwrap();
+    db.delete_entity_tag(&"Admiral".into(), &tag("person"))
+        .unwrap();
+    db.delete_history_item_tag(2.into(), &tag("war")).unwrap();
+    assert_eq!(
+        db.read_entity_tags(&"Admiral".into(), Visibility::AuthorOnly)
+            .unwrap(),
+        tags(&["war/naval"])
+    );
+    assert_eq!(
+        db.read_history_item_tags(2.into(), Visibility::AuthorOnly)
+            .unwrap(),
+        tags(&["war/land"])
+    );
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn tagging_missing_targets_fails() {
+    let (temp_path, db) = create_example();
+    assert!(db
+        .write_entity_tags(&"Nobody".into(), tags(&["person"]))
+        .is_err());
+    assert!(db
+        .write_history_item_tags(99.into(), tags(&["war"]))
+        .is_err());
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn tags_combine_with_search_params() {
+    let (temp_path, db) = create_example();
+    let cols = db
+        .read_entity_columns(EntityColumnSearchParams::empty().with_tag(tag("war")))
+        .unwrap();
+    assert_eq!(labels(&cols), vec!["Admiral", "Fortress"]);
+    let params = EntityColumnSearchParams::new(Some(SqlSearchText::partial("Mer")), None)
+        .with_tag(tag("person"));
+    assert_eq!(
+        labels(&db.read_entity_columns(params).unwrap()),
+        vec!["Merchant"]
+    );
+    assert_eq!(
+        db.count_entity_columns(EntityColumnSearchParams::empty().with_tag(tag("war/naval")))
+            .unwrap(),
+        1
+    );
+
+    let items = db
+        .read_history_items(HistoryItemSearchParams::empty().with_tag(tag("war")))
+        .unwrap();
+    assert_eq!(timestamps(&items), vec![1, 2]);
+    let items = db
+        .read_history_items(
+            HistoryItemSearchParams::new(Some(301.into()), None, None, None).with_tag(tag("war")),
+        )
+        .unwrap();
+    assert_eq!(timestamps(&items), vec![2]);
+    assert!(db
+        .read_history_items(HistoryItemSearchParams::empty().with_tag(tag("War")))
+        .unwrap()
+        .is_empty());
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn renaming_and_deleting_includes_nested_tags() {
+    let (temp_path, db) = create_example();
+    db.rename_tag(&tag("war"), &tag("conflict")).unwrap();
+    assert_eq!(
+        db.read_entity_tags(&"Admiral".into(), Visibility::AuthorOnly)
+            .unwrap(),
+        tags(&["conflict/naval", "person"])
+    );
+    assert_eq!(
+        db.read_history_item_tags(2.into(), Visibility::AuthorOnly)
+            .unwrap(),
+        tags(&["conflict", "conflict/land"])
+    );
+    assert_eq!(
+        db.read_entity_tags(&"Merchant".into(), Visibility::AuthorOnly)
+            .unwrap(),
+        tags(&["person", "warfare"])
+    );
+
+    db.delete_tag(&tag("conflict")).unwrap();
+    assert!(db
+        .read_history_item_tags(1.into(), Visibility::AuthorOnly)
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        db.read_entity_tags(&"Admiral".into(), Visibility::AuthorOnly)
+            .unwrap(),
+        tags(&["person"])
+    );
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn tags_follow_entities_and_history_items() {
+    let (temp_path, db) = create_example();
+    db.relabel_entity(&"Admiral".into(), &"Grand Admiral".into())
+        .unwrap();
+    assert_eq!(
+        db.read_entity_tags(&"Grand Admiral".into(), Visibility::AuthorOnly)
+            .unwrap(),
+        tags(&["person", "war/naval"])
+    );
+    db.delete_entity("Grand Admiral".into()).unwrap();
+    db.delete_history_item(1.into()).unwrap();
+    assert_eq!(
+        db.read_tags(Visibility::AuthorOnly).unwrap(),
+        vec![
+            (tag("person"), 1),
+            (tag("trade"), 1),
+            (tag("war"), 1),
+            (tag("war/land"), 2),
+            (tag("warfare"), 1),
+        ]
+    );
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn failed_relabeling_changes_nothing() {
+    let (temp_path, db) = create_example();
+    assert!(db
+        .relabel_entity(&"Admiral".into(), &"Merchant".into())
+        .is_err());
+    assert_eq!(
+        db.read_entity_tags(&"Admiral".into(), Visibility::AuthorOnly)
+            .unwrap(),
+        tags(&["person", "war/naval"])
+    );
+    assert_eq!(
+        labels(
+            &db.read_entity_columns(EntityColumnSearchParams::new(
+                Some(SqlSearchText::exact("Admiral")),
+                None
+            ))
+            .unwrap()
+        ),
+        vec!["Admiral"]
+    );
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn descriptors_can_be_converted_to_tags() {
+    let (temp_path, db) = create_example();
+    db.write_entity_columns(vec![
+        col("Fortress", "tags", "war/siege, ruin"),
+        col("Merchant", "tags", "trade,"),
+        col("Rumour", "tags", "gossip"),
+    ])
+    .unwrap();
+    assert_eq!(db.convert_descriptor_to_tags(&"tags".into()).unwrap(), 2);
+    assert!(db
+        .read_entity_tags(&"Rumour".into(), Visibility::AuthorOnly)
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        labels(
+            &db.read_entity_columns(EntityColumnSearchParams::new(
+                None,
+                Some(SqlSearchText::exact("tags"))
+            ))
+            .unwrap()
+        ),
+        vec!["Rumour"]
+    );
+    db.delete_entity("Rumour".into()).unwrap();
+    assert_eq!(
+        db.read_entity_tags(&"Fortress".into(), Visibility::AuthorOnly)
+            .unwrap(),
+        tags(&["ruin", "war/land", "war/siege"])
+    );
+    assert_eq!(
+        db.count_entity_columns(EntityColumnSearchParams::new(
+            None,
+            Some(SqlSearchText::exact("tags"))
+        ))
+        .unwrap(),
+        0
+    );
+
+    db.write_entity_columns(vec![col("Merchant", "tags", "a//b")])
+        .unwrap();
+    assert!(db.convert_descriptor_to_tags(&"tags".into()).is_err());
+    assert_eq!(
+        db.read_entity_tags(&"Merchant".into(), Visibility::AuthorOnly)
+            .unwrap()
+            .len(),
+        3
+    );
+    temp_path.close().unwrap();
+}
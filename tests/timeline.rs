@@ -0,0 +1,103 @@
+use lorecore::{
+    sql::{
+        lore_database::LoreDatabase, search_params::RelationshipTraversalParams,
+        timeline::TimelineReason,
+    },
+    types::*,
+};
+use std::path::PathBuf;
+use tempfile::NamedTempFile;
+
+mod common;
+use common::*;
+
+fn create_example() -> (tempfile::TempPath, LoreDatabase) {
+    let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+    let db = LoreDatabase::open(path_in.clone()).unwrap();
+
+    db.write_relationships(vec![
+        EntityRelationship::new("Fellowship".into(), "Frodo".into(), "member".into()),
+        EntityRelationship::new("Fellowship".into(), "Sam".into(), "member".into()),
+        EntityRelationship::new("Frodo".into(), "Bilbo".into(), "heir".into()),
+    ])
+    .unwrap();
+    db.write_history_items(vec![
+        item(1, 3018, 0, "The \\entityref{Fellowship} is formed."),
+        item(2, 2890, 0, "\\entityref{Bilbo} is born."),
+        item(
+            3,
+            3019,
+            0,
+            "\\entityref{Frodo} and \\entityref{Sam} reach Mount Doom.",
+        ),
+        item(4, 3001, 0, "\\entityref{Frodo} inherits Bag End."),
+        item(5, 3010, 0, "Nothing happens."),
+    ])
+    .unwrap();
+    (temp_path, db)
+}
+
+fn timestamps(entries: &[lorecore::sql::timeline::TimelineEntry]) -> Vec<i64> {
+    entries
+        .iter()
+        .map(|entry| entry.item.timestamp.to_int())
+        .collect()
+}
+
+#[test]
+fn timeline_of_mentions_only() {
+    let (temp_path, db) = create_example();
+    let timeline = db
+        .read_timeline(
+            &"Frodo".into(),
+            RelationshipTraversalParams::new(vec![], Some(0)),
+            Visibility::AuthorOnly,
+        )
+        .unwrap();
+    assert_eq!(timestamps(&timeline), vec![4, 3]);
+    assert!(timeline
+        .iter()
+        .all(|entry| entry.reasons == vec![TimelineReason::Mentioned]));
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn timeline_includes_members() {
+    let (temp_path, db) = create_example();
+    let timeline = db
+        .read_timeline(
+            &"Fellowship".into(),
+            RelationshipTraversalParams::new(vec!["member".into()], None),
+            Visibility::AuthorOnly,
+        )
+        .unwrap();
+    assert_eq!(timestamps(&timeline), vec![4, 1, 3]);
+    assert_eq!(timeline[1].reasons, vec![TimelineReason::Mentioned]);
+    let reasons: Vec<_> = timeline[2]
+        .reasons
+        .iter()
+        .map(|reason| reason.to_string())
+        .collect();
+    assert_eq!(
+        reasons,
+        vec![
+            "mentions Frodo (Fellowship -[member]-> Frodo)",
+            "mentions Sam (Fellowship -[member]-> Sam)"
+        ]
+    );
+
+    let timeline = db
+        .read_timeline(
+            &"Sam".into(),
+            RelationshipTraversalParams::empty(),
+            Visibility::AuthorOnly,
+        )
+        .unwrap();
+    assert_eq!(timestamps(&timeline), vec![2, 4, 1, 3]);
+    assert_eq!(
+        timeline[0].reasons[0].to_string(),
+        "mentions Bilbo (Sam <-[member]- Fellowship -[member]-> Frodo -[heir]-> Bilbo)"
+    );
+    temp_path.close().unwrap();
+}
@@ -0,0 +1,121 @@
+use lorecore::{
+    sql::{
+        lore_database::LoreDatabase,
+        validation::{ReferenceSource, UnknownEntityReference},
+    },
+    types::*,
+};
+use std::path::PathBuf;
+use tempfile::NamedTempFile;
+
+fn create_example() -> (tempfile::TempPath, LoreDatabase) {
+    let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+    let db = LoreDatabase::open(path_in.clone()).unwrap();
+
+    db.write_entity_columns(vec![
+        EntityColumn {
+            label: "Gandalf_the_Grey".into(),
+            descriptor: "Friends".into(),
+            description: "\\entityref{Frodo} and \\entityref{Sarumann}".into(),
+        },
+        EntityColumn {
+            label: "Frodo".into(),
+            descriptor: "Home".into(),
+            description: "The Shire".into(),
+        },
+        EntityColumn {
+            label: "Saruman".into(),
+            descriptor: "Home".into(),
+            description: "Isengard".into(),
+        },
+    ])
+    .unwrap();
+    db.write_history_items(vec![
+        HistoryItem::new(
+            1.into(),
+            3019.into(),
+            Day::NONE,
+            "\\entityref{Gandalf_the_Gray} returned.".into(),
+        )
+        .with_properties(
+            "{\"additional_concerns\":[\"\\\\entityref{Frodo}\"]}"
+                .try_into()
+                .unwrap(),
+        ),
+        HistoryItem::new(
+            2.into(),
+            3019.into(),
+            Day::NONE,
+            "\\entityref{Frodo} met \\entityref{Bilbo}.".into(),
+        ),
+    ])
+    .unwrap();
+    (temp_path, db)
+}
+
+#[test]
+fn suggest_labels_ranks_closest_first() {
+    let (temp_path, db) = create_example();
+
+    let suggestions = db
+        .suggest_labels("saruman", 5, Visibility::AuthorOnly)
+        .unwrap();
+    let labels: Vec<_> = suggestions.iter().map(|m| m.candidate.clone()).collect();
+    assert_eq!(labels, vec!["Saruman".into()]);
+    assert_eq!(suggestions[0].score, 1.0);
+
+    let suggestions = db
+        .suggest_labels("gandalf the grey", 5, Visibility::AuthorOnly)
+        .unwrap();
+    assert_eq!(suggestions[0].candidate, "Gandalf_the_Grey".into());
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn suggest_descriptors_ranks_closest_first() {
+    let (temp_path, db) = create_example();
+
+    let suggestions = db
+        .suggest_descriptors("hoem", 5, Visibility::AuthorOnly)
+        .unwrap();
+    assert_eq!(suggestions.len(), 1);
+    assert_eq!(suggestions[0].candidate, "Home".into());
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn unknown_entity_references_come_with_suggestions() {
+    let (temp_path, db) = create_example();
+
+    let unknown = db.find_unknown_entity_references().unwrap();
+
+    assert_eq!(
+        unknown,
+        vec![
+            UnknownEntityReference {
+                label: "Sarumann".into(),
+                source: ReferenceSource::EntityColumn("Gandalf_the_Grey".into(), "Friends".into()),
+                suggestions: vec!["Saruman".into()],
+            },
+            UnknownEntityReference {
+                label: "Gandalf_the_Gray".into(),
+                source: ReferenceSource::HistoryItem(1.into()),
+                suggestions: vec!["Gandalf_the_Grey".into()],
+            },
+            UnknownEntityReference {
+                label: "Bilbo".into(),
+                source: ReferenceSource::HistoryItem(2.into()),
+                suggestions: vec![],
+            },
+        ]
+    );
+    assert_eq!(
+        unknown[1].to_string(),
+        "Unknown entity \"Gandalf_the_Gray\" referenced in history item 1. Did you mean \"Gandalf_the_Grey\"?"
+    );
+
+    temp_path.close().unwrap();
+}
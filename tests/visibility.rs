@@ -0,0 +1,384 @@
+use lorecore::{
+    sql::{
+        era::render_timeline,
+        lore_database::LoreDatabase,
+        search_params::{
+            EntityColumnSearchParams, HistoryItemSearchParams, RelationshipSearchParams,
+            RelationshipTraversalParams,
+        },
+    },
+    types::*,
+};
+use std::path::PathBuf;
+use tempfile::NamedTempFile;
+
+mod common;
+use common::*;
+
+fn create_example() -> (tempfile::TempPath, LoreDatabase) {
+    let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+    let db = LoreDatabase::open(path_in.clone()).unwrap();
+
+    db.write_entity_columns(vec![
+        col(
+            "Gondor",
+            "ruler",
+            "\\entityref{King}, advised by \\entityref{Spy}",
+        ),
+        col("King", "title", "King of \\entityref{Gondor}"),
+        col("King", "weakness", "Fears the dark"),
+        col("Spy", "identity", "A servant of the enemy"),
+    ])
+    .unwrap();
+    db.write_relationships(vec![
+        EntityRelationship::new("Gondor".into(), "King".into(), "ruler".into()),
+        EntityRelationship::new("Gondor".into(), "Spy".into(), "advisor".into()),
+        EntityRelationship::new("King".into(), "Gondor".into(), "heir".into()),
+    ])
+    .unwrap();
+    db.write_history_items(vec![
+        item_with_properties(
+            1,
+            300,
+            0,
+            "\\entityref{Spy} arrives in \\entityref{Gondor}.",
+            "",
+        ),
+        item_with_properties(
+            2,
+            300,
+            0,
+            "The \\entityref{King} falls ill.",
+            "{\"is_secret\":true}",
+        ),
+        item_with_properties(
+            3,
+            300,
+            0,
+            "Rumours spread.",
+            "{\"visibility\":\"player_known\"}",
+        ),
+    ])
+    .unwrap();
+
+    db.change_entity_column_visibility((&"Spy".into(), &"identity".into()), Visibility::GmOnly)
+        .unwrap();
+    db.change_entity_column_visibility(
+        (&"King".into(), &"weakness".into()),
+        Visibility::AuthorOnly,
+    )
+    .unwrap();
+    db.change_relationship_visibility(
+        &EntityRelationship::new("King".into(), "Gondor".into(), "heir".into()),
+        Visibility::PlayerKnown,
+    )
+    .unwrap();
+    (temp_path, db)
+}
+
+fn timestamps(items: &[HistoryItem]) -> Vec<i64> {
+    items.iter().map(|item| item.timestamp.to_int()).collect()
+}
+
+#[test]
+fn default_reads_see_everything() {
+    let (temp_path, db) = create_example();
+    assert_eq!(
+        db.read_entity_columns(EntityColumnSearchParams::empty())
+            .unwrap()
+            .len(),
+        4
+    );
+    assert_eq!(
+        db.count_relationships(RelationshipSearchParams::empty())
+            .unwrap(),
+        3
+    );
+    assert_eq!(
+        timestamps(
+            &db.read_history_items(HistoryItemSearchParams::empty())
+                .unwrap()
+        ),
+        vec![1, 2, 3]
+    );
+    assert_eq!(db.read_hidden_labels(Visibility::GmOnly).unwrap().len(), 0);
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn public_audience_sees_redacted_world() {
+    let (temp_path, db) = create_example();
+    let audience = Visibility::Public;
+    let cols = db
+        .read_entity_columns(EntityColumnSearchParams::empty().for_audience(audience))
+        .unwrap();
+    assert_eq!(
+        cols,
+        vec![
+            col(
+                "Gondor",
+                "ruler",
+                "\\entityref{King}, advised by [redacted]"
+            ),
+            col("King", "title", "King of \\entityref{Gondor}"),
+        ]
+    );
+    assert_eq!(
+        db.count_entity_columns(EntityColumnSearchParams::empty().for_audience(audience))
+            .unwrap(),
+        2
+    );
+
+    let rels = db
+        .read_relationships(RelationshipSearchParams::empty().for_audience(audience))
+        .unwrap();
+    assert_eq!(
+        rels,
+        vec![EntityRelationship::new(
+            "Gondor".into(),
+            "King".into(),
+            "ruler".into()
+        )]
+    );
+    assert_eq!(
+        db.count_relationships(RelationshipSearchParams::empty().for_audience(audience))
+            .unwrap(),
+        1
+    );
+
+    let items = db
+        .read_history_items(HistoryItemSearchParams::empty().for_audience(audience))
+        .unwrap();
+    assert_eq!(timestamps(&items), vec![1]);
+    assert_eq!(
+        items[0].content.to_str(),
+        "[redacted] arrives in \\entityref{Gondor}."
+    );
+    assert_eq!(
+        db.count_history_items(HistoryItemSearchParams::empty().for_audience(audience))
+            .unwrap(),
+        1
+    );
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn higher_audiences_see_more() {
+    let (temp_path, db) = create_example();
+    let players = HistoryItemSearchParams::empty().for_audience(Visibility::PlayerKnown);
+    assert_eq!(
+        timestamps(&db.read_history_items(players).unwrap()),
+        vec![1, 3]
+    );
+    let gms = HistoryItemSearchParams::empty().for_audience(Visibility::GmOnly);
+    let items = db.read_history_items(gms).unwrap();
+    assert_eq!(timestamps(&items), vec![1, 2, 3]);
+    assert_eq!(
+        items[0].content.to_str(),
+        "\\entityref{Spy} arrives in \\entityref{Gondor}."
+    );
+    assert_eq!(
+        db.count_relationships(
+            RelationshipSearchParams::empty().for_audience(Visibility::PlayerKnown)
+        )
+        .unwrap(),
+        2
+    );
+    assert_eq!(
+        db.read_entity(&"King".into(), Visibility::GmOnly)
+            .unwrap()
+            .columns
+            .len(),
+        1
+    );
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn hidden_entities_cannot_be_read() {
+    let (temp_path, db) = create_example();
+    assert_eq!(
+        db.read_hidden_labels(Visibility::PlayerKnown).unwrap(),
+        ["Spy".into()].into()
+    );
+    assert!(db.read_entity(&"Spy".into(), Visibility::Public).is_err());
+    let gondor = db
+        .read_entity(&"Gondor".into(), Visibility::Public)
+        .unwrap();
+    assert!(!gondor
+        .outgoing_relationships
+        .contains_key(&"advisor".into()));
+    assert_eq!(
+        gondor.columns[0].description.to_str(),
+        "\\entityref{King}, advised by [redacted]"
+    );
+    assert!(db
+        .read_timeline(
+            &"Spy".into(),
+            RelationshipTraversalParams::empty(),
+            Visibility::Public
+        )
+        .unwrap()
+        .is_empty());
+
+    let world = db.world_at(300.into(), Visibility::Public).unwrap();
+    assert_eq!(world.columns.len(), 2);
+    assert_eq!(world.relationships.len(), 1);
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn exports_are_redacted() {
+    let (temp_path, db) = create_example();
+    let items = db
+        .read_dated_history_items(
+            HistoryItemSearchParams::empty().for_audience(Visibility::PlayerKnown),
+            None,
+            None,
+            Markup::Markdown,
+        )
+        .unwrap();
+    let rendered = render_timeline(&items, Markup::Markdown);
+    assert!(rendered.contains("[redacted] arrives"));
+    assert!(rendered.contains("Rumours spread."));
+    assert!(!rendered.contains("falls ill"));
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn visibilities_can_be_changed() {
+    let (temp_path, db) = create_example();
+    assert_eq!(
+        db.read_entity_column_visibility((&"Spy".into(), &"identity".into()))
+            .unwrap(),
+        Visibility::GmOnly
+    );
+    assert_eq!(
+        db.read_relationship_visibility(&EntityRelationship::new(
+            "Gondor".into(),
+            "King".into(),
+            "ruler".into()
+        ))
+        .unwrap(),
+        Visibility::Public
+    );
+    assert!(db
+        .change_entity_column_visibility((&"Nobody".into(), &"x".into()), Visibility::Public)
+        .is_err());
+
+    db.relabel_entity(&"Spy".into(), &"Agent".into()).unwrap();
+    assert_eq!(
+        db.read_entity_column_visibility((&"Agent".into(), &"identity".into()))
+            .unwrap(),
+        Visibility::GmOnly
+    );
+
+    db.change_history_item_visibility(2.into(), Visibility::Public)
+        .unwrap();
+    let items = db
+        .read_history_items(HistoryItemSearchParams::empty().for_audience(Visibility::Public))
+        .unwrap();
+    assert_eq!(timestamps(&items), vec![1, 2]);
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn timed_columns_respect_the_audience() {
+    let (temp_path, db) = create_example();
+    let timed = |label: &str, descriptor: &str, description: &str| TimedEntityColumn {
+        label: label.into(),
+        descriptor: descriptor.into(),
+        description: description.into(),
+        start: Some(100.into()),
+        end: None,
+    };
+    db.write_timed_entity_columns(vec![
+        timed("Gondor", "ruler", "\\entityref{Spy}"),
+        timed("King", "weakness", "Fears spiders"),
+        timed("Spy", "identity", "A servant of the enemy"),
+    ])
+    .unwrap();
+
+    let params = |audience| EntityColumnSearchParams::empty().for_audience(audience);
+    let cols = db
+        .read_timed_entity_columns(params(Visibility::Public))
+        .unwrap();
+    assert_eq!(cols.len(), 1);
+    assert_eq!(cols[0].description, "[redacted]".into());
+    assert_eq!(
+        db.read_timed_entity_columns(params(Visibility::GmOnly))
+            .unwrap()
+            .len(),
+        2
+    );
+    assert_eq!(
+        db.read_timed_entity_columns(params(Visibility::AuthorOnly))
+            .unwrap()
+            .len(),
+        3
+    );
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn derived_reads_respect_the_audience() {
+    let (temp_path, db) = create_example();
+    db.write_history_items(vec![
+        item_with_properties(4, 200, 0, "", "{\"births\":[\"Spy\"]}"),
+        item(5, 100, 0, "\\entityref{Spy} is mentioned early."),
+    ])
+    .unwrap();
+    db.write_entity_tags(&"Spy".into(), vec![Tag::try_from("enemy").unwrap()])
+        .unwrap();
+    db.write_history_item_tags(2.into(), vec![Tag::try_from("illness").unwrap()])
+        .unwrap();
+    let public = Visibility::Public;
+    let author = Visibility::AuthorOnly;
+    let spy: Label = "Spy".into();
+
+    let suggested = |audience| {
+        db.suggest_labels("Spy", 3, audience)
+            .unwrap()
+            .into_iter()
+            .map(|suggestion| suggestion.candidate)
+            .collect::<Vec<_>>()
+    };
+    assert!(!suggested(public).contains(&spy));
+    assert!(suggested(author).contains(&spy));
+
+    assert!(db.read_references_to(&spy, public).unwrap().is_empty());
+    assert!(!db.read_references_to(&spy, author).unwrap().is_empty());
+    assert_eq!(
+        db.read_references_from_entity(&"Gondor".into(), public)
+            .unwrap(),
+        vec!["King".into()]
+    );
+    assert!(db
+        .read_history_items_referencing(&"King".into(), public)
+        .unwrap()
+        .is_empty());
+
+    assert!(!db.read_lifespans(public).unwrap().contains_key(&spy));
+    assert!(db.read_lifespans(author).unwrap().contains_key(&spy));
+    let params = RelationshipTraversalParams::empty;
+    assert!(db
+        .read_ancestors(&spy, params(), public)
+        .unwrap()
+        .is_empty());
+    assert!(!db
+        .read_ancestors(&spy, params(), author)
+        .unwrap()
+        .is_empty());
+    assert!(db.check_timeline(params(), public).unwrap().is_empty());
+    assert!(!db.check_timeline(params(), author).unwrap().is_empty());
+
+    assert!(db.read_entity_tags(&spy, public).unwrap().is_empty());
+    assert!(db
+        .read_history_item_tags(2.into(), public)
+        .unwrap()
+        .is_empty());
+    assert!(db.read_tags(public).unwrap().is_empty());
+    assert_eq!(db.read_tags(author).unwrap().len(), 2);
+    temp_path.close().unwrap();
+}
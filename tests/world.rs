@@ -0,0 +1,167 @@
+use lorecore::{
+    sql::{lore_database::LoreDatabase, search_params::EntityColumnSearchParams},
+    types::*,
+};
+use std::path::PathBuf;
+use tempfile::NamedTempFile;
+
+fn timed(
+    descriptor: &str,
+    description: &str,
+    start: Option<i32>,
+    end: Option<i32>,
+) -> TimedEntityColumn {
+    TimedEntityColumn {
+        label: "minas_tirith".into(),
+        descriptor: descriptor.into(),
+        description: description.into(),
+        start: start.map(|year| year.into()),
+        end: end.map(|year| year.into()),
+    }
+}
+
+fn create_example() -> (tempfile::TempPath, LoreDatabase) {
+    let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+    let path_in: PathBuf = temp_path.as_os_str().into();
+    let db = LoreDatabase::open(path_in.clone()).unwrap();
+
+    db.write_entity_columns(vec![
+        EntityColumn {
+            label: "minas_tirith".into(),
+            descriptor: "ruler".into(),
+            description: "nobody".into(),
+        },
+        EntityColumn {
+            label: "minas_tirith".into(),
+            descriptor: "walls".into(),
+            description: "seven".into(),
+        },
+    ])
+    .unwrap();
+    db.write_timed_entity_columns(vec![
+        timed("ruler", "denethor", Some(100), Some(150)),
+        timed("ruler", "aragorn", Some(151), None),
+    ])
+    .unwrap();
+    db.write_relationships(vec![EntityRelationship::new(
+        "aragorn".into(),
+        "minas_tirith".into(),
+        "king".into(),
+    )
+    .valid_between(Some((151.into(), Day::NONE)), None)])
+        .unwrap();
+    (temp_path, db)
+}
+
+fn ruler(db: &LoreDatabase, year: i32) -> String {
+    db.world_at(year.into(), Visibility::AuthorOnly)
+        .unwrap()
+        .columns
+        .into_iter()
+        .find(|col| col.descriptor.to_str() == "ruler")
+        .unwrap()
+        .description
+        .to_string()
+}
+
+#[test]
+fn world_state_changes_over_time() {
+    let (temp_path, db) = create_example();
+
+    assert_eq!(ruler(&db, 50), "nobody");
+    assert_eq!(ruler(&db, 100), "denethor");
+    assert_eq!(ruler(&db, 150), "denethor");
+    assert_eq!(ruler(&db, 151), "aragorn");
+    assert_eq!(ruler(&db, 3000), "aragorn");
+
+    let world = db.world_at(120.into(), Visibility::AuthorOnly).unwrap();
+    assert_eq!(world.columns.len(), 2);
+    assert!(world.relationships.is_empty());
+    let world = db.world_at(200.into(), Visibility::AuthorOnly).unwrap();
+    assert_eq!(world.relationships.len(), 1);
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn overlapping_timed_columns_are_rejected() {
+    let (temp_path, db) = create_example();
+
+    assert!(db
+        .write_timed_entity_columns(vec![timed("ruler", "isildur", Some(140), Some(145))])
+        .is_err());
+    assert!(db
+        .write_timed_entity_columns(vec![
+            timed("steward", "húrin", None, Some(10)),
+            timed("steward", "denethor", Some(10), None),
+        ])
+        .is_err());
+    assert_eq!(
+        db.read_timed_entity_columns(EntityColumnSearchParams::empty())
+            .unwrap()
+            .len(),
+        2
+    );
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn timed_columns_follow_their_entity() {
+    let (temp_path, db) = create_example();
+
+    db.delete_timed_entity_column((&"minas_tirith".into(), &"ruler".into()), Some(100.into()))
+        .unwrap();
+    assert_eq!(ruler(&db, 120), "nobody");
+
+    db.relabel_entity(&"minas_tirith".into(), &"white_city".into())
+        .unwrap();
+    let cols = db
+        .read_timed_entity_columns(EntityColumnSearchParams::empty())
+        .unwrap();
+    assert_eq!(cols[0].label, "white_city".into());
+
+    db.delete_entity("white_city".into()).unwrap();
+    assert!(db
+        .read_timed_entity_columns(EntityColumnSearchParams::empty())
+        .unwrap()
+        .is_empty());
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn inverted_timed_columns_are_rejected() {
+    let (temp_path, db) = create_example();
+
+    assert!(db
+        .write_timed_entity_columns(vec![timed("steward", "húrin", Some(20), Some(10))])
+        .is_err());
+    assert!(db
+        .write_timed_entity_columns(vec![timed("steward", "húrin", Some(10), Some(10))])
+        .is_ok());
+
+    temp_path.close().unwrap();
+}
+
+#[test]
+fn timed_columns_follow_their_column() {
+    let (temp_path, db) = create_example();
+
+    db.change_entity_descriptor((&"minas_tirith".into(), "ruler".into()), &"lord".into())
+        .unwrap();
+    let cols = db
+        .read_timed_entity_columns(EntityColumnSearchParams::empty())
+        .unwrap();
+    assert_eq!(cols.len(), 2);
+    assert!(cols.iter().all(|col| col.descriptor == "lord".into()));
+
+    db.delete_entity_column(("minas_tirith".into(), "lord".into()))
+        .unwrap();
+    assert!(db
+        .read_timed_entity_columns(EntityColumnSearchParams::empty())
+        .unwrap()
+        .is_empty());
+
+    temp_path.close().unwrap();
+}